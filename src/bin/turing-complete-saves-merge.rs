@@ -0,0 +1,71 @@
+//! A git merge driver for saves.
+//!
+//! Configure it with:
+//!
+//! ```sh
+//! git config merge.turing-complete.name 'Turing Complete saves'
+//! git config merge.turing-complete.driver 'turing-complete-saves-merge %O %A %B %P'
+//! echo 'circuit.data merge=turing-complete' >> .gitattributes
+//! ```
+//!
+//! The merged save is written over ours. If there were conflicts, they are printed and the driver exits with 1
+//! so that git marks the file as conflicted, but the merged save is still written with ours kept for every conflict.
+
+use turing_complete_saves_parser::{
+	Save,
+	merge,
+};
+
+fn main() {
+	let mut args = std::env::args_os();
+	let argv0 = args.next().unwrap_or_else(|| env!("CARGO_BIN_NAME").into());
+	let args: Vec<std::path::PathBuf> = args.map(Into::into).collect();
+	let (base_path, ours_path, theirs_path, path) = match &args[..] {
+		[base, ours, theirs] => (base, ours, theirs, ours),
+		[base, ours, theirs, path] => (base, ours, theirs, path),
+		_ => {
+			eprintln!("Usage: {} <base> <ours> <theirs> [<path>]", argv0.to_string_lossy());
+			std::process::exit(2);
+		},
+	};
+
+	match run(base_path, ours_path, theirs_path) {
+		Ok(conflicts) => {
+			for conflict in &conflicts {
+				eprintln!("{}: conflict: {conflict}", path.display());
+			}
+			if !conflicts.is_empty() {
+				std::process::exit(1);
+			}
+		},
+
+		Err(err) => {
+			use std::fmt::Write;
+
+			let mut message = format!("{}: {err}", path.display());
+			let mut source = err.source();
+			while let Some(err) = source {
+				_ = write!(message, ": {err}");
+				source = err.source();
+			}
+			eprintln!("{message}");
+			std::process::exit(1);
+		},
+	}
+}
+
+fn run(base_path: &std::path::Path, ours_path: &std::path::Path, theirs_path: &std::path::Path) -> Result<Vec<merge::Conflict>, Box<dyn std::error::Error>> {
+	let read = |path: &std::path::Path| -> Result<_, Box<dyn std::error::Error>> {
+		match turing_complete_saves_parser::load(path)? {
+			Save::V10(circuit) => Ok(circuit),
+			_ => Err(format!("{} is not a version 10 save, only version 10 saves can be merged", path.display()).into()),
+		}
+	};
+	let base = read(base_path)?;
+	let ours = read(ours_path)?;
+	let theirs = read(theirs_path)?;
+
+	let merge::Merge { circuit, conflicts } = merge::merge(&base, &ours, &theirs);
+	std::fs::write(ours_path, circuit.encode_final())?;
+	Ok(conflicts)
+}
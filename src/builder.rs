@@ -0,0 +1,322 @@
+//! Construction of v10 circuits from code.
+//!
+//! [`CircuitBuilder::add`] places a component and returns a [`ComponentHandle`] whose [`ComponentHandle::input`] and [`ComponentHandle::out`]
+//! give the positions of its pins, laid out as in [`crate::layout`]. [`CircuitBuilder::wire`] then connects two points with a wire
+//! that goes diagonally until it is level with its end and straight the rest of the way, and [`CircuitBuilder::route`] connects them
+//! with a wire that goes around components and other wires, using [`crate::route`].
+//!
+//! Components get `permanent_id`s counting up from 1 in the order they are added, a `word_size` that depends on their kind
+//! (see [`default_word_size`]), and zero or empty for everything else.
+
+use crate::{
+	circuit,
+	layout,
+	route,
+	v10,
+};
+
+/// The longest a single [`v10::WireSegment`] can be, since its length is stored in 5 bits.
+pub const MAX_SEGMENT_LENGTH: u8 = 31;
+
+#[derive(Debug)]
+pub struct CircuitBuilder {
+	description: String,
+	clock_speed: u64,
+	dependencies: Vec<i64>,
+	components: Vec<v10::Component<'static>>,
+	wires: Vec<v10::Wire<'static>>,
+	routed_wires: Vec<(v10::Point, v10::Point)>,
+}
+
+/// A component that was added to a [`CircuitBuilder`].
+#[derive(Clone, Copy, Debug)]
+pub struct ComponentHandle {
+	pub permanent_id: u64,
+	pub position: v10::Point,
+	pub rotation: u8,
+}
+
+/// Shorthand for a [`v10::Point`].
+pub fn at(x: i16, y: i16) -> v10::Point {
+	v10::Point { x, y }
+}
+
+impl CircuitBuilder {
+	#[allow(clippy::new_without_default)]
+	pub fn new() -> Self {
+		Self {
+			description: String::new(),
+			clock_speed: 0,
+			dependencies: vec![],
+			components: vec![],
+			wires: vec![],
+			routed_wires: vec![],
+		}
+	}
+
+	pub fn description(&mut self, description: impl Into<String>) -> &mut Self {
+		self.description = description.into();
+		self
+	}
+
+	pub fn clock_speed(&mut self, clock_speed: u64) -> &mut Self {
+		self.clock_speed = clock_speed;
+		self
+	}
+
+	/// Adds a component of the given kind at the given position.
+	///
+	/// Use [`CircuitBuilder::add_custom`] for custom components.
+	pub fn add(&mut self, kind: v10::ComponentKind, position: v10::Point) -> ComponentHandle {
+		self.add_rotated(kind, position, 0)
+	}
+
+	/// Adds a component of the given kind at the given position, turned a quarter turn clockwise `rotation` times.
+	pub fn add_rotated(&mut self, kind: v10::ComponentKind, position: v10::Point, rotation: u8) -> ComponentHandle {
+		assert!(!matches!(kind, v10::ComponentKind::Custom), "custom components must be added with add_custom");
+		self.push_component(kind, position, rotation, None)
+	}
+
+	/// Adds an instance of the custom component with the given `custom_id`, and records it as a dependency of the circuit.
+	pub fn add_custom(&mut self, custom_id: i64, position: v10::Point, rotation: u8) -> ComponentHandle {
+		if !self.dependencies.contains(&custom_id) {
+			self.dependencies.push(custom_id);
+		}
+		let custom_data = v10::CustomCompData { id: custom_id, static_states: vec![].into() };
+		self.push_component(v10::ComponentKind::Custom, position, rotation, Some(custom_data))
+	}
+
+	fn push_component(
+		&mut self,
+		kind: v10::ComponentKind,
+		position: v10::Point,
+		rotation: u8,
+		custom_data: Option<v10::CustomCompData<'static>>,
+	) -> ComponentHandle {
+		let permanent_id = self.components.len() as u64 + 1;
+		self.components.push(v10::Component {
+			kind,
+			position,
+			rotation,
+			permanent_id,
+			custom_string: "".into(),
+			settings: vec![].into(),
+			buffer_size: 0,
+			ui_order: 0,
+			word_size: default_word_size(kind),
+			linked_components: vec![].into(),
+			selected_programs: v10::AssemblerInfo { programs: vec![].into() },
+			custom_data,
+		});
+		ComponentHandle { permanent_id, position, rotation }
+	}
+
+	/// The component that was added as `handle`, to set any other fields.
+	///
+	/// Changing its position or rotation does not move the pins of `handle` or the wires already connected to them.
+	pub fn component_mut(&mut self, handle: ComponentHandle) -> &mut v10::Component<'static> {
+		let index = usize::try_from(handle.permanent_id - 1).expect("permanent_id is an index into components");
+		&mut self.components[index]
+	}
+
+	/// Adds a wire from `from` to `to`. Nothing is added if they are the same point.
+	pub fn wire(&mut self, from: v10::Point, to: v10::Point) -> &mut Self {
+		if from != to {
+			self.wires.push(v10::Wire {
+				color: 0,
+				comment: "".into(),
+				start: from,
+				segments: v10::WireSegments::Segments(route_direct(from, to).into()),
+			});
+		}
+		self
+	}
+
+	/// Adds a wire from `from` to `to` that goes around components and other wires.
+	///
+	/// These wires are routed by [`CircuitBuilder::build`] once all components and other wires are in place, in the order they were added.
+	pub fn route(&mut self, from: v10::Point, to: v10::Point) -> &mut Self {
+		if from != to {
+			self.routed_wires.push((from, to));
+		}
+		self
+	}
+
+	pub fn build(&self) -> v10::CircuitData<'static> {
+		let mut circuit = v10::CircuitData {
+			custom_id: 0,
+			hub_id: 0,
+			gate: 0,
+			delay: 0,
+			menu_visible: false,
+			clock_speed: self.clock_speed,
+			dependencies: self.dependencies.clone().into(),
+			description: self.description.clone().into(),
+			camera_position: at(0, 0),
+			synced: v10::SyncState::Unsynced,
+			player_data: vec![].into(),
+			hub_description: "".into(),
+			components: self.components.clone().into(),
+			wires: self.wires.clone().into(),
+		};
+
+		if !self.routed_wires.is_empty() {
+			let mut router = route::Router::new(&circuit);
+			let mut wires = self.wires.clone();
+			for &(from, to) in &self.routed_wires {
+				wires.push(router.route(from, to));
+			}
+			circuit.wires = wires.into();
+		}
+
+		circuit
+	}
+}
+
+impl ComponentHandle {
+	/// The position of input pin `index`.
+	pub fn input(self, index: i16) -> v10::Point {
+		self.pin(layout::PinDirection::Input, index)
+	}
+
+	/// The position of output pin `index`.
+	pub fn out(self, index: i16) -> v10::Point {
+		self.pin(layout::PinDirection::Output, index)
+	}
+
+	fn pin(self, direction: layout::PinDirection, index: i16) -> v10::Point {
+		layout::pin_position(self.position.into(), self.rotation, direction, index).into()
+	}
+}
+
+/// The `word_size` that [`CircuitBuilder`] gives components of the given kind.
+///
+/// Kinds with a pin as wide as the component's `word_size` get 8. Other kinds get the width of their widest pin,
+/// so one-bit kinds such as `NotBit` and `LevelInput1` get 1. Kinds that [`crate::layout`] does not know get 8.
+pub fn default_word_size(kind: v10::ComponentKind) -> i64 {
+	let Some(layout) = layout::v10_layout(kind) else { return 8; };
+	let pins = layout.inputs.iter().chain(layout.outputs);
+	if pins.clone().any(|width| matches!(width, layout::PinWidth::Word)) {
+		return 8;
+	}
+	pins.map(|width| i64::from(width.bits(0))).max().unwrap_or(1).max(1)
+}
+
+/// The segments of a wire that goes diagonally from `from` until it is level with `to`, then straight to `to`.
+fn route_direct(from: v10::Point, to: v10::Point) -> Vec<v10::WireSegment> {
+	let dx = i32::from(to.x) - i32::from(from.x);
+	let dy = i32::from(to.y) - i32::from(from.y);
+	let diagonal = dx.abs().min(dy.abs());
+
+	let mut segments = vec![];
+	let diagonal_direction = match (dx.signum(), dy.signum()) {
+		(1, 1) => Some(circuit::WireDirection::DownRight),
+		(-1, 1) => Some(circuit::WireDirection::DownLeft),
+		(-1, -1) => Some(circuit::WireDirection::UpLeft),
+		(1, -1) => Some(circuit::WireDirection::UpRight),
+		_ => None,
+	};
+	if let Some(direction) = diagonal_direction {
+		push_run(&mut segments, direction, diagonal.unsigned_abs());
+	}
+
+	let (straight_direction, straight) =
+		if dx.abs() > dy.abs() {
+			(if dx > 0 { circuit::WireDirection::Right } else { circuit::WireDirection::Left }, dx.abs() - diagonal)
+		}
+		else {
+			(if dy > 0 { circuit::WireDirection::Down } else { circuit::WireDirection::Up }, dy.abs() - diagonal)
+		};
+	push_run(&mut segments, straight_direction, straight.unsigned_abs());
+
+	segments.push(v10::WireSegment { length: 0, direction: v10::WireDirection::Right });
+	segments
+}
+
+/// Appends `length` cells in `direction` to `segments`, split into segments of at most [`MAX_SEGMENT_LENGTH`].
+pub(crate) fn push_run(segments: &mut Vec<v10::WireSegment>, direction: circuit::WireDirection, mut length: u32) {
+	while length > 0 {
+		let segment_length = length.min(MAX_SEGMENT_LENGTH.into());
+		length -= segment_length;
+		segments.push(v10::WireSegment {
+			length: segment_length.try_into().expect("segment_length is at most MAX_SEGMENT_LENGTH"),
+			direction: direction.into(),
+		});
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		Either,
+		circuit::WireDirection,
+		v10,
+	};
+	use super::{CircuitBuilder, at};
+
+	fn segments(wire: &v10::Wire<'_>) -> Vec<(u8, WireDirection)> {
+		let v10::WireSegments::Segments(segments) = &wire.segments else { panic!("expected segments") };
+		segments.iter().map(Either::into_inner).map(|segment| (segment.length, segment.direction.into())).collect()
+	}
+
+	#[test]
+	fn add() {
+		let mut builder = CircuitBuilder::new();
+		let input = builder.add(v10::ComponentKind::LevelInput1, at(0, 0));
+		let not = builder.add_rotated(v10::ComponentKind::NotBit, at(6, 0), 1);
+		let add = builder.add(v10::ComponentKind::Add, at(12, 0));
+		let maker = builder.add(v10::ComponentKind::MakerBit4, at(18, 0));
+		let custom = builder.add_custom(77, at(24, 0), 0);
+		let circuit = builder.build();
+
+		assert_eq!([input, not, add, maker, custom].map(|handle| handle.permanent_id), [1, 2, 3, 4, 5]);
+		assert_eq!((not.position, not.rotation), (at(6, 0), 1));
+
+		let components: Vec<_> = circuit.components.iter().map(Either::into_inner).collect();
+		assert_eq!(components.iter().map(|component| component.permanent_id).collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+		assert_eq!(components.iter().map(|component| component.word_size).collect::<Vec<_>>(), [1, 1, 8, 4, 8]);
+		assert_eq!(components[1].rotation, 1);
+		assert_eq!(components[4].custom_data.as_ref().map(|custom_data| custom_data.id), Some(77));
+		assert_eq!(circuit.dependencies.iter().map(Either::into_inner).collect::<Vec<_>>(), [77]);
+	}
+
+	#[test]
+	fn wire() {
+		let mut builder = CircuitBuilder::new();
+		builder
+			.wire(at(0, 0), at(5, 2))
+			.wire(at(0, 0), at(0, -40))
+			.wire(at(3, 3), at(3, 3));
+		let circuit = builder.build();
+
+		let wires: Vec<_> = circuit.wires.iter().map(Either::into_inner).collect();
+		assert_eq!(wires.len(), 2, "a wire from a point to itself is not added");
+		assert_eq!(wires[0].start, at(0, 0));
+		assert_eq!(segments(&wires[0]), [
+			(2, WireDirection::DownRight),
+			(3, WireDirection::Right),
+			(0, WireDirection::Right),
+		]);
+		assert_eq!(segments(&wires[1]), [
+			(31, WireDirection::Up),
+			(9, WireDirection::Up),
+			(0, WireDirection::Right),
+		]);
+	}
+
+	#[test]
+	fn build() {
+		let mut builder = CircuitBuilder::new();
+		builder.description("desc").clock_speed(100);
+		let input = builder.add(v10::ComponentKind::LevelInput1, at(0, 0));
+		builder.component_mut(input).custom_string = "in".into();
+		let circuit = builder.build();
+
+		assert_eq!(circuit.description, "desc");
+		assert_eq!(circuit.clock_speed, 100);
+		assert_eq!(circuit.components.get(0).unwrap().into_inner().custom_string, "in");
+
+		// Building does not consume the builder, and builds the same circuit again.
+		assert_eq!(crate::Save::V10(builder.build()).encode_final(), crate::Save::V10(circuit).encode_final());
+	}
+}
@@ -0,0 +1,1480 @@
+//! A version-independent model of a circuit.
+//!
+//! [`Circuit`] holds the data that is common to every save version in a single shape,
+//! so that code which only cares about components and wires does not need to be written once per version.
+//! Fields that only exist in some versions are kept in [`ComponentExtra`] and [`WireExtra`],
+//! so converting a `vN::CircuitData` to a [`Circuit`] and back is lossless.
+//!
+//! Each version numbers component kinds differently and some kinds only exist in some versions. [`ComponentKind`] has the kinds
+//! of every version by name, and converting a circuit back to a version fails if it has a kind that the version does not have.
+
+use crate::{
+	Either,
+	Slice,
+	v6, v7, v8, v9, v10,
+};
+
+#[derive(Clone, Debug)]
+pub struct Circuit<'a> {
+	/// The save version this circuit was converted from, and which it converts back to.
+	pub version: u8,
+	pub custom_id: i64,
+	pub hub_id: u32,
+	pub gate: i64,
+	pub delay: i64,
+	pub menu_visible: bool,
+	pub clock_speed: u64,
+	pub dependencies: Vec<i64>,
+	pub description: std::borrow::Cow<'a, str>,
+	pub camera_position: Point,
+	pub synced: SyncState,
+	pub player_data: Vec<u8>,
+	pub hub_description: std::borrow::Cow<'a, str>,
+	pub components: Vec<Component<'a>>,
+	pub wires: Vec<Wire<'a>>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Point {
+	pub x: i16,
+	pub y: i16,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SyncState {
+	Unsynced,
+	Synced,
+	ChangedAfterSync,
+}
+
+#[derive(Clone, Debug)]
+pub struct Component<'a> {
+	pub kind: ComponentKind,
+	pub position: Point,
+	pub rotation: u8,
+	pub permanent_id: u64,
+	pub custom_string: std::borrow::Cow<'a, str>,
+	pub settings: Vec<u64>,
+	pub ui_order: i16,
+
+	/// For v6 saves, the word size that the v6 kind has in its name, as [`ComponentKind::from_v6`] maps it.
+	pub word_size: i64,
+
+	/// The `custom_id` of the circuit that a custom component is an instance of.
+	pub custom_id: Option<i64>,
+
+	pub extra: ComponentExtra<'a>,
+}
+
+/// Defines [`ComponentKind`] with the given variants, and conversions to and from the kinds of each version with the given names.
+macro_rules! component_kinds {
+	(
+		$(#[$meta:meta])*
+		[$($name:ident,)*]
+		$($version:ident = $version_number:literal [$($version_name:ident,)*],)*
+	) => {
+		$(#[$meta])*
+		#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+		pub enum ComponentKind {
+			$($name,)*
+		}
+
+		$(
+			impl From<$version::ComponentKind> for ComponentKind {
+				fn from(kind: $version::ComponentKind) -> Self {
+					match kind {
+						$($version::ComponentKind::$version_name => Self::$version_name,)*
+					}
+				}
+			}
+
+			impl TryFrom<ComponentKind> for $version::ComponentKind {
+				type Error = ConversionError;
+
+				fn try_from(kind: ComponentKind) -> Result<Self, Self::Error> {
+					match kind {
+						$(ComponentKind::$version_name => Ok(Self::$version_name),)*
+						kind => Err(ConversionError::UnsupportedKind { version: $version_number, kind }),
+					}
+				}
+			}
+		)*
+	};
+}
+
+component_kinds! {
+	/// The kind of a component, independent of the save version it was read from.
+	///
+	/// The variants are the kinds of v7 to v10 by name, followed by the kinds that only v6 has. A v6 kind that has an equivalent,
+	/// such as `Add8`, is that equivalent and a word size instead, as [`ComponentKind::from_v6`] maps it. v6's `DeletedN` kinds
+	/// are named `V6DeletedN`, since v10 has a `Deleted1` to `Deleted3` of its own.
+	///
+	/// The kinds of each version convert to this with `From`. The kinds of v7 to v10 convert back with `TryFrom`,
+	/// which fails for kinds that the version does not have. v6 kinds convert back with [`ComponentKind::to_v6`], which also needs the word size.
+	[
+		None, Off, On, NotBit, AndBit, And3Bit, NandBit, OrBit,
+		Or3Bit, NorBit, XorBit, XnorBit, SwitchBit, DelayLineBit, RegisterBit, FullAdder,
+		MakerBit8, SplitterBit8, NotWord, OrWord, AndWord, NandWord, NorWord, XorWord,
+		XnorWord, SwitchWord, Equal, LessU, LessS, Neg, Add, Mul,
+		Div, Lsl, Lsr, Rol, Ror, Asr, Counter, RegisterWord,
+		ImmRegisterWord, ImmDelayLineBit, Mux, Decoder1, Decoder2, Decoder3, Constant, SplitterWord2,
+		MakerWord2, FrontPanel, Assembler, Ssd, Ram, RamLatency, RamFast, DelayLineWord,
+		RamDualLoad, FileLoader, CcLevelOutput, LevelGate, LevelInput1, LevelInputWord, LevelInputSwitched, LevelInput2Pin,
+		LevelInput3Pin, LevelInput4Pin, LevelInputCustom, LevelInputArch, LevelOutput1, LevelOutputWord, LevelOutputSwitched, LevelOutput1Sum,
+		LevelOutput1Car, LevelOutput2Pin, LevelOutput3Pin, LevelOutput4Pin, LevelOutputArch, LevelOutputCounter, Custom, CcInput,
+		CcInputBuffer, CcOutput, ProbeMemoryBit, ProbeMemoryWord, ProbeWireBit, ProbeWireWord, ConfigDelay, Halt,
+		Console, SegmentDisplay, StaticValue, PixelScreen, Time, Keyboard, StaticEval, VerilogInput,
+		VerilogOutput, MakerWord4, MakerWord8, SplitterWord4, SplitterWord8, StaticIndexer, ImmProbeMemoryBit, ImmDelayLineWord,
+		Inc, CcLevelInputCustom, CcLevelInput, ImmRegisterBit, Mod, SplitterBit2, SplitterBit4, MakerBit2,
+		MakerBit4, ImmProbeMemoryWord, Concatenator2, Concatenator4, Concatenator8, StaticIndexerConfig, Rom, PunchCard,
+		RegisterWordConfig, DelayLineWordConfig, RamDualLoad1, RamDualLoad2, ImmStaticValue, Clz, Deleted3, LoadPort,
+		StorePort, Ctz, Deleted1, Deleted2,
+
+		Buffer1, VirtualCounter8, VirtualCounter64, VirtualRam8, V6Deleted0, V6Deleted1,
+		V6Deleted17, V6Deleted18, VirtualRegister8, VirtualRegister8red, VirtualRegister8redPlus, VirtualRegister64,
+		V6Deleted2, V6Deleted3, VirtualcomBitMemory, V6Deleted10, NoteSound, V6Deleted4,
+		V6Deleted5, WireCluster, LevelScreen, V6Deleted6, V6Deleted7, LevelInputConditions,
+		LevelInputCode, V6Deleted8, V6Deleted9, V6Deleted11, VirtualCustom, VirtualDelayLine1,
+		AndOrLatch, NandNandLatch, NorNorLatch, DotMatrixDisplay, V6Deleted12, V6Deleted13,
+		V6Deleted14, V6Deleted15, V6Deleted16, Buffer8, Buffer16, Buffer32,
+		Buffer64, VirtualRegister16, VirtualCounter16, VirtualRegister32, VirtualCounter32, VirtualRam,
+		VirtualcomRamLatency, VirtualcomRamFast, VirtualRom, SolutionRom, VirtualSolutionRom, VirtualDelayLine8,
+		VirtualDelayLine16, VirtualDelayLine32, VirtualDelayLine64, VirtualcomRamDualLoad, Hdd, VirtualHdd,
+		Network, IndexerBit, IndexerByte, SpriteDisplay, Clock, Bidirectional1,
+		VirtualBidirectional1, Bidirectional8, VirtualBidirectional8, Bidirectional16, VirtualBidirectional16, Bidirectional32,
+		VirtualBidirectional32, Bidirectional64, VirtualBidirectional64,
+	]
+
+	v7 = 7 [
+		None, Off, On, NotBit, AndBit, And3Bit, NandBit, OrBit,
+		Or3Bit, NorBit, XorBit, XnorBit, SwitchBit, DelayLineBit, RegisterBit, FullAdder,
+		MakerBit8, SplitterBit8, NotWord, OrWord, AndWord, NandWord, NorWord, XorWord,
+		XnorWord, SwitchWord, Equal, LessU, LessS, Neg, Add, Mul,
+		Div, Lsl, Lsr, Rol, Ror, Asr, Counter, RegisterWord,
+		ImmRegisterWord, ImmDelayLineBit, Mux, Decoder1, Decoder2, Decoder3, Constant, SplitterWord2,
+		MakerWord2, FrontPanel, Assembler, Ssd, Ram, RamLatency, RamFast, DelayLineWord,
+		RamDualLoad, FileLoader, CcLevelOutput, LevelGate, LevelInput1, LevelInputWord, LevelInputSwitched, LevelInput2Pin,
+		LevelInput3Pin, LevelInput4Pin, LevelInputCustom, LevelInputArch, LevelOutput1, LevelOutputWord, LevelOutputSwitched, LevelOutput1Sum,
+		LevelOutput1Car, LevelOutput2Pin, LevelOutput3Pin, LevelOutput4Pin, LevelOutputArch, LevelOutputCounter, Custom, CcInput,
+		CcInputBuffer, CcOutput, ProbeMemoryBit, ProbeMemoryWord, ProbeWireBit, ProbeWireWord, ConfigDelay, Halt,
+		Console, SegmentDisplay, StaticValue, PixelScreen, Time, Keyboard, StaticEval, VerilogInput,
+		VerilogOutput, MakerWord4, MakerWord8, SplitterWord4, SplitterWord8, StaticIndexer, ImmProbeMemoryBit, ImmDelayLineWord,
+		Inc, CcLevelInputCustom, CcLevelInput, ImmRegisterBit, Mod, SplitterBit2, SplitterBit4, MakerBit2,
+		MakerBit4, ImmProbeMemoryWord, Concatenator2, Concatenator4, Concatenator8, StaticIndexerConfig, Rom,
+	],
+
+	v8 = 8 [
+		None, Off, On, NotBit, AndBit, And3Bit, NandBit, OrBit,
+		Or3Bit, NorBit, XorBit, XnorBit, SwitchBit, DelayLineBit, RegisterBit, FullAdder,
+		MakerBit8, SplitterBit8, NotWord, OrWord, AndWord, NandWord, NorWord, XorWord,
+		XnorWord, SwitchWord, Equal, LessU, LessS, Neg, Add, Mul,
+		Div, Lsl, Lsr, Rol, Ror, Asr, Counter, RegisterWord,
+		ImmRegisterWord, ImmDelayLineBit, Mux, Decoder1, Decoder2, Decoder3, Constant, SplitterWord2,
+		MakerWord2, FrontPanel, Assembler, Ssd, Ram, RamLatency, RamFast, DelayLineWord,
+		RamDualLoad, FileLoader, CcLevelOutput, LevelGate, LevelInput1, LevelInputWord, LevelInputSwitched, LevelInput2Pin,
+		LevelInput3Pin, LevelInput4Pin, LevelInputCustom, LevelInputArch, LevelOutput1, LevelOutputWord, LevelOutputSwitched, LevelOutput1Sum,
+		LevelOutput1Car, LevelOutput2Pin, LevelOutput3Pin, LevelOutput4Pin, LevelOutputArch, LevelOutputCounter, Custom, CcInput,
+		CcInputBuffer, CcOutput, ProbeMemoryBit, ProbeMemoryWord, ProbeWireBit, ProbeWireWord, ConfigDelay, Halt,
+		Console, SegmentDisplay, StaticValue, PixelScreen, Time, Keyboard, StaticEval, VerilogInput,
+		VerilogOutput, MakerWord4, MakerWord8, SplitterWord4, SplitterWord8, StaticIndexer, ImmProbeMemoryBit, ImmDelayLineWord,
+		Inc, CcLevelInputCustom, CcLevelInput, ImmRegisterBit, Mod, SplitterBit2, SplitterBit4, MakerBit2,
+		MakerBit4, ImmProbeMemoryWord, Concatenator2, Concatenator4, Concatenator8, StaticIndexerConfig, Rom,
+	],
+
+	v9 = 9 [
+		None, Off, On, NotBit, AndBit, And3Bit, NandBit, OrBit,
+		Or3Bit, NorBit, XorBit, XnorBit, SwitchBit, DelayLineBit, RegisterBit, FullAdder,
+		MakerBit8, SplitterBit8, NotWord, OrWord, AndWord, NandWord, NorWord, XorWord,
+		XnorWord, SwitchWord, Equal, LessU, LessS, Neg, Add, Mul,
+		Div, Lsl, Lsr, Rol, Ror, Asr, Counter, RegisterWord,
+		ImmRegisterWord, ImmDelayLineBit, Mux, Decoder1, Decoder2, Decoder3, Constant, SplitterWord2,
+		MakerWord2, PunchCard, RegisterWordConfig, Ssd, Ram, RamLatency, RamFast, DelayLineWord,
+		RamDualLoad, FileLoader, CcLevelOutput, LevelGate, LevelInput1, LevelInputWord, LevelInputSwitched, LevelInput2Pin,
+		LevelInput3Pin, LevelInput4Pin, LevelInputCustom, LevelInputArch, LevelOutput1, LevelOutputWord, LevelOutputSwitched, LevelOutput1Sum,
+		LevelOutput1Car, LevelOutput2Pin, LevelOutput3Pin, LevelOutput4Pin, LevelOutputArch, LevelOutputCounter, Custom, CcInput,
+		CcInputBuffer, CcOutput, ProbeMemoryBit, ProbeMemoryWord, ProbeWireBit, ProbeWireWord, ConfigDelay, Halt,
+		Console, SegmentDisplay, StaticValue, PixelScreen, Time, Keyboard, StaticEval, VerilogInput,
+		VerilogOutput, MakerWord4, MakerWord8, SplitterWord4, SplitterWord8, StaticIndexer, ImmProbeMemoryBit, ImmDelayLineWord,
+		Inc, CcLevelInputCustom, CcLevelInput, ImmRegisterBit, Mod, SplitterBit2, SplitterBit4, MakerBit2,
+		MakerBit4, ImmProbeMemoryWord, Concatenator2, Concatenator4, Concatenator8, StaticIndexerConfig, Rom, DelayLineWordConfig,
+		RamDualLoad1, RamDualLoad2, ImmStaticValue,
+	],
+
+	v10 = 10 [
+		None, Off, On, NotBit, AndBit, And3Bit, NandBit, OrBit,
+		Or3Bit, NorBit, XorBit, XnorBit, SwitchBit, DelayLineBit, RegisterBit, FullAdder,
+		MakerBit8, SplitterBit8, NotWord, OrWord, AndWord, NandWord, NorWord, XorWord,
+		XnorWord, SwitchWord, Equal, LessU, LessS, Neg, Add, Mul,
+		Div, Lsl, Lsr, Rol, Ror, Asr, Counter, RegisterWord,
+		ImmRegisterWord, ImmDelayLineBit, Mux, Decoder1, Decoder2, Decoder3, Constant, SplitterWord2,
+		MakerWord2, Clz, RegisterWordConfig, Ssd, Deleted3, RamLatency, LoadPort, DelayLineWord,
+		StorePort, Ctz, CcLevelOutput, LevelGate, LevelInput1, LevelInputWord, LevelInputSwitched, LevelInput2Pin,
+		LevelInput3Pin, LevelInput4Pin, LevelInputCustom, LevelInputArch, LevelOutput1, LevelOutputWord, LevelOutputSwitched, LevelOutput1Sum,
+		LevelOutput1Car, LevelOutput2Pin, LevelOutput3Pin, LevelOutput4Pin, LevelOutputArch, LevelOutputCounter, Custom, CcInput,
+		CcInputBuffer, CcOutput, ProbeMemoryBit, ProbeMemoryWord, ProbeWireBit, ProbeWireWord, ConfigDelay, Halt,
+		Console, SegmentDisplay, StaticValue, PixelScreen, Time, Keyboard, StaticEval, VerilogInput,
+		VerilogOutput, MakerWord4, MakerWord8, SplitterWord4, SplitterWord8, StaticIndexer, ImmProbeMemoryBit, ImmDelayLineWord,
+		Inc, CcLevelInputCustom, CcLevelInput, ImmRegisterBit, Mod, SplitterBit2, SplitterBit4, MakerBit2,
+		MakerBit4, ImmProbeMemoryWord, Concatenator2, Concatenator4, Concatenator8, StaticIndexerConfig, RamFast, DelayLineWordConfig,
+		Deleted1, Deleted2, ImmStaticValue,
+	],
+}
+
+impl ComponentKind {
+	/// The kind that a v6 kind is, and the word size that v6 encoded in the kind, or 0 if it has none.
+	#[allow(clippy::too_many_lines)]
+	pub fn from_v6(kind: v6::ComponentKind) -> (Self, i64) {
+		use v6::ComponentKind as V6;
+
+		match kind {
+			V6::Error => (Self::None, 0),
+			V6::Off => (Self::Off, 0),
+			V6::On => (Self::On, 0),
+			V6::Not => (Self::NotBit, 0),
+			V6::And => (Self::AndBit, 0),
+			V6::And3 => (Self::And3Bit, 0),
+			V6::Nand => (Self::NandBit, 0),
+			V6::Or => (Self::OrBit, 0),
+			V6::Or3 => (Self::Or3Bit, 0),
+			V6::Nor => (Self::NorBit, 0),
+			V6::Xor => (Self::XorBit, 0),
+			V6::Xnor => (Self::XnorBit, 0),
+			V6::Switch1 => (Self::SwitchBit, 0),
+			V6::DelayLine1 => (Self::DelayLineBit, 0),
+			V6::ComBitMemory => (Self::RegisterBit, 0),
+			V6::ComFullAdder => (Self::FullAdder, 0),
+			V6::Maker8 => (Self::MakerBit8, 8),
+			V6::Splitter8 => (Self::SplitterBit8, 8),
+
+			V6::Not8 => (Self::NotWord, 8),
+			V6::Not16 => (Self::NotWord, 16),
+			V6::Not32 => (Self::NotWord, 32),
+			V6::Not64 => (Self::NotWord, 64),
+			V6::Or8 => (Self::OrWord, 8),
+			V6::Or16 => (Self::OrWord, 16),
+			V6::Or32 => (Self::OrWord, 32),
+			V6::Or64 => (Self::OrWord, 64),
+			V6::And8 => (Self::AndWord, 8),
+			V6::And16 => (Self::AndWord, 16),
+			V6::And32 => (Self::AndWord, 32),
+			V6::And64 => (Self::AndWord, 64),
+			V6::Nand8 => (Self::NandWord, 8),
+			V6::Nand16 => (Self::NandWord, 16),
+			V6::Nand32 => (Self::NandWord, 32),
+			V6::Nand64 => (Self::NandWord, 64),
+			V6::Nor8 => (Self::NorWord, 8),
+			V6::Nor16 => (Self::NorWord, 16),
+			V6::Nor32 => (Self::NorWord, 32),
+			V6::Nor64 => (Self::NorWord, 64),
+			V6::Xor8 => (Self::XorWord, 8),
+			V6::Xor16 => (Self::XorWord, 16),
+			V6::Xor32 => (Self::XorWord, 32),
+			V6::Xor64 => (Self::XorWord, 64),
+			V6::Xnor8 => (Self::XnorWord, 8),
+			V6::Xnor16 => (Self::XnorWord, 16),
+			V6::Xnor32 => (Self::XnorWord, 32),
+			V6::Xnor64 => (Self::XnorWord, 64),
+			V6::Switch8 => (Self::SwitchWord, 8),
+			V6::Switch16 => (Self::SwitchWord, 16),
+			V6::Switch32 => (Self::SwitchWord, 32),
+			V6::Switch64 => (Self::SwitchWord, 64),
+			V6::ComEqual8 => (Self::Equal, 8),
+			V6::ComEqual16 => (Self::Equal, 16),
+			V6::ComEqual32 => (Self::Equal, 32),
+			V6::ComEqual64 => (Self::Equal, 64),
+			V6::ComLessU8 => (Self::LessU, 8),
+			V6::ComLessU16 => (Self::LessU, 16),
+			V6::ComLessU32 => (Self::LessU, 32),
+			V6::ComLessU64 => (Self::LessU, 64),
+			V6::ComLessS8 => (Self::LessS, 8),
+			V6::ComLessS16 => (Self::LessS, 16),
+			V6::ComLessS32 => (Self::LessS, 32),
+			V6::ComLessS64 => (Self::LessS, 64),
+			V6::Neg8 => (Self::Neg, 8),
+			V6::Neg16 => (Self::Neg, 16),
+			V6::Neg32 => (Self::Neg, 32),
+			V6::Neg64 => (Self::Neg, 64),
+			V6::Add8 => (Self::Add, 8),
+			V6::Add16 => (Self::Add, 16),
+			V6::Add32 => (Self::Add, 32),
+			V6::Add64 => (Self::Add, 64),
+			V6::Mul8 => (Self::Mul, 8),
+			V6::Mul16 => (Self::Mul, 16),
+			V6::Mul32 => (Self::Mul, 32),
+			V6::Mul64 => (Self::Mul, 64),
+			V6::DivMod8 => (Self::Div, 8),
+			V6::DivMod16 => (Self::Div, 16),
+			V6::DivMod32 => (Self::Div, 32),
+			V6::DivMod64 => (Self::Div, 64),
+			V6::Shl8 => (Self::Lsl, 8),
+			V6::Shl16 => (Self::Lsl, 16),
+			V6::Shl32 => (Self::Lsl, 32),
+			V6::Shl64 => (Self::Lsl, 64),
+			V6::Shr8 => (Self::Lsr, 8),
+			V6::Shr16 => (Self::Lsr, 16),
+			V6::Shr32 => (Self::Lsr, 32),
+			V6::Shr64 => (Self::Lsr, 64),
+			V6::Rol8 => (Self::Rol, 8),
+			V6::Rol16 => (Self::Rol, 16),
+			V6::Rol32 => (Self::Rol, 32),
+			V6::Rol64 => (Self::Rol, 64),
+			V6::Ror8 => (Self::Ror, 8),
+			V6::Ror16 => (Self::Ror, 16),
+			V6::Ror32 => (Self::Ror, 32),
+			V6::Ror64 => (Self::Ror, 64),
+			V6::Ashr8 => (Self::Asr, 8),
+			V6::Ashr16 => (Self::Asr, 16),
+			V6::Ashr32 => (Self::Asr, 32),
+			V6::Ashr64 => (Self::Asr, 64),
+			V6::Counter8 => (Self::Counter, 8),
+			V6::Counter16 => (Self::Counter, 16),
+			V6::Counter32 => (Self::Counter, 32),
+			V6::Counter64 => (Self::Counter, 64),
+			V6::Register8 | V6::Register8red | V6::Register8redPlus => (Self::RegisterWord, 8),
+			V6::Register16 => (Self::RegisterWord, 16),
+			V6::Register32 => (Self::RegisterWord, 32),
+			V6::Register64 => (Self::RegisterWord, 64),
+			V6::Mux8 => (Self::Mux, 8),
+			V6::Mux16 => (Self::Mux, 16),
+			V6::Mux32 => (Self::Mux, 32),
+			V6::Mux64 => (Self::Mux, 64),
+			V6::Constant8 => (Self::Constant, 8),
+			V6::Constant16 => (Self::Constant, 16),
+			V6::Constant32 => (Self::Constant, 32),
+			V6::Constant64 => (Self::Constant, 64),
+			V6::DelayLine8 => (Self::DelayLineWord, 8),
+			V6::DelayLine16 => (Self::DelayLineWord, 16),
+			V6::DelayLine32 => (Self::DelayLineWord, 32),
+			V6::DelayLine64 => (Self::DelayLineWord, 64),
+			V6::ComDecoder1 => (Self::Decoder1, 0),
+			V6::ComDecoder2 => (Self::Decoder2, 0),
+			V6::ComDecoder3 => (Self::Decoder3, 0),
+			V6::Splitter16 => (Self::SplitterWord2, 16),
+			V6::Splitter32 => (Self::SplitterWord4, 32),
+			V6::Splitter64 => (Self::SplitterWord8, 64),
+			V6::Maker16 => (Self::MakerWord2, 16),
+			V6::Maker32 => (Self::MakerWord4, 32),
+			V6::Maker64 => (Self::MakerWord8, 64),
+
+			V6::Ram8 | V6::Ram => (Self::Ram, 8),
+			V6::ComRamLatency => (Self::RamLatency, 64),
+			V6::ComRamFast => (Self::RamFast, 64),
+			V6::ComRamDualLoad => (Self::RamDualLoad, 64),
+			V6::Rom => (Self::Rom, 64),
+			V6::Program => (Self::Assembler, 64),
+			V6::Program81 | V6::Program81red | V6::Program84 => (Self::Assembler, 8),
+			V6::ComFileLoader => (Self::FileLoader, 64),
+
+			V6::Input1 | V6::ComLevelInput1 => (Self::LevelInput1, 0),
+			V6::Input8 | V6::LevelInput8 => (Self::LevelInputWord, 8),
+			V6::Input16 => (Self::LevelInputWord, 16),
+			V6::Input32 => (Self::LevelInputWord, 32),
+			V6::Input64 => (Self::LevelInputWord, 64),
+			V6::ComLevelInput2Pin => (Self::LevelInput2Pin, 0),
+			V6::ComLevelInput3Pin => (Self::LevelInput3Pin, 0),
+			V6::ComLevelInput4Pin => (Self::LevelInput4Pin, 0),
+			V6::ComLevelInputArch => (Self::LevelInputArch, 64),
+			V6::Output1 | V6::ComLevelOutput1 => (Self::LevelOutput1, 0),
+			V6::Output8 | V6::LevelOutput8 => (Self::LevelOutputWord, 8),
+			V6::Output16 => (Self::LevelOutputWord, 16),
+			V6::Output32 => (Self::LevelOutputWord, 32),
+			V6::Output64 => (Self::LevelOutputWord, 64),
+			V6::Output1z => (Self::LevelOutputSwitched, 1),
+			V6::Output8z | V6::LevelOutput8z => (Self::LevelOutputSwitched, 8),
+			V6::Output16z => (Self::LevelOutputSwitched, 16),
+			V6::Output32z => (Self::LevelOutputSwitched, 32),
+			V6::Output64z => (Self::LevelOutputSwitched, 64),
+			V6::ComLevelOutput1Sum => (Self::LevelOutput1Sum, 0),
+			V6::ComLevelOutput1Car => (Self::LevelOutput1Car, 0),
+			V6::ComLevelOutput2Pin => (Self::LevelOutput2Pin, 0),
+			V6::ComLevelOutput3Pin => (Self::LevelOutput3Pin, 0),
+			V6::ComLevelOutput4Pin => (Self::LevelOutput4Pin, 0),
+			V6::ComLevelOutputArch => (Self::LevelOutputArch, 64),
+			V6::ComLevelOutputCounter => (Self::LevelOutputCounter, 64),
+			V6::ComLevelGate => (Self::LevelGate, 0),
+
+			V6::Custom => (Self::Custom, 0),
+			V6::Console => (Self::Console, 0),
+			V6::ComSegmentDisplay => (Self::SegmentDisplay, 0),
+			V6::ComTime => (Self::Time, 64),
+			V6::Keyboard => (Self::Keyboard, 8),
+			V6::Halt => (Self::Halt, 0),
+			V6::ComConfigDelay => (Self::ConfigDelay, 0),
+			V6::ComProbeMemoryBit => (Self::ProbeMemoryBit, 0),
+			V6::ComProbeMemoryWord => (Self::ProbeMemoryWord, 64),
+			V6::ComProbeWireBit => (Self::ProbeWireBit, 0),
+			V6::ComProbeWireWord => (Self::ProbeWireWord, 64),
+
+			V6::Buffer1 => (Self::Buffer1, 0),
+			V6::VirtualCounter8 => (Self::VirtualCounter8, 0),
+			V6::VirtualCounter64 => (Self::VirtualCounter64, 0),
+			V6::VirtualRam8 => (Self::VirtualRam8, 0),
+			V6::Deleted0 => (Self::V6Deleted0, 0),
+			V6::Deleted1 => (Self::V6Deleted1, 0),
+			V6::Deleted17 => (Self::V6Deleted17, 0),
+			V6::Deleted18 => (Self::V6Deleted18, 0),
+			V6::VirtualRegister8 => (Self::VirtualRegister8, 0),
+			V6::VirtualRegister8red => (Self::VirtualRegister8red, 0),
+			V6::VirtualRegister8redPlus => (Self::VirtualRegister8redPlus, 0),
+			V6::VirtualRegister64 => (Self::VirtualRegister64, 0),
+			V6::Deleted2 => (Self::V6Deleted2, 0),
+			V6::Deleted3 => (Self::V6Deleted3, 0),
+			V6::VirtualcomBitMemory => (Self::VirtualcomBitMemory, 0),
+			V6::Deleted10 => (Self::V6Deleted10, 0),
+			V6::NoteSound => (Self::NoteSound, 0),
+			V6::Deleted4 => (Self::V6Deleted4, 0),
+			V6::Deleted5 => (Self::V6Deleted5, 0),
+			V6::WireCluster => (Self::WireCluster, 0),
+			V6::LevelScreen => (Self::LevelScreen, 0),
+			V6::Deleted6 => (Self::V6Deleted6, 0),
+			V6::Deleted7 => (Self::V6Deleted7, 0),
+			V6::LevelInputConditions => (Self::LevelInputConditions, 0),
+			V6::LevelInputCode => (Self::LevelInputCode, 0),
+			V6::Deleted8 => (Self::V6Deleted8, 0),
+			V6::Deleted9 => (Self::V6Deleted9, 0),
+			V6::Deleted11 => (Self::V6Deleted11, 0),
+			V6::VirtualCustom => (Self::VirtualCustom, 0),
+			V6::VirtualDelayLine1 => (Self::VirtualDelayLine1, 0),
+			V6::AndOrLatch => (Self::AndOrLatch, 0),
+			V6::NandNandLatch => (Self::NandNandLatch, 0),
+			V6::NorNorLatch => (Self::NorNorLatch, 0),
+			V6::DotMatrixDisplay => (Self::DotMatrixDisplay, 0),
+			V6::Deleted12 => (Self::V6Deleted12, 0),
+			V6::Deleted13 => (Self::V6Deleted13, 0),
+			V6::Deleted14 => (Self::V6Deleted14, 0),
+			V6::Deleted15 => (Self::V6Deleted15, 0),
+			V6::Deleted16 => (Self::V6Deleted16, 0),
+			V6::Buffer8 => (Self::Buffer8, 0),
+			V6::Buffer16 => (Self::Buffer16, 0),
+			V6::Buffer32 => (Self::Buffer32, 0),
+			V6::Buffer64 => (Self::Buffer64, 0),
+			V6::VirtualRegister16 => (Self::VirtualRegister16, 0),
+			V6::VirtualCounter16 => (Self::VirtualCounter16, 0),
+			V6::VirtualRegister32 => (Self::VirtualRegister32, 0),
+			V6::VirtualCounter32 => (Self::VirtualCounter32, 0),
+			V6::VirtualRam => (Self::VirtualRam, 0),
+			V6::VirtualcomRamLatency => (Self::VirtualcomRamLatency, 0),
+			V6::VirtualcomRamFast => (Self::VirtualcomRamFast, 0),
+			V6::VirtualRom => (Self::VirtualRom, 0),
+			V6::SolutionRom => (Self::SolutionRom, 0),
+			V6::VirtualSolutionRom => (Self::VirtualSolutionRom, 0),
+			V6::VirtualDelayLine8 => (Self::VirtualDelayLine8, 0),
+			V6::VirtualDelayLine16 => (Self::VirtualDelayLine16, 0),
+			V6::VirtualDelayLine32 => (Self::VirtualDelayLine32, 0),
+			V6::VirtualDelayLine64 => (Self::VirtualDelayLine64, 0),
+			V6::VirtualcomRamDualLoad => (Self::VirtualcomRamDualLoad, 0),
+			V6::Hdd => (Self::Hdd, 0),
+			V6::VirtualHdd => (Self::VirtualHdd, 0),
+			V6::Network => (Self::Network, 0),
+			V6::IndexerBit => (Self::IndexerBit, 0),
+			V6::IndexerByte => (Self::IndexerByte, 0),
+			V6::SpriteDisplay => (Self::SpriteDisplay, 0),
+			V6::Clock => (Self::Clock, 0),
+			V6::Bidirectional1 => (Self::Bidirectional1, 0),
+			V6::VirtualBidirectional1 => (Self::VirtualBidirectional1, 0),
+			V6::Bidirectional8 => (Self::Bidirectional8, 0),
+			V6::VirtualBidirectional8 => (Self::VirtualBidirectional8, 0),
+			V6::Bidirectional16 => (Self::Bidirectional16, 0),
+			V6::VirtualBidirectional16 => (Self::VirtualBidirectional16, 0),
+			V6::Bidirectional32 => (Self::Bidirectional32, 0),
+			V6::VirtualBidirectional32 => (Self::VirtualBidirectional32, 0),
+			V6::Bidirectional64 => (Self::Bidirectional64, 0),
+			V6::VirtualBidirectional64 => (Self::VirtualBidirectional64, 0),
+		}
+	}
+
+	/// The v6 kind that is this kind with the given word size, or `None` if v6 has no such kind.
+	///
+	/// Where several v6 kinds are the same kind, such as `Input1` and `ComLevelInput1`, this is the one with the lowest number.
+	pub fn to_v6(self, word_size: i64) -> Option<v6::ComponentKind> {
+		(0..=u16::MAX)
+			.filter_map(|raw| v6::ComponentKind::try_from(raw).ok())
+			.find(|&kind| Self::from_v6(kind) == (self, word_size))
+	}
+
+	pub fn is_custom(self) -> bool {
+		matches!(self, Self::Custom)
+	}
+}
+
+impl From<v6::ComponentKind> for ComponentKind {
+	fn from(kind: v6::ComponentKind) -> Self {
+		Self::from_v6(kind).0
+	}
+}
+
+impl std::fmt::Display for ComponentKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{self:?}")
+	}
+}
+
+/// Component fields that only exist in some save versions.
+#[derive(Clone, Debug)]
+pub enum ComponentExtra<'a> {
+	V6 {
+		/// The kind as the save had it, since several v6 kinds can be the same [`ComponentKind`] and word size.
+		kind: v6::ComponentKind,
+		custom_nudge: Option<Point>,
+		assembler_data: Option<v6::AssemblerInfo<'a>>,
+	},
+
+	V7 {
+		buffer_size: i64,
+		discarded: i64,
+		static_states: Option<Slice<'a, u16, (i64, i64)>>,
+		linked_word_sizes: Option<Slice<'a, u16, (i64, i64)>>,
+		assembler_data: Option<v7::AssemblerInfo<'a>>,
+	},
+
+	V8 {
+		buffer_size: i64,
+		watched_components: Slice<'a, u16, v8::WatchedComponent<'a>>,
+		static_states: Option<Slice<'a, u16, (i64, i64)>>,
+		selected_programs: Option<v8::AssemblerInfo<'a>>,
+	},
+
+	V9 {
+		buffer_size: i64,
+		watched_components: Slice<'a, u16, v9::WatchedComponent<'a>>,
+		static_states: Option<Slice<'a, u16, (i64, i64)>>,
+		selected_programs: v9::AssemblerInfo<'a>,
+	},
+
+	V10 {
+		buffer_size: i64,
+		linked_components: Slice<'a, u16, v10::LinkedComponent<'a>>,
+		static_states: Option<Slice<'a, u16, (i64, i64)>>,
+		selected_programs: v10::AssemblerInfo<'a>,
+	},
+}
+
+impl ComponentExtra<'_> {
+	pub fn version(&self) -> u8 {
+		match self {
+			Self::V6 { .. } => 6,
+			Self::V7 { .. } => 7,
+			Self::V8 { .. } => 8,
+			Self::V9 { .. } => 9,
+			Self::V10 { .. } => 10,
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct Wire<'a> {
+	pub color: u8,
+	pub comment: std::borrow::Cow<'a, str>,
+	pub start: Point,
+	pub segments: WireSegments,
+	pub extra: WireExtra,
+}
+
+impl Wire<'_> {
+	/// The point where the wire ends, after following all its segments, or `None` if they leave the grid.
+	pub fn end(&self) -> Option<Point> {
+		match &self.segments {
+			WireSegments::TeleWireEnd(end) => Some(*end),
+			WireSegments::Segments(segments) => segments.iter().try_fold(self.start, |end, segment| segment.step(end)),
+		}
+	}
+}
+
+/// Wire fields that only exist in some save versions.
+#[derive(Clone, Copy, Debug)]
+pub enum WireExtra {
+	V6 {
+		width: u8,
+	},
+	V7,
+	V8,
+	V9,
+	V10,
+}
+
+impl WireExtra {
+	pub fn version(self) -> u8 {
+		match self {
+			Self::V6 { .. } => 6,
+			Self::V7 => 7,
+			Self::V8 => 8,
+			Self::V9 => 9,
+			Self::V10 => 10,
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+pub enum WireSegments {
+	TeleWireEnd(Point),
+	Segments(Vec<WireSegment>),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WireSegment {
+	pub length: u8,
+	pub direction: WireDirection,
+}
+
+impl WireSegment {
+	/// The point reached by following this segment from `start`, or `None` if it is outside the range of a [`Point`].
+	pub fn step(self, start: Point) -> Option<Point> {
+		let (dx, dy) = self.direction.delta();
+		let len = i16::from(self.length);
+		Some(Point {
+			x: start.x.checked_add(dx * len)?,
+			y: start.y.checked_add(dy * len)?,
+		})
+	}
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WireDirection {
+	Right,
+	DownRight,
+	Down,
+	DownLeft,
+	Left,
+	UpLeft,
+	Up,
+	UpRight,
+}
+
+impl WireDirection {
+	/// The change in `(x, y)` for one step in this direction.
+	pub fn delta(self) -> (i16, i16) {
+		match self {
+			Self::Right => (1, 0),
+			Self::DownRight => (1, 1),
+			Self::Down => (0, 1),
+			Self::DownLeft => (-1, 1),
+			Self::Left => (-1, 0),
+			Self::UpLeft => (-1, -1),
+			Self::Up => (0, -1),
+			Self::UpRight => (1, -1),
+		}
+	}
+}
+
+#[derive(Debug)]
+pub enum ConversionError {
+	/// The circuit, one of its components or one of its wires is from a different save version than the one being converted to.
+	VersionMismatch { expected: u8, found: u8 },
+
+	/// The value of the given field cannot be represented in the save version being converted to.
+	ValueOutOfRange(&'static str),
+
+	/// The given field is required by the save version being converted to.
+	MissingField(&'static str),
+
+	/// The save version being converted to has no component of the given kind.
+	UnsupportedKind { version: u8, kind: ComponentKind },
+}
+
+impl std::fmt::Display for ConversionError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::VersionMismatch { expected, found } => write!(f, "expected version {expected} data but found version {found} data"),
+			Self::ValueOutOfRange(field) => write!(f, "{field} is out of range"),
+			Self::MissingField(field) => write!(f, "{field} is missing"),
+			Self::UnsupportedKind { version, kind } => write!(f, "version {version} has no {kind} component"),
+		}
+	}
+}
+
+impl std::error::Error for ConversionError {}
+
+fn to_vec<'a, N, T>(slice: &Slice<'a, N, T>) -> Vec<T> where T: crate::Parse<'a> + Clone {
+	slice.iter().map(Either::into_inner).collect()
+}
+
+fn check_version(expected: u8, found: u8) -> Result<(), ConversionError> {
+	if expected == found {
+		Ok(())
+	}
+	else {
+		Err(ConversionError::VersionMismatch { expected, found })
+	}
+}
+
+macro_rules! common_conversions {
+	($($version:ident,)*) => {
+		$(
+			impl From<$version::Point> for Point {
+				fn from($version::Point { x, y }: $version::Point) -> Self {
+					Self { x, y }
+				}
+			}
+
+			impl From<Point> for $version::Point {
+				fn from(Point { x, y }: Point) -> Self {
+					Self { x, y }
+				}
+			}
+
+			impl From<$version::SyncState> for SyncState {
+				fn from(synced: $version::SyncState) -> Self {
+					match synced {
+						$version::SyncState::Unsynced => Self::Unsynced,
+						$version::SyncState::Synced => Self::Synced,
+						$version::SyncState::ChangedAfterSync => Self::ChangedAfterSync,
+					}
+				}
+			}
+
+			impl From<SyncState> for $version::SyncState {
+				fn from(synced: SyncState) -> Self {
+					match synced {
+						SyncState::Unsynced => Self::Unsynced,
+						SyncState::Synced => Self::Synced,
+						SyncState::ChangedAfterSync => Self::ChangedAfterSync,
+					}
+				}
+			}
+
+			impl From<$version::WireDirection> for WireDirection {
+				fn from(direction: $version::WireDirection) -> Self {
+					match direction {
+						$version::WireDirection::Right => Self::Right,
+						$version::WireDirection::DownRight => Self::DownRight,
+						$version::WireDirection::Down => Self::Down,
+						$version::WireDirection::DownLeft => Self::DownLeft,
+						$version::WireDirection::Left => Self::Left,
+						$version::WireDirection::UpLeft => Self::UpLeft,
+						$version::WireDirection::Up => Self::Up,
+						$version::WireDirection::UpRight => Self::UpRight,
+					}
+				}
+			}
+
+			impl From<WireDirection> for $version::WireDirection {
+				fn from(direction: WireDirection) -> Self {
+					match direction {
+						WireDirection::Right => Self::Right,
+						WireDirection::DownRight => Self::DownRight,
+						WireDirection::Down => Self::Down,
+						WireDirection::DownLeft => Self::DownLeft,
+						WireDirection::Left => Self::Left,
+						WireDirection::UpLeft => Self::UpLeft,
+						WireDirection::Up => Self::Up,
+						WireDirection::UpRight => Self::UpRight,
+					}
+				}
+			}
+
+			impl From<$version::WireSegment> for WireSegment {
+				fn from($version::WireSegment { length, direction }: $version::WireSegment) -> Self {
+					Self { length, direction: direction.into() }
+				}
+			}
+
+			impl From<WireSegment> for $version::WireSegment {
+				fn from(WireSegment { length, direction }: WireSegment) -> Self {
+					Self { length, direction: direction.into() }
+				}
+			}
+
+			impl From<&$version::WireSegments<'_>> for WireSegments {
+				fn from(segments: &$version::WireSegments<'_>) -> Self {
+					match segments {
+						$version::WireSegments::TeleWireEnd(end) => Self::TeleWireEnd((*end).into()),
+						$version::WireSegments::Segments(segments) => Self::Segments(segments.iter().map(|segment| segment.into_inner().into()).collect()),
+					}
+				}
+			}
+
+			impl From<WireSegments> for $version::WireSegments<'_> {
+				fn from(segments: WireSegments) -> Self {
+					match segments {
+						WireSegments::TeleWireEnd(end) => Self::TeleWireEnd(end.into()),
+						WireSegments::Segments(segments) => Self::Segments(segments.into_iter().map(Into::into).collect::<Vec<_>>().into()),
+					}
+				}
+			}
+		)*
+	};
+}
+
+common_conversions! {
+	v6,
+	v7,
+	v8,
+	v9,
+	v10,
+}
+
+impl<'a> From<v6::CircuitData<'a>> for Circuit<'a> {
+	fn from(circuit: v6::CircuitData<'a>) -> Self {
+		Self {
+			version: 6,
+			custom_id: circuit.custom_id,
+			hub_id: circuit.hub_id,
+			gate: circuit.gate,
+			delay: circuit.delay,
+			menu_visible: circuit.menu_visible,
+			clock_speed: circuit.clock_speed.into(),
+			dependencies: to_vec(&circuit.dependencies),
+			description: circuit.description,
+			camera_position: circuit.camera_position.into(),
+			synced: circuit.synced.into(),
+			player_data: to_vec(&circuit.player_data),
+			hub_description: circuit.hub_description,
+			components: circuit.components.iter().map(|component| {
+				let component = component.into_inner();
+				let (kind, word_size) = ComponentKind::from_v6(component.kind);
+				Component {
+					kind,
+					position: component.position.into(),
+					rotation: component.rotation,
+					permanent_id: component.permanent_id,
+					custom_string: component.custom_string,
+					settings: component.settings.into(),
+					ui_order: component.ui_order,
+					word_size,
+					custom_id: component.custom_data.map(|custom_data| custom_data.id),
+					extra: ComponentExtra::V6 {
+						kind: component.kind,
+						custom_nudge: component.custom_data.map(|custom_data| custom_data.custom_nudge.into()),
+						assembler_data: component.assembler_data,
+					},
+				}
+			}).collect(),
+			wires: circuit.wires.iter().map(|wire| {
+				let wire = wire.as_inner_ref();
+				Wire {
+					color: wire.color,
+					comment: wire.comment.clone(),
+					start: wire.start.into(),
+					segments: (&wire.segments).into(),
+					extra: WireExtra::V6 { width: wire.width },
+				}
+			}).collect(),
+		}
+	}
+}
+
+impl<'a> TryFrom<Circuit<'a>> for v6::CircuitData<'a> {
+	type Error = ConversionError;
+
+	fn try_from(circuit: Circuit<'a>) -> Result<Self, Self::Error> {
+		check_version(6, circuit.version)?;
+
+		let components: Result<Vec<_>, _> = circuit.components.into_iter().map(|component| {
+			let ComponentExtra::V6 { kind, custom_nudge, assembler_data } = component.extra else {
+				return Err(ConversionError::VersionMismatch { expected: 6, found: component.extra.version() });
+			};
+			let kind =
+				if ComponentKind::from_v6(kind) == (component.kind, component.word_size) { kind }
+				else { component.kind.to_v6(component.word_size).ok_or(ConversionError::UnsupportedKind { version: 6, kind: component.kind })? };
+			Ok(v6::Component {
+				kind,
+				position: component.position.into(),
+				rotation: component.rotation,
+				permanent_id: component.permanent_id,
+				custom_string: component.custom_string,
+				settings: component.settings.try_into().map_err(|_| ConversionError::ValueOutOfRange("Component.settings"))?,
+				ui_order: component.ui_order,
+				custom_data: component.custom_id.map(|id| v6::CustomCompData {
+					id,
+					custom_nudge: custom_nudge.unwrap_or(Point { x: 0, y: 0 }).into(),
+				}),
+				assembler_data,
+			})
+		}).collect();
+
+		let wires: Result<Vec<_>, _> = circuit.wires.into_iter().map(|wire| {
+			let WireExtra::V6 { width } = wire.extra else {
+				return Err(ConversionError::VersionMismatch { expected: 6, found: wire.extra.version() });
+			};
+			Ok(v6::Wire {
+				width,
+				color: wire.color,
+				comment: wire.comment.clone(),
+				start: wire.start.into(),
+				segments: wire.segments.into(),
+			})
+		}).collect();
+
+		Ok(Self {
+			custom_id: circuit.custom_id,
+			hub_id: circuit.hub_id,
+			gate: circuit.gate,
+			delay: circuit.delay,
+			menu_visible: circuit.menu_visible,
+			clock_speed: circuit.clock_speed.try_into().map_err(|_| ConversionError::ValueOutOfRange("CircuitData.clock_speed"))?,
+			dependencies: circuit.dependencies.into(),
+			description: circuit.description,
+			camera_position: circuit.camera_position.into(),
+			synced: circuit.synced.into(),
+			player_data: circuit.player_data.into(),
+			hub_description: circuit.hub_description,
+			components: components?.into(),
+			wires: wires?.into(),
+		})
+	}
+}
+
+impl<'a> From<v7::CircuitData<'a>> for Circuit<'a> {
+	fn from(circuit: v7::CircuitData<'a>) -> Self {
+		Self {
+			version: 7,
+			custom_id: circuit.custom_id,
+			hub_id: circuit.hub_id,
+			gate: circuit.gate,
+			delay: circuit.delay,
+			menu_visible: circuit.menu_visible,
+			clock_speed: circuit.clock_speed,
+			dependencies: to_vec(&circuit.dependencies),
+			description: circuit.description,
+			camera_position: circuit.camera_position.into(),
+			synced: circuit.synced.into(),
+			player_data: to_vec(&circuit.player_data),
+			hub_description: circuit.hub_description,
+			components: circuit.components.iter().map(|component| {
+				let component = component.into_inner();
+				let (custom_id, static_states, linked_word_sizes) = match component.custom_data {
+					Some(custom_data) => (Some(custom_data.id), Some(custom_data.static_states), Some(custom_data.linked_word_sizes)),
+					None => (None, None, None),
+				};
+				Component {
+					kind: component.kind.into(),
+					position: component.position.into(),
+					rotation: component.rotation,
+					permanent_id: component.permanent_id,
+					custom_string: component.custom_string,
+					settings: to_vec(&component.settings),
+					ui_order: component.ui_order,
+					word_size: component.word_size,
+					custom_id,
+					extra: ComponentExtra::V7 {
+						buffer_size: component.buffer_size,
+						discarded: component.discarded,
+						static_states,
+						linked_word_sizes,
+						assembler_data: component.assembler_data,
+					},
+				}
+			}).collect(),
+			wires: circuit.wires.iter().map(|wire| {
+				let wire = wire.as_inner_ref();
+				Wire {
+					color: wire.color,
+					comment: wire.comment.clone(),
+					start: wire.start.into(),
+					segments: (&wire.segments).into(),
+					extra: WireExtra::V7,
+				}
+			}).collect(),
+		}
+	}
+}
+
+impl<'a> TryFrom<Circuit<'a>> for v7::CircuitData<'a> {
+	type Error = ConversionError;
+
+	fn try_from(circuit: Circuit<'a>) -> Result<Self, Self::Error> {
+		check_version(7, circuit.version)?;
+
+		let components: Result<Vec<_>, _> = circuit.components.into_iter().map(|component| {
+			let ComponentExtra::V7 { buffer_size, discarded, static_states, linked_word_sizes, assembler_data } = component.extra else {
+				return Err(ConversionError::VersionMismatch { expected: 7, found: component.extra.version() });
+			};
+			Ok(v7::Component {
+				kind: component.kind.try_into()?,
+				position: component.position.into(),
+				rotation: component.rotation,
+				permanent_id: component.permanent_id,
+				custom_string: component.custom_string,
+				settings: component.settings.into(),
+				buffer_size,
+				ui_order: component.ui_order,
+				word_size: component.word_size,
+				discarded,
+				custom_data: component.custom_id.map(|id| v7::CustomCompData {
+					id,
+					static_states: static_states.unwrap_or_else(|| vec![].into()),
+					linked_word_sizes: linked_word_sizes.unwrap_or_else(|| vec![].into()),
+				}),
+				assembler_data,
+			})
+		}).collect();
+
+		let wires: Result<Vec<_>, _> = circuit.wires.into_iter().map(|wire| {
+			let WireExtra::V7 = wire.extra else {
+				return Err(ConversionError::VersionMismatch { expected: 7, found: wire.extra.version() });
+			};
+			Ok(v7::Wire {
+				color: wire.color,
+				comment: wire.comment.clone(),
+				start: wire.start.into(),
+				segments: wire.segments.into(),
+			})
+		}).collect();
+
+		Ok(Self {
+			custom_id: circuit.custom_id,
+			hub_id: circuit.hub_id,
+			gate: circuit.gate,
+			delay: circuit.delay,
+			menu_visible: circuit.menu_visible,
+			clock_speed: circuit.clock_speed,
+			dependencies: circuit.dependencies.into(),
+			description: circuit.description,
+			camera_position: circuit.camera_position.into(),
+			synced: circuit.synced.into(),
+			player_data: circuit.player_data.into(),
+			hub_description: circuit.hub_description,
+			components: components?.into(),
+			wires: wires?.into(),
+		})
+	}
+}
+
+impl<'a> From<v8::CircuitData<'a>> for Circuit<'a> {
+	fn from(circuit: v8::CircuitData<'a>) -> Self {
+		Self {
+			version: 8,
+			custom_id: circuit.custom_id,
+			hub_id: circuit.hub_id,
+			gate: circuit.gate,
+			delay: circuit.delay,
+			menu_visible: circuit.menu_visible,
+			clock_speed: circuit.clock_speed,
+			dependencies: to_vec(&circuit.dependencies),
+			description: circuit.description,
+			camera_position: circuit.camera_position.into(),
+			synced: circuit.synced.into(),
+			player_data: to_vec(&circuit.player_data),
+			hub_description: circuit.hub_description,
+			components: circuit.components.iter().map(|component| {
+				let component = component.into_inner();
+				let (custom_id, static_states) = match component.custom_data {
+					Some(custom_data) => (Some(custom_data.id), Some(custom_data.static_states)),
+					None => (None, None),
+				};
+				Component {
+					kind: component.kind.into(),
+					position: component.position.into(),
+					rotation: component.rotation,
+					permanent_id: component.permanent_id,
+					custom_string: component.custom_string,
+					settings: to_vec(&component.settings),
+					ui_order: component.ui_order,
+					word_size: component.word_size,
+					custom_id,
+					extra: ComponentExtra::V8 {
+						buffer_size: component.buffer_size,
+						watched_components: component.watched_components,
+						static_states,
+						selected_programs: component.selected_programs,
+					},
+				}
+			}).collect(),
+			wires: circuit.wires.iter().map(|wire| {
+				let wire = wire.as_inner_ref();
+				Wire {
+					color: wire.color,
+					comment: wire.comment.clone(),
+					start: wire.start.into(),
+					segments: (&wire.segments).into(),
+					extra: WireExtra::V8,
+				}
+			}).collect(),
+		}
+	}
+}
+
+impl<'a> TryFrom<Circuit<'a>> for v8::CircuitData<'a> {
+	type Error = ConversionError;
+
+	fn try_from(circuit: Circuit<'a>) -> Result<Self, Self::Error> {
+		check_version(8, circuit.version)?;
+
+		let components: Result<Vec<_>, _> = circuit.components.into_iter().map(|component| {
+			let ComponentExtra::V8 { buffer_size, watched_components, static_states, selected_programs } = component.extra else {
+				return Err(ConversionError::VersionMismatch { expected: 8, found: component.extra.version() });
+			};
+			Ok(v8::Component {
+				kind: component.kind.try_into()?,
+				position: component.position.into(),
+				rotation: component.rotation,
+				permanent_id: component.permanent_id,
+				custom_string: component.custom_string,
+				settings: component.settings.into(),
+				buffer_size,
+				ui_order: component.ui_order,
+				word_size: component.word_size,
+				watched_components,
+				custom_data: component.custom_id.map(|id| v8::CustomCompData {
+					id,
+					static_states: static_states.unwrap_or_else(|| vec![].into()),
+				}),
+				selected_programs,
+			})
+		}).collect();
+
+		let wires: Result<Vec<_>, _> = circuit.wires.into_iter().map(|wire| {
+			let WireExtra::V8 = wire.extra else {
+				return Err(ConversionError::VersionMismatch { expected: 8, found: wire.extra.version() });
+			};
+			Ok(v8::Wire {
+				color: wire.color,
+				comment: wire.comment.clone(),
+				start: wire.start.into(),
+				segments: wire.segments.into(),
+			})
+		}).collect();
+
+		Ok(Self {
+			custom_id: circuit.custom_id,
+			hub_id: circuit.hub_id,
+			gate: circuit.gate,
+			delay: circuit.delay,
+			menu_visible: circuit.menu_visible,
+			clock_speed: circuit.clock_speed,
+			dependencies: circuit.dependencies.into(),
+			description: circuit.description,
+			camera_position: circuit.camera_position.into(),
+			synced: circuit.synced.into(),
+			player_data: circuit.player_data.into(),
+			hub_description: circuit.hub_description,
+			components: components?.into(),
+			wires: wires?.into(),
+		})
+	}
+}
+
+impl<'a> From<v9::CircuitData<'a>> for Circuit<'a> {
+	fn from(circuit: v9::CircuitData<'a>) -> Self {
+		Self {
+			version: 9,
+			custom_id: circuit.custom_id,
+			hub_id: circuit.hub_id,
+			gate: circuit.gate,
+			delay: circuit.delay,
+			menu_visible: circuit.menu_visible,
+			clock_speed: circuit.clock_speed,
+			dependencies: to_vec(&circuit.dependencies),
+			description: circuit.description,
+			camera_position: circuit.camera_position.into(),
+			synced: circuit.synced.into(),
+			player_data: to_vec(&circuit.player_data),
+			hub_description: circuit.hub_description,
+			components: circuit.components.iter().map(|component| {
+				let component = component.into_inner();
+				let (custom_id, static_states) = match component.custom_data {
+					Some(custom_data) => (Some(custom_data.id), Some(custom_data.static_states)),
+					None => (None, None),
+				};
+				Component {
+					kind: component.kind.into(),
+					position: component.position.into(),
+					rotation: component.rotation,
+					permanent_id: component.permanent_id,
+					custom_string: component.custom_string,
+					settings: to_vec(&component.settings),
+					ui_order: component.ui_order,
+					word_size: component.word_size,
+					custom_id,
+					extra: ComponentExtra::V9 {
+						buffer_size: component.buffer_size,
+						watched_components: component.watched_components,
+						static_states,
+						selected_programs: component.selected_programs,
+					},
+				}
+			}).collect(),
+			wires: circuit.wires.iter().map(|wire| {
+				let wire = wire.as_inner_ref();
+				Wire {
+					color: wire.color,
+					comment: wire.comment.clone(),
+					start: wire.start.into(),
+					segments: (&wire.segments).into(),
+					extra: WireExtra::V9,
+				}
+			}).collect(),
+		}
+	}
+}
+
+impl<'a> TryFrom<Circuit<'a>> for v9::CircuitData<'a> {
+	type Error = ConversionError;
+
+	fn try_from(circuit: Circuit<'a>) -> Result<Self, Self::Error> {
+		check_version(9, circuit.version)?;
+
+		let components: Result<Vec<_>, _> = circuit.components.into_iter().map(|component| {
+			let ComponentExtra::V9 { buffer_size, watched_components, static_states, selected_programs } = component.extra else {
+				return Err(ConversionError::VersionMismatch { expected: 9, found: component.extra.version() });
+			};
+			Ok(v9::Component {
+				kind: component.kind.try_into()?,
+				position: component.position.into(),
+				rotation: component.rotation,
+				permanent_id: component.permanent_id,
+				custom_string: component.custom_string,
+				settings: component.settings.into(),
+				buffer_size,
+				ui_order: component.ui_order,
+				word_size: component.word_size,
+				watched_components,
+				selected_programs,
+				custom_data: component.custom_id.map(|id| v9::CustomCompData {
+					id,
+					static_states: static_states.unwrap_or_else(|| vec![].into()),
+				}),
+			})
+		}).collect();
+
+		let wires: Result<Vec<_>, _> = circuit.wires.into_iter().map(|wire| {
+			let WireExtra::V9 = wire.extra else {
+				return Err(ConversionError::VersionMismatch { expected: 9, found: wire.extra.version() });
+			};
+			Ok(v9::Wire {
+				color: wire.color,
+				comment: wire.comment.clone(),
+				start: wire.start.into(),
+				segments: wire.segments.into(),
+			})
+		}).collect();
+
+		Ok(Self {
+			custom_id: circuit.custom_id,
+			hub_id: circuit.hub_id,
+			gate: circuit.gate,
+			delay: circuit.delay,
+			menu_visible: circuit.menu_visible,
+			clock_speed: circuit.clock_speed,
+			dependencies: circuit.dependencies.into(),
+			description: circuit.description,
+			camera_position: circuit.camera_position.into(),
+			synced: circuit.synced.into(),
+			player_data: circuit.player_data.into(),
+			hub_description: circuit.hub_description,
+			components: components?.into(),
+			wires: wires?.into(),
+		})
+	}
+}
+
+impl<'a> From<v10::CircuitData<'a>> for Circuit<'a> {
+	fn from(circuit: v10::CircuitData<'a>) -> Self {
+		Self {
+			version: 10,
+			custom_id: circuit.custom_id,
+			hub_id: circuit.hub_id,
+			gate: circuit.gate,
+			delay: circuit.delay,
+			menu_visible: circuit.menu_visible,
+			clock_speed: circuit.clock_speed,
+			dependencies: to_vec(&circuit.dependencies),
+			description: circuit.description,
+			camera_position: circuit.camera_position.into(),
+			synced: circuit.synced.into(),
+			player_data: to_vec(&circuit.player_data),
+			hub_description: circuit.hub_description,
+			components: circuit.components.iter().map(|component| {
+				let component = component.into_inner();
+				let (custom_id, static_states) = match component.custom_data {
+					Some(custom_data) => (Some(custom_data.id), Some(custom_data.static_states)),
+					None => (None, None),
+				};
+				Component {
+					kind: component.kind.into(),
+					position: component.position.into(),
+					rotation: component.rotation,
+					permanent_id: component.permanent_id,
+					custom_string: component.custom_string,
+					settings: to_vec(&component.settings),
+					ui_order: component.ui_order,
+					word_size: component.word_size,
+					custom_id,
+					extra: ComponentExtra::V10 {
+						buffer_size: component.buffer_size,
+						linked_components: component.linked_components,
+						static_states,
+						selected_programs: component.selected_programs,
+					},
+				}
+			}).collect(),
+			wires: circuit.wires.iter().map(|wire| {
+				let wire = wire.as_inner_ref();
+				Wire {
+					color: wire.color,
+					comment: wire.comment.clone(),
+					start: wire.start.into(),
+					segments: (&wire.segments).into(),
+					extra: WireExtra::V10,
+				}
+			}).collect(),
+		}
+	}
+}
+
+impl<'a> TryFrom<Circuit<'a>> for v10::CircuitData<'a> {
+	type Error = ConversionError;
+
+	fn try_from(circuit: Circuit<'a>) -> Result<Self, Self::Error> {
+		check_version(10, circuit.version)?;
+
+		let components: Result<Vec<_>, _> = circuit.components.into_iter().map(|component| {
+			let ComponentExtra::V10 { buffer_size, linked_components, static_states, selected_programs } = component.extra else {
+				return Err(ConversionError::VersionMismatch { expected: 10, found: component.extra.version() });
+			};
+			Ok(v10::Component {
+				kind: component.kind.try_into()?,
+				position: component.position.into(),
+				rotation: component.rotation,
+				permanent_id: component.permanent_id,
+				custom_string: component.custom_string,
+				settings: component.settings.into(),
+				buffer_size,
+				ui_order: component.ui_order,
+				word_size: component.word_size,
+				linked_components,
+				selected_programs,
+				custom_data: component.custom_id.map(|id| v10::CustomCompData {
+					id,
+					static_states: static_states.unwrap_or_else(|| vec![].into()),
+				}),
+			})
+		}).collect();
+
+		let wires: Result<Vec<_>, _> = circuit.wires.into_iter().map(|wire| {
+			let WireExtra::V10 = wire.extra else {
+				return Err(ConversionError::VersionMismatch { expected: 10, found: wire.extra.version() });
+			};
+			Ok(v10::Wire {
+				color: wire.color,
+				comment: wire.comment.clone(),
+				start: wire.start.into(),
+				segments: wire.segments.into(),
+			})
+		}).collect();
+
+		Ok(Self {
+			custom_id: circuit.custom_id,
+			hub_id: circuit.hub_id,
+			gate: circuit.gate,
+			delay: circuit.delay,
+			menu_visible: circuit.menu_visible,
+			clock_speed: circuit.clock_speed,
+			dependencies: circuit.dependencies.into(),
+			description: circuit.description,
+			camera_position: circuit.camera_position.into(),
+			synced: circuit.synced.into(),
+			player_data: circuit.player_data.into(),
+			hub_description: circuit.hub_description,
+			components: components?.into(),
+			wires: wires?.into(),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		Encode,
+		testing,
+		v6, v7, v8, v9, v10,
+	};
+	use super::{Circuit, ComponentExtra, ComponentKind, ConversionError, Point, Wire, WireDirection, WireExtra, WireSegment, WireSegments};
+
+	macro_rules! round_trip_kinds {
+		($version:ident) => {
+			for kind in (0..=u16::MAX).filter_map(|raw| $version::ComponentKind::try_from(raw).ok()) {
+				let converted = $version::ComponentKind::try_from(ComponentKind::from(kind)).unwrap();
+				assert_eq!(u16::from(converted), u16::from(kind), "{kind:?}");
+			}
+		};
+	}
+
+	#[test]
+	fn kinds() {
+		round_trip_kinds!(v7);
+		round_trip_kinds!(v8);
+		round_trip_kinds!(v9);
+		round_trip_kinds!(v10);
+
+		for kind in (0..=u16::MAX).filter_map(|raw| v6::ComponentKind::try_from(raw).ok()) {
+			let (converted, word_size) = ComponentKind::from_v6(kind);
+			let back = converted.to_v6(word_size).unwrap();
+			assert_eq!(ComponentKind::from_v6(back), (converted, word_size), "{kind:?}");
+		}
+
+		assert_eq!(ComponentKind::from_v6(v6::ComponentKind::Register8red), (ComponentKind::RegisterWord, 8));
+		assert!(matches!(ComponentKind::RegisterWord.to_v6(16), Some(v6::ComponentKind::Register16)));
+		assert!(ComponentKind::RegisterWord.to_v6(12).is_none());
+		assert!(ComponentKind::Clz.to_v6(0).is_none());
+
+		// v6's deleted kinds are not v10's.
+		assert_eq!(ComponentKind::from(v6::ComponentKind::Deleted1), ComponentKind::V6Deleted1);
+		assert_eq!(ComponentKind::from(v10::ComponentKind::Deleted1), ComponentKind::Deleted1);
+
+		assert!(matches!(v9::ComponentKind::try_from(ComponentKind::FrontPanel), Err(ConversionError::UnsupportedKind { version: 9, kind: ComponentKind::FrontPanel })));
+		assert!(matches!(v10::ComponentKind::try_from(ComponentKind::Ram), Err(ConversionError::UnsupportedKind { version: 10, .. })));
+		assert!(v7::ComponentKind::try_from(ComponentKind::Buffer1).is_err());
+	}
+
+	macro_rules! round_trip_circuit {
+		($version:ident, $circuit:expr) => {{
+			let original = $circuit;
+			let circuit: Circuit<'_> = original.clone().into();
+			let converted = $version::CircuitData::try_from(circuit.clone()).unwrap();
+
+			let mut expected = vec![];
+			original.encode(&mut expected);
+			let mut actual = vec![];
+			converted.encode(&mut actual);
+			assert_eq!(actual, expected, stringify!($version));
+			circuit
+		}};
+	}
+
+	#[test]
+	fn circuits() {
+		let circuit = round_trip_circuit!(v6, testing::v6_circuit());
+		round_trip_circuit!(v7, testing::v7_circuit());
+		round_trip_circuit!(v8, testing::v8_circuit());
+		round_trip_circuit!(v9, testing::v9_circuit());
+		let v10_circuit = round_trip_circuit!(v10, testing::v10_circuit());
+
+		// The kind of a v6 component is the kind without its width, and the width is its word size.
+		assert_eq!((circuit.components[1].kind, circuit.components[1].word_size), (ComponentKind::AndWord, 8));
+
+		let mut changed = circuit.clone();
+		changed.components[1].word_size = 16;
+		let converted = v6::CircuitData::try_from(changed).unwrap();
+		assert!(matches!(converted.components.get(1).unwrap().into_inner().kind, v6::ComponentKind::And16));
+
+		// Several v6 kinds are the same kind, and the one that the save had is kept.
+		let mut circuit = circuit;
+		circuit.components[1].kind = ComponentKind::RegisterWord;
+		let ComponentExtra::V6 { kind, .. } = &mut circuit.components[1].extra else { panic!("expected v6 fields") };
+		*kind = v6::ComponentKind::Register8red;
+		let converted = v6::CircuitData::try_from(circuit.clone()).unwrap();
+		assert!(matches!(converted.components.get(1).unwrap().into_inner().kind, v6::ComponentKind::Register8red));
+
+		circuit.components[1].kind = ComponentKind::Clz;
+		assert!(matches!(v6::CircuitData::try_from(circuit).unwrap_err(), ConversionError::UnsupportedKind { version: 6, kind: ComponentKind::Clz }));
+
+		let mut circuit = v10_circuit;
+		circuit.components[3].kind = ComponentKind::Ram;
+		assert!(matches!(v10::CircuitData::try_from(circuit).unwrap_err(), ConversionError::UnsupportedKind { version: 10, kind: ComponentKind::Ram }));
+	}
+
+	#[test]
+	fn steps_off_the_grid() {
+		let segment = |direction, length| WireSegment { length, direction };
+		let start = Point { x: i16::MAX - 31, y: i16::MIN + 1 };
+		assert_eq!(segment(WireDirection::Right, 31).step(start), Some(Point { x: i16::MAX, y: i16::MIN + 1 }));
+		assert_eq!(segment(WireDirection::DownRight, 31).step(Point { x: i16::MAX - 30, y: 0 }), None);
+		assert_eq!(segment(WireDirection::Up, 2).step(start), None);
+
+		let wire = |segments| Wire { color: 0, comment: "".into(), start, segments, extra: WireExtra::V10 };
+		assert_eq!(wire(WireSegments::Segments(vec![segment(WireDirection::Right, 31), segment(WireDirection::Left, 1)])).end(), Some(Point { x: i16::MAX - 1, y: i16::MIN + 1 }));
+		assert_eq!(wire(WireSegments::Segments(vec![segment(WireDirection::Right, 31), segment(WireDirection::Right, 1)])).end(), None);
+		assert_eq!(wire(WireSegments::TeleWireEnd(Point { x: 0, y: 0 })).end(), Some(Point { x: 0, y: 0 }));
+	}
+}
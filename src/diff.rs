@@ -0,0 +1,331 @@
+//! Structural comparison of two circuits.
+//!
+//! Components are matched by their `permanent_id`. Wires have no identity of their own,
+//! so they are matched by their geometric path, regardless of which end they were drawn from
+//! or how their segments were split.
+
+use crate::circuit::{
+	Circuit,
+	Component,
+	ComponentKind,
+	Point,
+	Wire,
+	WireSegments,
+};
+
+#[derive(Clone, Debug)]
+pub enum Change {
+	/// A field of the circuit itself changed, such as its `description` or `clock_speed`.
+	Metadata { field: &'static str, old: String, new: String },
+
+	ComponentAdded { permanent_id: u64, kind: ComponentKind, position: Point },
+
+	ComponentRemoved { permanent_id: u64, kind: ComponentKind, position: Point },
+
+	ComponentMoved { permanent_id: u64, old: Point, new: Point },
+
+	ComponentRotated { permanent_id: u64, old: u8, new: u8 },
+
+	/// A field of a component other than its position and rotation changed, such as its `settings` or `word_size`.
+	ComponentReconfigured { permanent_id: u64, field: &'static str, old: String, new: String },
+
+	WireAdded { path: WirePath },
+
+	WireRemoved { path: WirePath },
+
+	/// A wire with the same path has a different `color` or `comment`.
+	WireChanged { path: WirePath, field: &'static str, old: String, new: String },
+}
+
+/// The geometric path of a wire, independent of the direction it was drawn in.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum WirePath {
+	/// The points where the wire starts, turns and ends.
+	Segments(Vec<Point>),
+
+	/// The two ends of a wire that ends with [`WireSegments::TeleWireEnd`].
+	TeleWire(Point, Point),
+}
+
+impl std::fmt::Display for Change {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Metadata { field, old, new } => write!(f, "{field} changed from {old} to {new}"),
+
+			Self::ComponentAdded { permanent_id, kind, position } =>
+				write!(f, "added component {permanent_id} {kind} at ({}, {})", position.x, position.y),
+
+			Self::ComponentRemoved { permanent_id, kind, position } =>
+				write!(f, "removed component {permanent_id} {kind} at ({}, {})", position.x, position.y),
+
+			Self::ComponentMoved { permanent_id, old, new } =>
+				write!(f, "moved component {permanent_id} from ({}, {}) to ({}, {})", old.x, old.y, new.x, new.y),
+
+			Self::ComponentRotated { permanent_id, old, new } => write!(f, "rotated component {permanent_id} from {old} to {new}"),
+
+			Self::ComponentReconfigured { permanent_id, field, old, new } =>
+				write!(f, "component {permanent_id} {field} changed from {old} to {new}"),
+
+			Self::WireAdded { path } => write!(f, "added wire {path}"),
+
+			Self::WireRemoved { path } => write!(f, "removed wire {path}"),
+
+			Self::WireChanged { path, field, old, new } => write!(f, "wire {path} {field} changed from {old} to {new}"),
+		}
+	}
+}
+
+impl std::fmt::Display for WirePath {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Segments(points) => {
+				for (i, point) in points.iter().enumerate() {
+					if i > 0 {
+						f.write_str(" -> ")?;
+					}
+					write!(f, "({}, {})", point.x, point.y)?;
+				}
+				Ok(())
+			},
+
+			Self::TeleWire(start, end) => write!(f, "({}, {}) ~> ({}, {})", start.x, start.y, end.x, end.y),
+		}
+	}
+}
+
+impl WirePath {
+	pub fn of(wire: &Wire<'_>) -> Self {
+		Self::new(wire.start, &wire.segments)
+	}
+
+	/// The path of a wire that starts at `start` and follows `segments`.
+	pub fn new(start: Point, segments: &WireSegments) -> Self {
+		match segments {
+			WireSegments::TeleWireEnd(end) => Self::TeleWire(start.min(*end), start.max(*end)),
+
+			WireSegments::Segments(segments) => {
+				let mut points = vec![start];
+				let mut previous_direction = None;
+				for segment in segments {
+					if segment.length == 0 {
+						continue;
+					}
+
+					let Some(end) = segment.step(points[points.len() - 1]) else { break; };
+					if previous_direction == Some(segment.direction) {
+						let last = points.len() - 1;
+						points[last] = end;
+					}
+					else {
+						points.push(end);
+					}
+					previous_direction = Some(segment.direction);
+				}
+
+				let mut reversed = points.clone();
+				reversed.reverse();
+				Self::Segments(points.min(reversed))
+			},
+		}
+	}
+}
+
+/// Finds the changes that turn `old` into `new`.
+///
+/// Changes to the circuit come first, then changes to components in ascending order of `permanent_id`,
+/// then changes to wires in ascending order of their paths.
+/// The camera position is not compared, since it changes every time the circuit is viewed.
+pub fn diff(old: &Circuit<'_>, new: &Circuit<'_>) -> Vec<Change> {
+	let mut changes = vec![];
+	diff_metadata(old, new, &mut changes);
+	diff_components(&old.components, &new.components, &mut changes);
+	diff_wires(&old.wires, &new.wires, &mut changes);
+	changes
+}
+
+fn diff_metadata(old: &Circuit<'_>, new: &Circuit<'_>, changes: &mut Vec<Change>) {
+	let mut field = |field, old: String, new: String| if old != new {
+		changes.push(Change::Metadata { field, old, new });
+	};
+
+	field("version", old.version.to_string(), new.version.to_string());
+	field("custom_id", old.custom_id.to_string(), new.custom_id.to_string());
+	field("hub_id", old.hub_id.to_string(), new.hub_id.to_string());
+	field("gate", old.gate.to_string(), new.gate.to_string());
+	field("delay", old.delay.to_string(), new.delay.to_string());
+	field("menu_visible", old.menu_visible.to_string(), new.menu_visible.to_string());
+	field("clock_speed", old.clock_speed.to_string(), new.clock_speed.to_string());
+	field("dependencies", format!("{:?}", old.dependencies), format!("{:?}", new.dependencies));
+	field("description", format!("{:?}", old.description), format!("{:?}", new.description));
+	field("synced", format!("{:?}", old.synced), format!("{:?}", new.synced));
+	field("player_data", format!("{:?}", old.player_data), format!("{:?}", new.player_data));
+	field("hub_description", format!("{:?}", old.hub_description), format!("{:?}", new.hub_description));
+}
+
+fn diff_components(old: &[Component<'_>], new: &[Component<'_>], changes: &mut Vec<Change>) {
+	let old: std::collections::BTreeMap<_, _> = old.iter().map(|component| (component.permanent_id, component)).collect();
+	let new: std::collections::BTreeMap<_, _> = new.iter().map(|component| (component.permanent_id, component)).collect();
+
+	let permanent_ids: std::collections::BTreeSet<_> = old.keys().chain(new.keys()).copied().collect();
+	for permanent_id in permanent_ids {
+		let (old, new) = match (old.get(&permanent_id), new.get(&permanent_id)) {
+			(Some(old), Some(new)) => (old, new),
+
+			(Some(old), None) => {
+				changes.push(Change::ComponentRemoved { permanent_id, kind: old.kind, position: old.position });
+				continue;
+			},
+
+			(None, Some(new)) => {
+				changes.push(Change::ComponentAdded { permanent_id, kind: new.kind, position: new.position });
+				continue;
+			},
+
+			(None, None) => unreachable!(),
+		};
+
+		if old.position != new.position {
+			changes.push(Change::ComponentMoved { permanent_id, old: old.position, new: new.position });
+		}
+
+		if old.rotation != new.rotation {
+			changes.push(Change::ComponentRotated { permanent_id, old: old.rotation, new: new.rotation });
+		}
+
+		let mut field = |field, old: String, new: String| if old != new {
+			changes.push(Change::ComponentReconfigured { permanent_id, field, old, new });
+		};
+
+		field("kind", old.kind.to_string(), new.kind.to_string());
+		field("custom_id", format!("{:?}", old.custom_id), format!("{:?}", new.custom_id));
+		field("word_size", format!("{:?}", old.word_size), format!("{:?}", new.word_size));
+		field("settings", format!("{:?}", old.settings), format!("{:?}", new.settings));
+		field("custom_string", format!("{:?}", old.custom_string), format!("{:?}", new.custom_string));
+		field("ui_order", old.ui_order.to_string(), new.ui_order.to_string());
+	}
+}
+
+fn diff_wires(old: &[Wire<'_>], new: &[Wire<'_>], changes: &mut Vec<Change>) {
+	let old = wires_by_path(old);
+	let new = wires_by_path(new);
+
+	let paths: std::collections::BTreeSet<_> = old.keys().chain(new.keys()).collect();
+	for path in paths {
+		let old = old.get(path).map_or(&[][..], Vec::as_slice);
+		let new = new.get(path).map_or(&[][..], Vec::as_slice);
+
+		for (old, new) in old.iter().zip(new) {
+			if old.color != new.color {
+				changes.push(Change::WireChanged { path: path.clone(), field: "color", old: old.color.to_string(), new: new.color.to_string() });
+			}
+			if old.comment != new.comment {
+				changes.push(Change::WireChanged { path: path.clone(), field: "comment", old: format!("{:?}", old.comment), new: format!("{:?}", new.comment) });
+			}
+		}
+
+		for _ in new.len()..old.len() {
+			changes.push(Change::WireRemoved { path: path.clone() });
+		}
+		for _ in old.len()..new.len() {
+			changes.push(Change::WireAdded { path: path.clone() });
+		}
+	}
+}
+
+fn wires_by_path<'a, 'b>(wires: &'a [Wire<'b>]) -> std::collections::BTreeMap<WirePath, Vec<&'a Wire<'b>>> {
+	let mut result: std::collections::BTreeMap<_, Vec<_>> = Default::default();
+	for wire in wires {
+		result.entry(WirePath::of(wire)).or_default().push(wire);
+	}
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		circuit::{Circuit, Point, Wire, WireDirection, WireSegment, WireSegments},
+		testing,
+	};
+	use super::{Change, WirePath, diff};
+
+	fn segments(segments: &[(WireDirection, u8)]) -> WireSegments {
+		WireSegments::Segments(segments.iter().map(|&(direction, length)| WireSegment { length, direction }).collect())
+	}
+
+	fn circuit() -> Circuit<'static> {
+		testing::not_gate().into()
+	}
+
+	#[test]
+	fn same_circuit() {
+		let old = circuit();
+		let mut new = old.clone();
+		new.camera_position = Point { x: 5, y: 5 };
+		assert!(diff(&old, &new).is_empty());
+	}
+
+	#[test]
+	fn wire_paths() {
+		let start = Point { x: 0, y: 0 };
+		let path = WirePath::new(start, &segments(&[(WireDirection::Right, 3), (WireDirection::Down, 2), (WireDirection::Right, 0)]));
+		assert_eq!(path, WirePath::Segments(vec![start, Point { x: 3, y: 0 }, Point { x: 3, y: 2 }]));
+
+		let reversed = WirePath::new(Point { x: 3, y: 2 }, &segments(&[(WireDirection::Up, 2), (WireDirection::Left, 3)]));
+		assert_eq!(reversed, path);
+
+		let split = WirePath::new(start, &segments(&[(WireDirection::Right, 1), (WireDirection::Right, 2), (WireDirection::Down, 2)]));
+		assert_eq!(split, path);
+
+		let tele_wire = WirePath::new(Point { x: 3, y: 2 }, &WireSegments::TeleWireEnd(start));
+		assert_eq!(tele_wire, WirePath::new(start, &WireSegments::TeleWireEnd(Point { x: 3, y: 2 })));
+		assert_ne!(tele_wire, path);
+	}
+
+	#[test]
+	fn changes() {
+		let old = circuit();
+		let mut new = old.clone();
+		new.clock_speed = 100;
+		new.components[1].rotation = 1;
+		new.components[1].settings = vec![1];
+		new.components.remove(0);
+		let wire = new.wires[0].clone();
+		new.wires[0].color = 2;
+		new.wires.push(wire.clone());
+		new.wires.push(Wire { start: Point { x: 20, y: 20 }, segments: segments(&[(WireDirection::Down, 1)]), ..wire });
+
+		let path = WirePath::of(&old.wires[0]);
+		let changes: Vec<_> = diff(&old, &new).iter().map(ToString::to_string).collect();
+		assert_eq!(changes, [
+			"clock_speed changed from 0 to 100",
+			"removed component 1 LevelInput1 at (0, 0)",
+			"rotated component 2 from 0 to 1",
+			"component 2 settings changed from [] to [1]",
+			&format!("wire {path} color changed from 0 to 2"),
+			&format!("added wire {path}"),
+			"added wire (20, 20) -> (20, 21)",
+		]);
+
+		let reverse = diff(&new, &old);
+		assert!(matches!(reverse[1], Change::ComponentAdded { permanent_id: 1, .. }));
+		assert_eq!(reverse.iter().filter(|change| matches!(change, Change::WireRemoved { .. })).count(), 2);
+	}
+
+	#[test]
+	fn decoded_saves() {
+		for save in testing::decoded_saves() {
+			let version = save.version();
+			let old = save.into_circuit();
+			assert!(diff(&old, &old.clone()).is_empty(), "v{version}");
+
+			let mut new = old.clone();
+			new.components.remove(1);
+			new.wires[0].color = 5;
+			let changes: Vec<_> = diff(&old, &new).iter().map(ToString::to_string).collect();
+			assert_eq!(changes, [
+				format!("removed component 2 {} at (6, 0)", old.components[1].kind),
+				"wire (1, 0) -> (4, 0) -> (4, 2) -> (5, 1) color changed from 2 to 5".to_owned(),
+			], "v{version}");
+		}
+	}
+}
@@ -0,0 +1,300 @@
+//! The footprints and pins of components of every save version.
+//!
+//! Saves do not record where a component's pins are, and the game's own footprints and pin offsets
+//! are not available to this crate. Instead, every kind of component is laid out by the same convention,
+//! which [`crate::builder`] places its wires by. So circuits made with the builder agree with this module,
+//! but the pins of components placed in the game are generally not where this module puts them,
+//! and their wires do not reach them.
+//!
+//! Each component is a box three cells wide with a row for each of its pins, starting at the component's position.
+//! Input `i` is on the left edge at `(-1, i)` and output `i` is on the right edge at `(1, i)`.
+//! Each step of the component's `rotation` turns the box and its pins a quarter turn clockwise
+//! around the component's position, taking `(x, y)` to `(-y, x)`.
+//!
+//! The number and widths of the pins of each kind are in a table for v10,
+//! in the order that [`crate::sim`] reads and writes them.
+//! A kind of an older version has the pins of the v10 kind of the same [`circuit::ComponentKind`].
+//! The `word_size` that [`circuit::ComponentKind::from_v6`] finds in the name of a v6 kind, such as `Add8`,
+//! is the width of its [`PinWidth::Word`] pins.
+
+use crate::{
+	circuit,
+	v6, v7, v8, v9, v10,
+};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum PinDirection {
+	Input,
+	Output,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PinWidth {
+	Bits(u32),
+
+	/// The component's `word_size`.
+	Word,
+}
+
+impl PinWidth {
+	pub fn bits(self, word_size: u32) -> u32 {
+		match self {
+			Self::Bits(bits) => bits,
+			Self::Word => word_size,
+		}
+	}
+}
+
+/// The pins of a kind of component.
+#[derive(Clone, Copy, Debug)]
+pub struct Layout {
+	pub inputs: &'static [PinWidth],
+	pub outputs: &'static [PinWidth],
+}
+
+impl Layout {
+	/// The number of rows of the component's box.
+	pub fn rows(&self) -> i16 {
+		let rows = self.inputs.len().max(self.outputs.len()).max(1);
+		rows.try_into().unwrap_or(i16::MAX)
+	}
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Pin {
+	pub direction: PinDirection,
+	pub index: usize,
+	pub position: circuit::Point,
+	pub width: PinWidth,
+}
+
+/// The layout of the given kind of component, or `None` if its pins are not known.
+///
+/// Custom components have the pins of their schematic, so their pins are never known here.
+pub fn layout(kind: circuit::ComponentKind) -> Option<Layout> {
+	v10_layout(kind.try_into().ok()?)
+}
+
+/// The cells that a component of the given kind at `position` with the given `rotation` covers.
+///
+/// Components whose pins are not known cover a single row.
+pub fn footprint(kind: circuit::ComponentKind, rotation: u8, position: circuit::Point) -> Vec<circuit::Point> {
+	let rows = layout(kind).map_or(1, |layout| layout.rows());
+	let mut cells = vec![];
+	for y in 0..rows {
+		for x in -1..=1 {
+			let (dx, dy) = rotate((x, y), rotation);
+			cells.push(circuit::Point { x: position.x + dx, y: position.y + dy });
+		}
+	}
+	cells
+}
+
+/// The pins of a component of the given kind at `position` with the given `rotation`, inputs first, or `None` if they are not known.
+pub fn pins(kind: circuit::ComponentKind, rotation: u8, position: circuit::Point) -> Option<Vec<Pin>> {
+	let layout = layout(kind)?;
+	let mut pins = vec![];
+	for (direction, widths) in [(PinDirection::Input, layout.inputs), (PinDirection::Output, layout.outputs)] {
+		for ((index, i), &pin_width) in (0..).zip(0..).zip(widths) {
+			pins.push(Pin { direction, index, position: pin_position(position, rotation, direction, i), width: pin_width });
+		}
+	}
+	Some(pins)
+}
+
+/// The position of the given pin of a component at `position` with the given `rotation`.
+pub fn pin_position(position: circuit::Point, rotation: u8, direction: PinDirection, index: i16) -> circuit::Point {
+	let x = match direction {
+		PinDirection::Input => -1,
+		PinDirection::Output => 1,
+	};
+	let (dx, dy) = rotate((x, index), rotation);
+	circuit::Point { x: position.x + dx, y: position.y + dy }
+}
+
+fn rotate((mut x, mut y): (i16, i16), rotation: u8) -> (i16, i16) {
+	for _ in 0..rotation % 4 {
+		(x, y) = (-y, x);
+	}
+	(x, y)
+}
+
+pub fn v6_layout(kind: v6::ComponentKind) -> Option<Layout> {
+	layout(kind.into())
+}
+
+pub fn v7_layout(kind: v7::ComponentKind) -> Option<Layout> {
+	layout(kind.into())
+}
+
+pub fn v8_layout(kind: v8::ComponentKind) -> Option<Layout> {
+	layout(kind.into())
+}
+
+pub fn v9_layout(kind: v9::ComponentKind) -> Option<Layout> {
+	layout(kind.into())
+}
+
+#[allow(clippy::too_many_lines)]
+pub fn v10_layout(kind: v10::ComponentKind) -> Option<Layout> {
+	const B: PinWidth = PinWidth::Bits(1);
+	const B8: PinWidth = PinWidth::Bits(8);
+	const W: PinWidth = PinWidth::Word;
+
+	let (inputs, outputs): (&[_], &[_]) = match kind {
+		v10::ComponentKind::Off |
+		v10::ComponentKind::On |
+		v10::ComponentKind::LevelInput1 => (&[], &[B]),
+
+		v10::ComponentKind::NotBit |
+		v10::ComponentKind::DelayLineBit => (&[B], &[B]),
+
+		v10::ComponentKind::AndBit |
+		v10::ComponentKind::NandBit |
+		v10::ComponentKind::OrBit |
+		v10::ComponentKind::NorBit |
+		v10::ComponentKind::XorBit |
+		v10::ComponentKind::XnorBit |
+		v10::ComponentKind::SwitchBit => (&[B, B], &[B]),
+
+		v10::ComponentKind::And3Bit |
+		v10::ComponentKind::Or3Bit |
+		v10::ComponentKind::RegisterBit => (&[B, B, B], &[B]),
+
+		v10::ComponentKind::FullAdder => (&[B, B, B], &[B, B]),
+
+		v10::ComponentKind::MakerBit2 => (&[B, B], &[PinWidth::Bits(2)]),
+		v10::ComponentKind::MakerBit4 => (&[B, B, B, B], &[PinWidth::Bits(4)]),
+		v10::ComponentKind::MakerBit8 => (&[B, B, B, B, B, B, B, B], &[B8]),
+		v10::ComponentKind::SplitterBit2 => (&[PinWidth::Bits(2)], &[B, B]),
+		v10::ComponentKind::SplitterBit4 => (&[PinWidth::Bits(4)], &[B, B, B, B]),
+		v10::ComponentKind::SplitterBit8 => (&[B8], &[B, B, B, B, B, B, B, B]),
+
+		v10::ComponentKind::NotWord |
+		v10::ComponentKind::Neg |
+		v10::ComponentKind::Inc |
+		v10::ComponentKind::Clz |
+		v10::ComponentKind::Ctz |
+		v10::ComponentKind::DelayLineWord => (&[W], &[W]),
+
+		v10::ComponentKind::OrWord |
+		v10::ComponentKind::AndWord |
+		v10::ComponentKind::NandWord |
+		v10::ComponentKind::NorWord |
+		v10::ComponentKind::XorWord |
+		v10::ComponentKind::XnorWord |
+		v10::ComponentKind::Mod => (&[W, W], &[W]),
+
+		v10::ComponentKind::Lsl |
+		v10::ComponentKind::Lsr |
+		v10::ComponentKind::Rol |
+		v10::ComponentKind::Ror |
+		v10::ComponentKind::Asr => (&[W, B8], &[W]),
+
+		v10::ComponentKind::SwitchWord |
+		v10::ComponentKind::Counter => (&[B, W], &[W]),
+
+		v10::ComponentKind::Equal |
+		v10::ComponentKind::LessU |
+		v10::ComponentKind::LessS => (&[W, W], &[B]),
+
+		v10::ComponentKind::Add => (&[W, W, B], &[W, B]),
+
+		v10::ComponentKind::Mul |
+		v10::ComponentKind::Div => (&[W, W], &[W, W]),
+
+		v10::ComponentKind::RegisterWord => (&[B, B, W], &[W]),
+		v10::ComponentKind::Mux => (&[B, W, W], &[W]),
+		v10::ComponentKind::RamFast => (&[B, B, W, W], &[W]),
+
+		v10::ComponentKind::Decoder1 => (&[B], &[B, B]),
+		v10::ComponentKind::Decoder2 => (&[B, B], &[B, B, B, B]),
+		v10::ComponentKind::Decoder3 => (&[B, B, B], &[B, B, B, B, B, B, B, B]),
+
+		v10::ComponentKind::Constant |
+		v10::ComponentKind::LevelInputWord => (&[], &[W]),
+
+		v10::ComponentKind::LevelInput2Pin => (&[], &[B, B]),
+		v10::ComponentKind::LevelInput3Pin => (&[], &[B, B, B]),
+		v10::ComponentKind::LevelInput4Pin => (&[], &[B, B, B, B]),
+
+		v10::ComponentKind::LevelOutput1 |
+		v10::ComponentKind::ProbeWireBit => (&[B], &[]),
+
+		v10::ComponentKind::LevelOutputWord |
+		v10::ComponentKind::ProbeWireWord => (&[W], &[]),
+
+		v10::ComponentKind::LevelOutput2Pin => (&[B, B], &[]),
+		v10::ComponentKind::LevelOutput3Pin => (&[B, B, B], &[]),
+		v10::ComponentKind::LevelOutput4Pin => (&[B, B, B, B], &[]),
+
+		_ => return None,
+	};
+	Some(Layout { inputs, outputs })
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		circuit,
+		v6, v9, v10,
+	};
+	use super::{PinDirection, footprint, pin_position, pins, v6_layout, v9_layout, v10_layout};
+
+	fn v10_kinds() -> impl Iterator<Item = v10::ComponentKind> {
+		(0..=u16::MAX).filter_map(|raw| v10::ComponentKind::try_from(raw).ok())
+	}
+
+	#[test]
+	fn pins_are_on_the_edge_of_the_footprint() {
+		let position = circuit::Point { x: 5, y: -3 };
+		for kind in v10_kinds() {
+			let kind = circuit::ComponentKind::from(kind);
+			for rotation in 0..4 {
+				let footprint = footprint(kind, rotation, position);
+				let Some(pins) = pins(kind, rotation, position) else { continue; };
+
+				let mut positions: Vec<_> = pins.iter().map(|pin| pin.position).collect();
+				positions.sort_unstable();
+				positions.dedup();
+				assert_eq!(positions.len(), pins.len(), "{kind} has two pins in the same cell");
+
+				for pin in &pins {
+					assert!(footprint.contains(&pin.position), "{kind} pin {pin:?} is outside its footprint");
+					assert_ne!(pin.position, position, "{kind} pin {pin:?} is at the component's position");
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn rotation() {
+		let position = circuit::Point { x: 0, y: 0 };
+		let pin = |rotation| pin_position(position, rotation, PinDirection::Output, 2);
+		assert_eq!(pin(0), circuit::Point { x: 1, y: 2 });
+		assert_eq!(pin(1), circuit::Point { x: -2, y: 1 });
+		assert_eq!(pin(2), circuit::Point { x: -1, y: -2 });
+		assert_eq!(pin(3), circuit::Point { x: 2, y: -1 });
+		assert_eq!(pin(4), pin(0));
+
+		let kind = circuit::ComponentKind::Add;
+		assert_eq!(footprint(kind, 0, position).len(), 9);
+		assert_eq!(footprint(kind, 5, position), footprint(kind, 1, position));
+	}
+
+	#[test]
+	fn older_versions() {
+		let v9 = v9_layout(v9::ComponentKind::Add).unwrap();
+		let v10 = v10_layout(v10::ComponentKind::Add).unwrap();
+		assert_eq!((v9.inputs, v9.outputs), (v10.inputs, v10.outputs));
+
+		// v6 kinds with a width in their name have the pins of the kind without it, and the width is the word size.
+		let v6 = v6_layout(v6::ComponentKind::Add8).unwrap();
+		assert_eq!((v6.inputs, v6.outputs), (v10.inputs, v10.outputs));
+		assert_eq!(circuit::ComponentKind::from_v6(v6::ComponentKind::Add8), (circuit::ComponentKind::Add, 8));
+		let widths: Vec<_> = v6.inputs.iter().chain(v6.outputs).map(|width| width.bits(8)).collect();
+		assert_eq!(widths, [8, 8, 1, 8, 1]);
+
+		assert!(v10_layout(v10::ComponentKind::Custom).is_none());
+	}
+}
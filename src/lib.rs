@@ -3,10 +3,33 @@ mod parser;
 pub use parser::{
 	Either,
 	Encode,
+	Input,
+	IntoOwned,
 	Parse,
+	ParseError,
+	ParseErrorKind,
 	Slice, SliceIter,
 };
 
+pub mod builder;
+pub mod circuit;
+pub mod diff;
+pub mod layout;
+pub mod lint;
+pub mod merge;
+pub mod migrate;
+pub mod netlist;
+pub mod render;
+pub mod route;
+pub mod schematics;
+pub mod sim;
+pub mod stream;
+pub mod text;
+pub mod textconv;
+pub mod verilog;
+#[cfg(test)]
+mod testing;
+
 pub mod v6;
 pub mod v7;
 pub mod v8;
@@ -23,36 +46,200 @@ pub enum CircuitData {
 }
 
 impl<'a> Parse<'a> for CircuitData {
-	fn parse(input: &mut &'a [u8]) -> Self {
-		let (&version, rest) = input.split_first().unwrap();
-		*input = rest;
+	fn parse(input: &mut Input<'a>) -> Result<Self, ParseError> {
+		let original_input = *input;
+		let version = u8::parse(input)?;
+
+		let decompress = |input: &mut Input<'a>| {
+			let compressed = input.take(input.bytes.len())?;
+			snap::raw::Decoder::new().decompress_vec(compressed.bytes).map_err(|err| compressed.error(ParseErrorKind::Decompress(err)))
+		};
+
 		match version {
 			6 => {
-				let input = snap::raw::Decoder::new().decompress_vec(input).unwrap();
-				Self::V6(input)
+				let input = decompress(input)?;
+				Ok(Self::V6(input))
 			},
 
 			7 => {
-				let input = snap::raw::Decoder::new().decompress_vec(input).unwrap();
-				Self::V7(input)
+				let input = decompress(input)?;
+				Ok(Self::V7(input))
 			},
 
 			8 => {
-				let input = snap::raw::Decoder::new().decompress_vec(input).unwrap();
-				Self::V8(input)
+				let input = decompress(input)?;
+				Ok(Self::V8(input))
 			},
 
 			9 => {
-				let input = snap::raw::Decoder::new().decompress_vec(input).unwrap();
-				Self::V9(input)
+				let input = decompress(input)?;
+				Ok(Self::V9(input))
 			},
 
 			10 => {
-				let input = snap::raw::Decoder::new().decompress_vec(input).unwrap();
-				Self::V10(input)
+				let input = decompress(input)?;
+				Ok(Self::V10(input))
 			},
 
-			version => panic!("version {version} unsupported"),
+			version => Err(original_input.error(ParseErrorKind::UnsupportedVersion(version))),
+		}
+	}
+}
+
+impl CircuitData {
+	pub fn version(&self) -> u8 {
+		match self {
+			Self::V6(_) => 6,
+			Self::V7(_) => 7,
+			Self::V8(_) => 8,
+			Self::V9(_) => 9,
+			Self::V10(_) => 10,
+		}
+	}
+
+	/// Parses the decompressed save into the version-independent [`circuit::Circuit`] model.
+	pub fn parse_circuit(&self) -> Result<circuit::Circuit<'_>, ParseError> {
+		Ok(match self {
+			Self::V6(input) => v6::CircuitData::parse_from(input)?.into(),
+			Self::V7(input) => v7::CircuitData::parse_from(input)?.into(),
+			Self::V8(input) => v8::CircuitData::parse_from(input)?.into(),
+			Self::V9(input) => v9::CircuitData::parse_from(input)?.into(),
+			Self::V10(input) => v10::CircuitData::parse_from(input)?.into(),
+		})
+	}
+}
+
+/// A parsed save of any version that owns all of its data.
+#[derive(Clone, Debug)]
+pub enum Save {
+	V6(v6::CircuitData<'static>),
+	V7(v7::CircuitData<'static>),
+	V8(v8::CircuitData<'static>),
+	V9(v9::CircuitData<'static>),
+	V10(v10::CircuitData<'static>),
+}
+
+impl Save {
+	pub fn version(&self) -> u8 {
+		match self {
+			Self::V6(_) => 6,
+			Self::V7(_) => 7,
+			Self::V8(_) => 8,
+			Self::V9(_) => 9,
+			Self::V10(_) => 10,
+		}
+	}
+
+	/// Converts the save into the version-independent [`circuit::Circuit`] model.
+	pub fn into_circuit(self) -> circuit::Circuit<'static> {
+		match self {
+			Self::V6(circuit) => circuit.into(),
+			Self::V7(circuit) => circuit.into(),
+			Self::V8(circuit) => circuit.into(),
+			Self::V9(circuit) => circuit.into(),
+			Self::V10(circuit) => circuit.into(),
+		}
+	}
+
+	/// Encodes and compresses the save in its own version.
+	pub fn encode_final(&self) -> Vec<u8> {
+		match self {
+			Self::V6(circuit) => circuit.encode_final(),
+			Self::V7(circuit) => circuit.encode_final(),
+			Self::V8(circuit) => circuit.encode_final(),
+			Self::V9(circuit) => circuit.encode_final(),
+			Self::V10(circuit) => circuit.encode_final(),
+		}
+	}
+}
+
+/// Decompresses and parses the contents of a save file of any supported version.
+///
+/// The offsets of errors from parsing the circuit itself are relative to the decompressed save.
+pub fn from_bytes(bytes: &[u8]) -> Result<Save, ParseError> {
+	Ok(match CircuitData::parse_from(bytes)? {
+		CircuitData::V6(input) => Save::V6(v6::CircuitData::parse_from(&input)?.into_owned()),
+		CircuitData::V7(input) => Save::V7(v7::CircuitData::parse_from(&input)?.into_owned()),
+		CircuitData::V8(input) => Save::V8(v8::CircuitData::parse_from(&input)?.into_owned()),
+		CircuitData::V9(input) => Save::V9(v9::CircuitData::parse_from(&input)?.into_owned()),
+		CircuitData::V10(input) => Save::V10(v10::CircuitData::parse_from(&input)?.into_owned()),
+	})
+}
+
+/// Reads, decompresses and parses a save of any supported version from `reader`.
+///
+/// The save is decompressed as it is read, so only the decompressed save is held in memory in full.
+/// Use [`stream::stream`] to parse the components and wires one at a time instead.
+pub fn from_reader(mut reader: impl std::io::Read) -> Result<Save, LoadError> {
+	let mut version = [0];
+	reader.read_exact(&mut version).map_err(LoadError::Read)?;
+	let decompress = || stream::decompress_all(reader).map_err(LoadError::Read);
+	Ok(match version[0] {
+		6 => Save::V6(v6::CircuitData::parse_from(&decompress()?).map_err(LoadError::Parse)?.into_owned()),
+		7 => Save::V7(v7::CircuitData::parse_from(&decompress()?).map_err(LoadError::Parse)?.into_owned()),
+		8 => Save::V8(v8::CircuitData::parse_from(&decompress()?).map_err(LoadError::Parse)?.into_owned()),
+		9 => Save::V9(v9::CircuitData::parse_from(&decompress()?).map_err(LoadError::Parse)?.into_owned()),
+		10 => Save::V10(v10::CircuitData::parse_from(&decompress()?).map_err(LoadError::Parse)?.into_owned()),
+		version => return Err(LoadError::Parse(ParseError { offset: 0, field: None, kind: ParseErrorKind::UnsupportedVersion(version) })),
+	})
+}
+
+/// Reads, decompresses and parses the save file at `path`.
+pub fn load(path: impl AsRef<std::path::Path>) -> Result<Save, LoadError> {
+	let bytes = std::fs::read(path).map_err(LoadError::Read)?;
+	from_bytes(&bytes).map_err(LoadError::Parse)
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+	Read(std::io::Error),
+	Parse(ParseError),
+}
+
+impl std::fmt::Display for LoadError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Read(_) => f.write_str("could not read save"),
+			Self::Parse(_) => f.write_str("could not parse save"),
 		}
 	}
 }
+
+impl std::error::Error for LoadError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Read(err) => Some(err),
+			Self::Parse(err) => Some(err),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{Encode, IntoOwned, Parse, testing, v6, v7, v8, v9, v10};
+
+	/// Parses the encoded circuit from a buffer that is dropped before the owned circuit is encoded again.
+	macro_rules! owned_outlives_input {
+		($version:ident, $circuit:expr) => {{
+			let mut encoded = vec![];
+			$circuit.encode(&mut encoded);
+
+			let buffer = encoded.clone();
+			let owned: $version::CircuitData<'static> = $version::CircuitData::parse_from(&buffer).unwrap().into_owned();
+			drop(buffer);
+
+			let mut out = vec![];
+			owned.encode(&mut out);
+			assert_eq!(out, encoded, stringify!($version));
+		}};
+	}
+
+	#[test]
+	fn into_owned() {
+		owned_outlives_input!(v6, testing::v6_circuit());
+		owned_outlives_input!(v7, testing::v7_circuit());
+		owned_outlives_input!(v8, testing::v8_circuit());
+		owned_outlives_input!(v9, testing::v9_circuit());
+		owned_outlives_input!(v10, testing::v10_circuit());
+	}
+}
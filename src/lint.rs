@@ -0,0 +1,357 @@
+//! Checks for mistakes in circuits that the game does not prevent.
+//!
+//! Checks return [`Diagnostic`]s rather than printing or panicking, so that callers decide what to do with them.
+
+use crate::{
+	circuit::{
+		Circuit,
+		Point,
+		WireSegments,
+	},
+	layout::PinDirection,
+	netlist::{
+		NetId,
+		Netlist,
+		PinId,
+	},
+};
+
+#[derive(Clone, Debug)]
+pub enum Diagnostic {
+	/// Two wires run along the same cells from `overlap.0` to `overlap.1`.
+	///
+	/// Wires that only touch at a single cell, such as when one starts where the other ends, do not overlap.
+	WireOverlap { wires: [WireRef; 2], overlap: (Point, Point) },
+
+	/// Two wires connect the same two points without overlapping.
+	DuplicateWire { wires: [WireRef; 2] },
+
+	/// A net connects pins of different widths. `point` is the smallest point on the net.
+	WidthMismatch { point: Point, pins: Vec<PinRef> },
+
+	/// A pin is not connected to any other pin.
+	///
+	/// This is a [`Severity::Warning`], since circuits often leave pins such as carry outputs unused.
+	UnconnectedPin { pin: PinRef },
+}
+
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+	/// Something that is often intended, but might be a mistake.
+	Warning,
+
+	/// Something that is almost certainly a mistake.
+	Error,
+}
+
+impl Diagnostic {
+	pub fn severity(&self) -> Severity {
+		match self {
+			Self::WireOverlap { .. } |
+			Self::DuplicateWire { .. } |
+			Self::WidthMismatch { .. } => Severity::Error,
+
+			Self::UnconnectedPin { .. } => Severity::Warning,
+		}
+	}
+}
+
+/// A wire that a [`Diagnostic`] is about.
+#[derive(Clone, Copy, Debug)]
+pub struct WireRef {
+	/// The index of the wire in [`Circuit::wires`].
+	pub index: usize,
+	pub start: Point,
+	pub end: Point,
+}
+
+/// A pin of a component that a [`Diagnostic`] is about.
+#[derive(Clone, Copy, Debug)]
+pub struct PinRef {
+	pub permanent_id: u64,
+	pub direction: PinDirection,
+	pub index: usize,
+	pub position: Point,
+	pub width: u32,
+}
+
+impl std::fmt::Display for Diagnostic {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::WireOverlap { wires: [first, second], overlap: (start, end) } =>
+				write!(f, "{first} and {second} overlap from ({}, {}) to ({}, {})", start.x, start.y, end.x, end.y),
+
+			Self::DuplicateWire { wires: [first, second] } =>
+				write!(f, "{first} and {second} connect the same points"),
+
+			Self::WidthMismatch { point, pins } => {
+				write!(f, "net at ({}, {}) connects pins of different widths:", point.x, point.y)?;
+				for (i, pin) in pins.iter().enumerate() {
+					write!(f, "{} {pin} ({} bits)", if i == 0 { "" } else { "," }, pin.width)?;
+				}
+				Ok(())
+			},
+
+			Self::UnconnectedPin { pin } =>
+				write!(f, "{pin} at ({}, {}) is not connected", pin.position.x, pin.position.y),
+		}
+	}
+}
+
+impl std::fmt::Display for WireRef {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let Self { index, start, end } = self;
+		write!(f, "wire {index} ({}, {}) -> ({}, {})", start.x, start.y, end.x, end.y)
+	}
+}
+
+impl std::fmt::Display for PinRef {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let Self { permanent_id, direction, index, .. } = self;
+		let direction = match direction {
+			PinDirection::Input => "input",
+			PinDirection::Output => "output",
+		};
+		write!(f, "component {permanent_id} {direction} {index}")
+	}
+}
+
+/// Runs the checks that hold for circuits made in the game.
+///
+/// [`net_widths`] is not one of them. It finds pins where [`crate::layout`] puts them, which is not where the game does,
+/// so it only gives meaningful results for circuits made with [`crate::builder`].
+pub fn lint(circuit: &Circuit<'_>) -> Vec<Diagnostic> {
+	let mut diagnostics = vec![];
+	wire_overlaps(circuit, &mut diagnostics);
+	diagnostics
+}
+
+/// Finds wires that overlap each other, and wires that connect the same two points.
+///
+/// Wires that end with [`WireSegments::TeleWireEnd`], and wires whose segments leave the range of a [`Point`], are not checked.
+/// A wire that runs over itself does not overlap.
+pub fn wire_overlaps(circuit: &Circuit<'_>, diagnostics: &mut Vec<Diagnostic>) {
+	// The unit steps that each wire takes, from its start to its end.
+	let paths: Vec<_> =
+		circuit.wires.iter()
+		.map(|wire| match &wire.segments {
+			WireSegments::TeleWireEnd(_) => None,
+			WireSegments::Segments(segments) => {
+				let mut steps = vec![];
+				let mut point = wire.start;
+				for segment in segments {
+					let (dx, dy) = segment.direction.delta();
+					for _ in 0..segment.length {
+						let next = Point { x: point.x.checked_add(dx)?, y: point.y.checked_add(dy)? };
+						steps.push((point, next));
+						point = next;
+					}
+				}
+				Some(steps)
+			},
+		})
+		.collect();
+
+	let end = |index: usize| {
+		let start = circuit.wires[index].start;
+		paths[index].as_deref().and_then(<[_]>::last).map_or(start, |&(_, end)| end)
+	};
+	let wire_ref = |index: usize| WireRef { index, start: circuit.wires[index].start, end: end(index) };
+
+	let mut by_step: std::collections::BTreeMap<_, std::collections::BTreeSet<_>> = Default::default();
+	for (i, steps) in paths.iter().enumerate() {
+		for &(from, to) in steps.iter().flatten() {
+			by_step.entry((from.min(to), from.max(to))).or_default().insert(i);
+		}
+	}
+
+	// Pairs of different wires, with the lower index first.
+	let mut overlapping: std::collections::BTreeSet<_> = Default::default();
+	for wires in by_step.values() {
+		for (i, &first) in wires.iter().enumerate() {
+			for &second in wires.iter().skip(i + 1) {
+				overlapping.insert((first, second));
+			}
+		}
+	}
+
+	for &(first, second) in &overlapping {
+		let first_steps = paths[first].as_deref().unwrap_or_default();
+		let second_steps: std::collections::BTreeSet<_> =
+			paths[second].iter().flatten()
+			.map(|&(from, to)| (from.min(to), from.max(to)))
+			.collect();
+
+		// Split the shared steps into runs of consecutive steps in the same direction, and report each run once,
+		// even if the first wire runs over it more than once.
+		let mut runs: Vec<(Point, Point, (i16, i16))> = vec![];
+		for &(from, to) in first_steps {
+			if !second_steps.contains(&(from.min(to), from.max(to))) {
+				continue;
+			}
+
+			let direction = (to.x - from.x, to.y - from.y);
+			match runs.last_mut() {
+				Some((_, end, run_direction)) if *end == from && *run_direction == direction => *end = to,
+				_ => runs.push((from, to, direction)),
+			}
+		}
+		let mut reported: std::collections::BTreeSet<_> = Default::default();
+		for (start, end, _) in runs {
+			if reported.insert((start.min(end), start.max(end))) {
+				diagnostics.push(Diagnostic::WireOverlap { wires: [wire_ref(first), wire_ref(second)], overlap: (start, end) });
+			}
+		}
+	}
+
+	let mut by_ends: std::collections::BTreeMap<_, _> = Default::default();
+	for (i, wire) in circuit.wires.iter().enumerate() {
+		if paths[i].is_none() {
+			continue;
+		}
+
+		let start = wire.start;
+		let end = end(i);
+		if let Some(previous) = by_ends.insert((start.min(end), start.max(end)), i) && !overlapping.contains(&(previous, i)) {
+			diagnostics.push(Diagnostic::DuplicateWire { wires: [wire_ref(previous), wire_ref(i)] });
+		}
+	}
+}
+
+/// Finds nets that connect pins of different widths, and pins that are not connected to any other pin.
+///
+/// The netlist's pins are where [`crate::layout`] puts them, so this is only meaningful for circuits made with [`crate::builder`].
+/// Only the pins that the netlist knows are checked, so custom components are only checked if it was made with their schematics.
+pub fn net_widths(netlist: &Netlist, diagnostics: &mut Vec<Diagnostic>) {
+	for (i, net) in netlist.nets.iter().enumerate() {
+		let pin_ref = |id: &PinId| {
+			let pin = netlist.pin(*id);
+			PinRef {
+				permanent_id: netlist.components[id.component].permanent_id,
+				direction: id.direction,
+				index: id.index,
+				position: pin.position,
+				width: pin.width,
+			}
+		};
+
+		match &*net.pins {
+			[] => (),
+
+			[pin] => diagnostics.push(Diagnostic::UnconnectedPin { pin: pin_ref(pin) }),
+
+			pins => if netlist.width(NetId(i)).is_none() {
+				diagnostics.push(Diagnostic::WidthMismatch { point: net.points[0], pins: pins.iter().map(pin_ref).collect() });
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		builder::{CircuitBuilder, at},
+		circuit::{Circuit, Point, Wire, WireDirection, WireExtra, WireSegment, WireSegments},
+		netlist::Netlist,
+		testing,
+		v10,
+	};
+	use super::{Severity, net_widths, wire_overlaps};
+
+	type Segments<'s> = &'s [(WireDirection, u8)];
+
+	/// The diagnostics of [`wire_overlaps`] for a circuit with wires that start at the given points and follow the given segments.
+	fn overlaps(wires: &[((i16, i16), Segments<'_>)]) -> Vec<String> {
+		let mut circuit: Circuit<'_> = CircuitBuilder::new().build().into();
+		circuit.wires = wires.iter().map(|&((x, y), segments)| Wire {
+			color: 0,
+			comment: "".into(),
+			start: Point { x, y },
+			segments: WireSegments::Segments(segments.iter().map(|&(direction, length)| WireSegment { length, direction }).collect()),
+			extra: WireExtra::V10,
+		}).collect();
+
+		let mut diagnostics = vec![];
+		wire_overlaps(&circuit, &mut diagnostics);
+		diagnostics.iter().map(ToString::to_string).collect()
+	}
+
+	const R: WireDirection = WireDirection::Right;
+	const D: WireDirection = WireDirection::Down;
+	const L: WireDirection = WireDirection::Left;
+
+	#[test]
+	fn partial_overlap() {
+		assert_eq!(overlaps(&[((0, 0), &[(R, 4)]), ((2, 0), &[(R, 4)])]), [
+			"wire 0 (0, 0) -> (4, 0) and wire 1 (2, 0) -> (6, 0) overlap from (2, 0) to (4, 0)",
+		]);
+
+		// The overlap is reported in the direction of the first wire.
+		assert_eq!(overlaps(&[((4, 0), &[(L, 4)]), ((2, 0), &[(R, 4)])]), [
+			"wire 0 (4, 0) -> (0, 0) and wire 1 (2, 0) -> (6, 0) overlap from (4, 0) to (2, 0)",
+		]);
+	}
+
+	#[test]
+	fn touching_and_crossing() {
+		assert!(overlaps(&[((0, 0), &[(R, 2)]), ((2, 0), &[(R, 2)])]).is_empty());
+		assert!(overlaps(&[((0, 1), &[(R, 2)]), ((1, 0), &[(D, 2)])]).is_empty());
+		assert!(overlaps(&[((0, 0), &[(WireDirection::DownRight, 2)]), ((2, 0), &[(WireDirection::DownLeft, 2)])]).is_empty());
+	}
+
+	#[test]
+	fn duplicates() {
+		assert_eq!(overlaps(&[((0, 0), &[(R, 2), (D, 2)]), ((2, 2), &[(L, 2), (WireDirection::Up, 2)])]), [
+			"wire 0 (0, 0) -> (2, 2) and wire 1 (2, 2) -> (0, 0) connect the same points",
+		]);
+
+		// Wires along the same cells overlap, and are not also reported as connecting the same points.
+		assert_eq!(overlaps(&[((0, 0), &[(R, 2), (R, 0)]), ((0, 0), &[(R, 1), (R, 1)])]), [
+			"wire 0 (0, 0) -> (2, 0) and wire 1 (0, 0) -> (2, 0) overlap from (0, 0) to (2, 0)",
+		]);
+	}
+
+	#[test]
+	fn wires_that_run_over_themselves() {
+		assert!(overlaps(&[((0, 0), &[(R, 2), (L, 2), (R, 2)])]).is_empty());
+		assert_eq!(overlaps(&[((0, 0), &[(R, 2), (L, 2), (R, 2)]), ((0, 0), &[(R, 1)])]), [
+			"wire 0 (0, 0) -> (2, 0) and wire 1 (0, 0) -> (1, 0) overlap from (0, 0) to (1, 0)",
+		]);
+	}
+
+	#[test]
+	fn wires_off_the_grid() {
+		assert!(overlaps(&[((i16::MAX - 1, 0), &[(R, 5)]), ((i16::MAX - 1, 0), &[(R, 5)])]).is_empty());
+	}
+
+	fn net_widths_of(circuit: &v10::CircuitData<'_>) -> Vec<(Severity, String)> {
+		let mut diagnostics = vec![];
+		net_widths(&Netlist::new(circuit), &mut diagnostics);
+		diagnostics.iter().map(|diagnostic| (diagnostic.severity(), diagnostic.to_string())).collect()
+	}
+
+	#[test]
+	fn width_mismatch() {
+		assert!(net_widths_of(&testing::not_gate()).is_empty());
+
+		let mut builder = CircuitBuilder::new();
+		let input = builder.add(v10::ComponentKind::LevelInputWord, at(0, 0));
+		let not = builder.add(v10::ComponentKind::NotBit, at(6, 0));
+		let output = builder.add(v10::ComponentKind::LevelOutput1, at(12, 0));
+		builder.wire(input.out(0), not.input(0));
+		builder.wire(not.out(0), output.input(0));
+		assert_eq!(net_widths_of(&builder.build()), [
+			(Severity::Error, "net at (1, 0) connects pins of different widths: component 1 output 0 (8 bits), component 2 input 0 (1 bits)".to_owned()),
+		]);
+	}
+
+	#[test]
+	fn unconnected_pins() {
+		let mut circuit = testing::not_gate();
+		circuit.wires.remove(0);
+		assert_eq!(net_widths_of(&circuit), [
+			(Severity::Warning, "component 1 output 0 at (1, 0) is not connected".to_owned()),
+			(Severity::Warning, "component 2 input 0 at (5, 0) is not connected".to_owned()),
+		]);
+	}
+}
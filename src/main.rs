@@ -1,76 +1,443 @@
+use turing_complete_saves_parser::{
+	CircuitData,
+	Encode,
+	Parse,
+	circuit,
+	diff,
+	lint,
+	migrate,
+	render,
+	schematics,
+	text,
+	textconv,
+	v6, v7, v8, v9, v10,
+};
+
+/// Runs `$body` with `$input` bound to the decompressed save and `$vN` bound to the module of its version.
+macro_rules! with_version {
+	($circuit_data:expr, |$input:ident, $vN:ident| $body:expr) => {
+		match $circuit_data {
+			CircuitData::V6($input) => { use v6 as $vN; $body },
+			CircuitData::V7($input) => { use v7 as $vN; $body },
+			CircuitData::V8($input) => { use v8 as $vN; $body },
+			CircuitData::V9($input) => { use v9 as $vN; $body },
+			CircuitData::V10($input) => { use v10 as $vN; $body },
+		}
+	};
+}
+
 fn main() {
 	let mut args = std::env::args_os();
 	let argv0 = args.next().unwrap_or_else(|| env!("CARGO_BIN_NAME").into());
-	let path = parse_args(args, &argv0);
-
-	let input = std::fs::read(&path).unwrap();
-	let circuit_data = <turing_complete_saves_parser::CircuitData as turing_complete_saves_parser::Parse<'_>>::parse(&mut &input[..]);
-	#[allow(clippy::match_same_arms)]
-	match circuit_data {
-		turing_complete_saves_parser::CircuitData::V6(input) => {
-			let cd = <turing_complete_saves_parser::v6::CircuitData<'_> as turing_complete_saves_parser::Parse<'_>>::parse(&mut &input[..]);
-			// println!("{cd:#?}");
-			cd.check_wire_dupes();
-		},
+	let (command, paths) = parse_args(args, &argv0);
 
-		turing_complete_saves_parser::CircuitData::V7(input) => {
-			let cd = <turing_complete_saves_parser::v7::CircuitData<'_> as turing_complete_saves_parser::Parse<'_>>::parse(&mut &input[..]);
-			// println!("{cd:#?}");
-			cd.check_wire_dupes();
-		},
+	if let Command::Diff = command {
+		let [old_path, new_path] = &paths[..] else { unreachable!("parse_args only accepts two paths for diff") };
+		if !run_diff(old_path, new_path) {
+			std::process::exit(1);
+		}
+		return;
+	}
 
-		turing_complete_saves_parser::CircuitData::V8(input) => {
-			let cd = <turing_complete_saves_parser::v8::CircuitData<'_> as turing_complete_saves_parser::Parse<'_>>::parse(&mut &input[..]);
-			// println!("{cd:#?}");
-			cd.check_wire_dupes();
-		},
+	let mut files = vec![];
+	let mut failed = false;
+	for path in paths {
+		if path.is_dir() {
+			match schematics::find_circuits(&path) {
+				Ok(paths) => files.extend(paths),
+				Err(err) => {
+					print_error(&path, &err);
+					failed = true;
+				},
+			}
+		}
+		else {
+			files.push(path);
+		}
+	}
 
-		turing_complete_saves_parser::CircuitData::V9(input) => {
-			let cd = <turing_complete_saves_parser::v9::CircuitData<'_> as turing_complete_saves_parser::Parse<'_>>::parse(&mut &input[..]);
-			// println!("{cd:#?}");
-			cd.check_wire_dupes();
-		},
+	for path in &files {
+		if let Err(err) = run(command, path, files.len() > 1) {
+			print_error(path, &*err);
+			failed = true;
+		}
+	}
 
-		turing_complete_saves_parser::CircuitData::V10(input) => {
-			let cd = <turing_complete_saves_parser::v10::CircuitData<'_> as turing_complete_saves_parser::Parse<'_>>::parse(&mut &input[..]);
-			// println!("{cd:#?}");
-			cd.check_wire_dupes();
-		},
+	if failed {
+		std::process::exit(1);
 	}
 }
 
-fn parse_args(mut args: impl Iterator<Item = std::ffi::OsString>, argv0: &std::ffi::OsStr) -> std::path::PathBuf {
-	let mut path = None;
+#[derive(Clone, Copy, Debug)]
+enum Command {
+	Dump { format: Format },
+	Check,
+	Convert { to_version: u8 },
+	Stats,
+	Roundtrip,
+	Render { format: Format, labels: bool },
+	Diff,
+	Textconv,
+	FromText,
+}
 
-	for opt in &mut args {
+#[derive(Clone, Copy, Debug)]
+enum Format {
+	Debug,
+	Json,
+	Svg,
+	Png,
+	Text,
+}
+
+fn parse_args(mut args: impl Iterator<Item = std::ffi::OsString>, argv0: &std::ffi::OsStr) -> (Command, Vec<std::path::PathBuf>) {
+	let mut command = None;
+	let mut format = None;
+	let mut to_version = None;
+	let mut labels = true;
+	let mut paths = vec![];
+
+	while let Some(opt) = args.next() {
 		match opt.to_str() {
 			Some("--help") => {
 				write_usage(std::io::stdout(), argv0);
 				std::process::exit(0);
 			},
 
+			Some("--format") if format.is_none() => format = match args.next().as_deref().and_then(std::ffi::OsStr::to_str) {
+				Some("debug") => Some(Format::Debug),
+				Some("json") => Some(Format::Json),
+				Some("svg") => Some(Format::Svg),
+				Some("png") => Some(Format::Png),
+				Some("text") => Some(Format::Text),
+				_ => write_usage_and_crash(argv0),
+			},
+
+			Some("--to-version") if to_version.is_none() => to_version = match args.next().as_deref().and_then(std::ffi::OsStr::to_str).map(str::parse) {
+				Some(Ok(to_version @ 6..=10)) => Some(to_version),
+				_ => write_usage_and_crash(argv0),
+			},
+
+			Some("--no-labels") if labels => labels = false,
+
 			Some("--") => {
-				path = args.next();
+				paths.extend(args.by_ref().map(Into::into));
 				break;
 			},
 
-			_ if path.is_none() => path = Some(opt),
+			Some(opt) if opt.starts_with("--") => write_usage_and_crash(argv0),
+
+			Some(name) if command.is_none() => command = Some(name.to_owned()),
+
+			_ if command.is_some() => paths.push(opt.into()),
 
 			_ => write_usage_and_crash(argv0),
 		}
 	}
 
-	let None = args.next() else { write_usage_and_crash(argv0); };
+	let command = match (command.as_deref(), format, to_version, labels) {
+		(Some("dump"), format @ (None | Some(Format::Debug | Format::Json | Format::Text)), None, true) => Command::Dump { format: format.unwrap_or(Format::Debug) },
+		(Some("check"), None, None, true) => Command::Check,
+		(Some("convert"), None, Some(to_version), true) => Command::Convert { to_version },
+		(Some("stats"), None, None, true) => Command::Stats,
+		(Some("roundtrip"), None, None, true) => Command::Roundtrip,
+		(Some("render"), format @ (None | Some(Format::Svg | Format::Png)), None, labels) => Command::Render { format: format.unwrap_or(Format::Svg), labels },
+		(Some("diff"), None, None, true) => Command::Diff,
+		(Some("textconv"), None, None, true) => Command::Textconv,
+		(Some("from-text"), None, None, true) => Command::FromText,
+		_ => write_usage_and_crash(argv0),
+	};
+
+	if paths.is_empty() || (matches!(command, Command::Diff) && paths.len() != 2) {
+		write_usage_and_crash(argv0);
+	}
+
+	(command, paths)
+}
+
+fn run(command: Command, path: &std::path::Path, print_path: bool) -> Result<(), Box<dyn std::error::Error>> {
+	if let Command::FromText = command {
+		return run_from_text(path);
+	}
+
+	let circuit_data = read(path)?;
+	let print_header = || if print_path {
+		println!("==> {} <==", path.display());
+	};
+
+	match command {
+		Command::Dump { format: Format::Debug } => {
+			let circuit = circuit_data.parse_circuit()?;
+			print_header();
+			println!("{circuit:#?}");
+		},
+
+		Command::Dump { format: Format::Json } => dump_json(&circuit_data)?,
+
+		Command::Dump { format: Format::Text } => {
+			let CircuitData::V10(input) = &circuit_data else {
+				return Err("--format text only supports version 10 saves, convert the save with --to-version 10 first".into());
+			};
+			let circuit = v10::CircuitData::parse_from(input)?;
+			print_header();
+			print!("{}", text::print(&circuit));
+		},
+
+		Command::Dump { format: Format::Svg | Format::Png } | Command::Render { format: Format::Debug | Format::Json | Format::Text, .. } =>
+			unreachable!("parse_args only accepts valid formats for each command"),
+
+		Command::Diff => unreachable!("diff is handled by run_diff"),
+
+		Command::FromText => unreachable!("from-text is handled by run_from_text"),
+
+		Command::Check => run_check(&circuit_data, path)?,
+
+		Command::Convert { to_version } => run_convert(&circuit_data, to_version, path)?,
+
+		Command::Stats => run_stats(&circuit_data, path)?,
+
+		Command::Roundtrip => run_roundtrip(&circuit_data, path)?,
+
+		Command::Render { format, labels } => {
+			let circuit = circuit_data.parse_circuit()?;
+			let options = render::RenderOptions { labels, ..Default::default() };
+			let (output, extension) = match format {
+				Format::Svg => (render::svg(&circuit, options).into_bytes(), "svg"),
+				_ => (render_png(&circuit, options)?, "png"),
+			};
+
+			let output_path = path.with_extension(extension);
+			std::fs::write(&output_path, output)?;
+			println!("{} -> {}", path.display(), output_path.display());
+		},
+
+		Command::Textconv => {
+			let circuit = circuit_data.parse_circuit()?;
+			print_header();
+			print!("{}", textconv::textconv(&circuit));
+		},
+	}
+
+	Ok(())
+}
+
+/// Reads and decompresses the save at `path`. Every command that reads saves reads them with this.
+fn read(path: &std::path::Path) -> Result<CircuitData, Box<dyn std::error::Error>> {
+	let input = std::fs::read(path)?;
+	Ok(CircuitData::parse_from(&input)?)
+}
+
+fn run_check(circuit_data: &CircuitData, path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+	let circuit = circuit_data.parse_circuit()?;
+	let diagnostics = lint::lint(&circuit);
+	for diagnostic in &diagnostics {
+		let severity = if diagnostic.severity() == lint::Severity::Warning { "warning: " } else { "" };
+		println!("{}: {severity}{diagnostic}", path.display());
+	}
+	let errors = diagnostics.iter().filter(|diagnostic| diagnostic.severity() == lint::Severity::Error).count();
+	if errors > 0 {
+		return Err(format!("found {errors} problems").into());
+	}
+	Ok(())
+}
+
+fn run_convert(circuit_data: &CircuitData, to_version: u8, path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+	let version = circuit_data.version();
+	if to_version < version {
+		return Err(format!("cannot convert a version {version} save to version {to_version}").into());
+	}
+
+	let mut issues = vec![];
+	let output = with_version!(circuit_data, |input, vN| vN::CircuitData::parse_from(input)?.convert(to_version, &mut issues));
+	for issue in issues {
+		eprintln!("{}: warning: {issue}", path.display());
+	}
+
+	let output_path = path.with_extension(format!("v{to_version}.data"));
+	std::fs::write(&output_path, output)?;
+	println!("{} -> {}", path.display(), output_path.display());
+	Ok(())
+}
+
+fn run_stats(circuit_data: &CircuitData, path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+	let circuit = circuit_data.parse_circuit()?;
+	println!("{}: version {}, {} components, {} wires", path.display(), circuit.version, circuit.components.len(), circuit.wires.len());
+
+	let mut kinds: std::collections::BTreeMap<_, usize> = Default::default();
+	for component in &circuit.components {
+		*kinds.entry(component.kind.to_string()).or_default() += 1;
+	}
+	let mut kinds: Vec<_> = kinds.into_iter().collect();
+	kinds.sort_by(|(name1, count1), (name2, count2)| count2.cmp(count1).then_with(|| name1.cmp(name2)));
+	for (name, count) in kinds {
+		println!("\t{count} {name}");
+	}
+	Ok(())
+}
+
+fn run_roundtrip(circuit_data: &CircuitData, path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+	with_version!(circuit_data, |input, vN| {
+		let parsed = vN::CircuitData::parse_from(input)?;
+
+		let mut reencoded = vec![];
+		parsed.encode(&mut reencoded);
+		compare(input, &reencoded, "re-encoding")?;
+
+		let converted = vN::CircuitData::try_from(circuit::Circuit::from(parsed))?;
+		let mut reencoded = vec![];
+		converted.encode(&mut reencoded);
+		compare(input, &reencoded, "converting through circuit::Circuit")?;
+	});
+
+	println!("{}: ok", path.display());
+	Ok(())
+}
+
+fn run_from_text(path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+	let input = std::fs::read_to_string(path)?;
+	let circuit = text::parse(&input)?;
+	let output_path = path.with_extension("data");
+	std::fs::write(&output_path, circuit.encode_final())?;
+	println!("{} -> {}", path.display(), output_path.display());
+	Ok(())
+}
+
+/// Prints the changes from the save at `old_path` to the save at `new_path`. Returns `false` if either could not be read.
+fn run_diff(old_path: &std::path::Path, new_path: &std::path::Path) -> bool {
+	let paths = [old_path, new_path];
+	let Some([old, new]) = both(paths, [read(old_path), read(new_path)]) else { return false; };
+	let Some([old, new]) = both(paths, [old.parse_circuit().map_err(Into::into), new.parse_circuit().map_err(Into::into)]) else { return false; };
 
-	let Some(path) = path else { write_usage_and_crash(argv0); };
-	path.into()
+	for change in diff::diff(&old, &new) {
+		println!("{change}");
+	}
+	true
+}
+
+/// The values of both results if both are `Ok`. Otherwise prints the errors, each with the corresponding path.
+fn both<T>(paths: [&std::path::Path; 2], results: [Result<T, Box<dyn std::error::Error>>; 2]) -> Option<[T; 2]> {
+	match results {
+		[Ok(a), Ok(b)] => Some([a, b]),
+		results => {
+			for (path, result) in paths.into_iter().zip(results) {
+				if let Err(err) = result {
+					print_error(path, &*err);
+				}
+			}
+			None
+		},
+	}
+}
+
+#[cfg(feature = "serde")]
+fn dump_json(circuit_data: &CircuitData) -> Result<(), Box<dyn std::error::Error>> {
+	let value = with_version!(circuit_data, |input, vN| serde_json::json!({
+		"version": circuit_data.version(),
+		"circuit": vN::CircuitData::parse_from(input)?,
+	}));
+
+	let stdout = std::io::stdout().lock();
+	serde_json::to_writer_pretty(stdout, &value)?;
+	println!();
+	Ok(())
+}
+
+#[cfg(not(feature = "serde"))]
+fn dump_json(_circuit_data: &CircuitData) -> Result<(), Box<dyn std::error::Error>> {
+	Err("--format json requires the serde feature".into())
+}
+
+#[cfg(feature = "png")]
+fn render_png(circuit: &circuit::Circuit<'_>, options: render::RenderOptions) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+	Ok(render::png(circuit, options)?)
+}
+
+#[cfg(not(feature = "png"))]
+fn render_png(_circuit: &circuit::Circuit<'_>, _options: render::RenderOptions) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+	Err("--format png requires the png feature".into())
+}
+
+/// Converts the circuit to the given version, which must not be older than it, and encodes it.
+///
+/// Anything that has no equivalent in the newer version is reported in `issues`.
+trait Convert {
+	fn convert(&self, to_version: u8, issues: &mut Vec<migrate::MigrationIssue>) -> Vec<u8>;
+}
+
+macro_rules! impl_convert {
+	($($vN:ident = $version:literal => $step:ident,)*) => {
+		$(
+			impl Convert for $vN::CircuitData<'_> {
+				fn convert(&self, to_version: u8, issues: &mut Vec<migrate::MigrationIssue>) -> Vec<u8> {
+					if to_version == $version {
+						return self.encode_final();
+					}
+
+					let migrate::Migration { circuit, issues: mut new_issues } = migrate::$step(self);
+					issues.append(&mut new_issues);
+					circuit.convert(to_version, issues)
+				}
+			}
+		)*
+	};
+}
+
+impl_convert! {
+	v6 = 6 => v6_to_v7,
+	v7 = 7 => v7_to_v8,
+	v8 = 8 => v8_to_v9,
+	v9 = 9 => v9_to_v10,
+}
+
+impl Convert for v10::CircuitData<'_> {
+	fn convert(&self, _to_version: u8, _issues: &mut Vec<migrate::MigrationIssue>) -> Vec<u8> {
+		self.encode_final()
+	}
+}
+
+fn compare(expected: &[u8], actual: &[u8], what: &str) -> Result<(), Box<dyn std::error::Error>> {
+	match expected.iter().zip(actual).position(|(expected, actual)| expected != actual) {
+		Some(offset) => Err(format!("{what} changed the save at offset {offset}").into()),
+		None if expected.len() != actual.len() => Err(format!("{what} changed the length of the save from {} to {}", expected.len(), actual.len()).into()),
+		None => Ok(()),
+	}
+}
+
+fn print_error(path: &std::path::Path, err: &dyn std::error::Error) {
+	use std::fmt::Write;
+
+	let mut message = format!("{}: {err}", path.display());
+	let mut source = err.source();
+	while let Some(err) = source {
+		_ = write!(message, ": {err}");
+		source = err.source();
+	}
+	eprintln!("{message}");
 }
 
 fn write_usage_and_crash(argv0: &std::ffi::OsStr) -> ! {
 	write_usage(std::io::stderr(), argv0);
-	std::process::exit(1);
+	std::process::exit(2);
 }
 
 fn write_usage(mut w: impl std::io::Write, argv0: &std::ffi::OsStr) {
-	_ = writeln!(w, "Usage: {} <circuit.data>", argv0.to_string_lossy());
+	let argv0 = argv0.to_string_lossy();
+	_ = writeln!(w, "Usage: {argv0} <command> [options] <path>...");
+	_ = writeln!(w);
+	_ = writeln!(w, "Commands:");
+	_ = writeln!(w, "    dump [--format debug|json|text]");
+	_ = writeln!(w, "                                  Print the parsed saves. The text format is only supported for version 10 saves.");
+	_ = writeln!(w, "    check                         Validate the saves, and check that their wires do not overlap. Warnings do not fail the check.");
+	_ = writeln!(w, "    convert --to-version <N>      Convert the saves to version N, and write them next to the originals as *.vN.data.");
+	_ = writeln!(w, "    stats                         Print the number of components of each kind and the number of wires.");
+	_ = writeln!(w, "    roundtrip                     Check that parsing and re-encoding the saves reproduces them exactly.");
+	_ = writeln!(w, "    render [--format svg|png] [--no-labels]");
+	_ = writeln!(w, "                                  Draw the saves, and write them next to the originals as *.svg or *.png.");
+	_ = writeln!(w, "    diff <old> <new>              Print the components, wires and metadata that changed between two saves.");
+	_ = writeln!(w, "    textconv                      Print the saves in a stable line-oriented text form, for use as a git textconv filter.");
+	_ = writeln!(w, "    from-text                     Parse version 10 saves from the text format of dump --format text, and write them as *.data.");
+	_ = writeln!(w);
+	_ = writeln!(w, "Directories are searched for circuit.data files, except by diff which takes exactly two saves.");
+	_ = writeln!(w);
+	_ = writeln!(w, "Exits with 0 if every save was processed successfully, 1 if any save failed, and 2 if the arguments are invalid.");
 }
@@ -0,0 +1,595 @@
+//! Three-way merge of v10 saves.
+//!
+//! Components are matched by their `permanent_id` and merged field by field, so one side moving a component
+//! while the other changes its settings merges cleanly. Wires are matched by their [`WirePath`],
+//! and wires that only differ in their `color` or `comment` are merged the same way.
+//!
+//! When both sides change the same thing to different values, ours is kept and a [`Conflict`] is reported.
+
+use crate::{
+	Either,
+	Encode,
+	Slice,
+	circuit,
+	diff::WirePath,
+	lint,
+	v10,
+};
+
+#[derive(Debug)]
+pub struct Merge<'a> {
+	pub circuit: v10::CircuitData<'a>,
+	pub conflicts: Vec<Conflict>,
+}
+
+#[derive(Debug)]
+pub enum Conflict {
+	/// Both sides changed the field of the circuit to different values.
+	Metadata { field: &'static str },
+
+	/// Both sides changed the field of the component to different values.
+	Component { permanent_id: u64, field: &'static str },
+
+	/// One side removed the component and the other side changed it. The changed component was kept.
+	RemovedAndChanged { permanent_id: u64, removed_by: Side },
+
+	/// Both sides added different components with the same `permanent_id`.
+	BothAdded { permanent_id: u64 },
+
+	/// Both sides changed the field of a wire with the same path to different values.
+	Wire { path: WirePath, field: &'static str },
+
+	/// Wires from the two sides overlap in the merged circuit, though they did not overlap on either side.
+	Wires(lint::Diagnostic),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Side {
+	Ours,
+	Theirs,
+}
+
+impl std::fmt::Display for Conflict {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Metadata { field } => write!(f, "both sides changed {field}, kept ours"),
+
+			Self::Component { permanent_id, field } => write!(f, "both sides changed {field} of component {permanent_id}, kept ours"),
+
+			Self::RemovedAndChanged { permanent_id, removed_by } => {
+				let changed_by = match removed_by {
+					Side::Ours => Side::Theirs,
+					Side::Theirs => Side::Ours,
+				};
+				write!(f, "{removed_by} removed component {permanent_id} but {changed_by} changed it, kept {changed_by}")
+			},
+
+			Self::BothAdded { permanent_id } => write!(f, "both sides added a different component {permanent_id}, kept ours"),
+
+			Self::Wire { path, field } => write!(f, "both sides changed {field} of wire {path}, kept ours"),
+
+			Self::Wires(diagnostic) => write!(f, "merged wires conflict: {diagnostic}"),
+		}
+	}
+}
+
+impl std::fmt::Display for Side {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			Self::Ours => "ours",
+			Self::Theirs => "theirs",
+		})
+	}
+}
+
+/// Merges the changes from `base` to `ours` and from `base` to `theirs`.
+///
+/// The camera position is merged like any other field, but never reported as a conflict.
+pub fn merge<'a>(base: &v10::CircuitData<'a>, ours: &v10::CircuitData<'a>, theirs: &v10::CircuitData<'a>) -> Merge<'a> {
+	let mut conflicts = vec![];
+
+	macro_rules! field {
+		($field:ident) => { field!($field, Encode::encode) };
+		($field:ident, $encode:expr) => {
+			merge_value(&base.$field, &ours.$field, &theirs.$field, $encode).unwrap_or_else(|| {
+				conflicts.push(Conflict::Metadata { field: stringify!($field) });
+				ours.$field.clone()
+			})
+		};
+	}
+
+	let custom_id = field!(custom_id);
+	let hub_id = field!(hub_id);
+	let gate = field!(gate);
+	let delay = field!(delay);
+	let menu_visible = field!(menu_visible);
+	let clock_speed = field!(clock_speed);
+	let dependencies = field!(dependencies, Slice::encode_with_length_prefix);
+	let description = field!(description);
+	let camera_position = merge_value(&base.camera_position, &ours.camera_position, &theirs.camera_position, Encode::encode).unwrap_or(ours.camera_position);
+	let synced = field!(synced);
+	let player_data = field!(player_data, Slice::encode_with_length_prefix);
+	let hub_description = field!(hub_description);
+
+	let components = merge_components(&base.components, &ours.components, &theirs.components, &mut conflicts);
+	let wires = merge_wires(&base.wires, &ours.wires, &theirs.wires, &mut conflicts);
+
+	let circuit = v10::CircuitData {
+		custom_id,
+		hub_id,
+		gate,
+		delay,
+		menu_visible,
+		clock_speed,
+		dependencies,
+		description,
+		camera_position,
+		synced,
+		player_data,
+		hub_description,
+		components: components.into(),
+		wires: wires.into(),
+	};
+
+	// Wires that overlapped on one side already are not the merge's doing.
+	let overlaps = |circuit: &v10::CircuitData<'a>| {
+		let circuit = circuit::Circuit::from(circuit.clone());
+		let mut diagnostics = vec![];
+		lint::wire_overlaps(&circuit, &mut diagnostics);
+		let paths: std::collections::BTreeSet<_> = diagnostics.iter().map(|diagnostic| overlap_paths(&circuit, diagnostic)).collect();
+		(circuit, diagnostics, paths)
+	};
+	let (_, _, ours_overlaps) = overlaps(ours);
+	let (_, _, theirs_overlaps) = overlaps(theirs);
+	let (merged, merged_diagnostics, _) = overlaps(&circuit);
+	for diagnostic in merged_diagnostics {
+		let paths = overlap_paths(&merged, &diagnostic);
+		if !ours_overlaps.contains(&paths) && !theirs_overlaps.contains(&paths) {
+			conflicts.push(Conflict::Wires(diagnostic));
+		}
+	}
+
+	Merge { circuit, conflicts }
+}
+
+fn merge_components<'a>(
+	base: &Slice<'a, u64, v10::Component<'a>>,
+	ours: &Slice<'a, u64, v10::Component<'a>>,
+	theirs: &Slice<'a, u64, v10::Component<'a>>,
+	conflicts: &mut Vec<Conflict>,
+) -> Vec<v10::Component<'a>> {
+	let by_id = |components: &Slice<'a, u64, v10::Component<'a>>| -> std::collections::BTreeMap<_, _> {
+		components.iter().map(Either::into_inner).map(|component| (component.permanent_id, component)).collect()
+	};
+	let base_by_id = by_id(base);
+	let ours_by_id = by_id(ours);
+	let theirs_by_id = by_id(theirs);
+
+	let mut result = vec![];
+
+	for ours in ours.iter().map(Either::into_inner) {
+		let permanent_id = ours.permanent_id;
+		match (base_by_id.get(&permanent_id), theirs_by_id.get(&permanent_id)) {
+			(Some(base), Some(theirs)) => result.push(merge_component(base, &ours, theirs, conflicts)),
+
+			(Some(base), None) =>
+				if encoded(base) != encoded(&ours) {
+					conflicts.push(Conflict::RemovedAndChanged { permanent_id, removed_by: Side::Theirs });
+					result.push(ours);
+				},
+
+			(None, Some(theirs)) => {
+				if encoded(theirs) != encoded(&ours) {
+					conflicts.push(Conflict::BothAdded { permanent_id });
+				}
+				result.push(ours);
+			},
+
+			(None, None) => result.push(ours),
+		}
+	}
+
+	for theirs in theirs.iter().map(Either::into_inner) {
+		let permanent_id = theirs.permanent_id;
+		if ours_by_id.contains_key(&permanent_id) {
+			continue;
+		}
+
+		match base_by_id.get(&permanent_id) {
+			Some(base) =>
+				if encoded(base) != encoded(&theirs) {
+					conflicts.push(Conflict::RemovedAndChanged { permanent_id, removed_by: Side::Ours });
+					result.push(theirs);
+				},
+
+			None => result.push(theirs),
+		}
+	}
+
+	result
+}
+
+fn merge_component<'a>(
+	base: &v10::Component<'a>,
+	ours: &v10::Component<'a>,
+	theirs: &v10::Component<'a>,
+	conflicts: &mut Vec<Conflict>,
+) -> v10::Component<'a> {
+	if let Some(component) = merge_value(base, ours, theirs, Encode::encode) {
+		return component;
+	}
+
+	let permanent_id = ours.permanent_id;
+
+	macro_rules! field {
+		($field:ident) => { field!($field, Encode::encode) };
+		($field:ident, $encode:expr) => {
+			merge_value(&base.$field, &ours.$field, &theirs.$field, $encode).unwrap_or_else(|| {
+				conflicts.push(Conflict::Component { permanent_id, field: stringify!($field) });
+				ours.$field.clone()
+			})
+		};
+	}
+
+	// The kind decides whether the component has custom data, so they are merged together.
+	let kind_and_custom_data = |component: &v10::Component<'a>| (component.kind, component.custom_data.clone());
+	let (kind, custom_data) =
+		merge_value(&kind_and_custom_data(base), &kind_and_custom_data(ours), &kind_and_custom_data(theirs), Encode::encode)
+		.unwrap_or_else(|| {
+			conflicts.push(Conflict::Component { permanent_id, field: "kind" });
+			kind_and_custom_data(ours)
+		});
+
+	v10::Component {
+		kind,
+		position: field!(position),
+		rotation: field!(rotation),
+		permanent_id,
+		custom_string: field!(custom_string),
+		settings: field!(settings, Slice::encode_with_length_prefix),
+		buffer_size: field!(buffer_size),
+		ui_order: field!(ui_order),
+		word_size: field!(word_size),
+		linked_components: field!(linked_components, Slice::encode_with_length_prefix),
+		selected_programs: field!(selected_programs),
+		custom_data,
+	}
+}
+
+/// Merges the wires of each path separately.
+///
+/// Every wire that either side added or removed is added or removed, except that a wire which both sides added is only added once.
+/// The kept wires of ours come first, in their original order, followed by the wires that only theirs added.
+fn merge_wires<'a>(
+	base: &Slice<'a, u64, v10::Wire<'a>>,
+	ours: &Slice<'a, u64, v10::Wire<'a>>,
+	theirs: &Slice<'a, u64, v10::Wire<'a>>,
+	conflicts: &mut Vec<Conflict>,
+) -> Vec<v10::Wire<'a>> {
+	let by_path = |wires: &Slice<'a, u64, v10::Wire<'a>>| {
+		let mut result: std::collections::BTreeMap<_, Vec<_>> = Default::default();
+		for wire in wires.iter().map(Either::into_inner) {
+			result.entry(wire_path(&wire)).or_default().push(wire);
+		}
+		result
+	};
+	let base_by_path = by_path(base);
+	let ours_by_path = by_path(ours);
+	let theirs_by_path = by_path(theirs);
+
+	// The number of wires of each path in the result, and how many of them come from ours.
+	let counts: std::collections::BTreeMap<_, _> =
+		base_by_path.keys().chain(ours_by_path.keys()).chain(theirs_by_path.keys())
+		.map(|path| {
+			let base = wires_of(&base_by_path, path).len();
+			let ours = wires_of(&ours_by_path, path).len();
+			let theirs = wires_of(&theirs_by_path, path).len();
+			let count = match (ours.cmp(&base), theirs.cmp(&base)) {
+				(std::cmp::Ordering::Greater, std::cmp::Ordering::Greater) => ours.max(theirs),
+				(std::cmp::Ordering::Less, std::cmp::Ordering::Less) => ours.min(theirs),
+				_ => ours + theirs - base,
+			};
+			(path.clone(), (count, ours.min(count)))
+		})
+		.collect();
+
+	let mut result = vec![];
+
+	let mut seen: std::collections::BTreeMap<_, usize> = Default::default();
+	for wire in ours.iter().map(Either::into_inner) {
+		let path = wire_path(&wire);
+		let i = seen.entry(path.clone()).or_default();
+		let index = *i;
+		*i += 1;
+		if index >= counts[&path].1 {
+			continue;
+		}
+
+		let (base, theirs) = (wires_of(&base_by_path, &path).get(index), wires_of(&theirs_by_path, &path).get(index));
+		let Some(theirs) = theirs else {
+			result.push(wire);
+			continue;
+		};
+
+		// A wire that both sides added has no base, so any difference between the two is a conflict.
+		let color = match base {
+			Some(base) => merge_value(&base.color, &wire.color, &theirs.color, Encode::encode),
+			None => (wire.color == theirs.color).then_some(wire.color),
+		};
+		let color = color.unwrap_or_else(|| {
+			conflicts.push(Conflict::Wire { path: path.clone(), field: "color" });
+			wire.color
+		});
+		let comment = match base {
+			Some(base) => merge_value(&base.comment, &wire.comment, &theirs.comment, Encode::encode),
+			None => (wire.comment == theirs.comment).then(|| wire.comment.clone()),
+		};
+		let comment = comment.unwrap_or_else(|| {
+			conflicts.push(Conflict::Wire { path: path.clone(), field: "comment" });
+			wire.comment.clone()
+		});
+		result.push(v10::Wire { color, comment, ..wire });
+	}
+
+	let mut seen: std::collections::BTreeMap<_, usize> = Default::default();
+	for wire in theirs.iter().map(Either::into_inner) {
+		let path = wire_path(&wire);
+		let i = seen.entry(path.clone()).or_default();
+		let index = *i;
+		*i += 1;
+		let (count, from_ours) = counts[&path];
+		if (from_ours..count).contains(&index) {
+			result.push(wire);
+		}
+	}
+
+	result
+}
+
+/// Merges one value. Returns `None` if both sides changed it to different values.
+fn merge_value<T>(base: &T, ours: &T, theirs: &T, encode: impl Fn(&T, &mut Vec<u8>)) -> Option<T> where T: Clone {
+	let encoded = |value| {
+		let mut out = vec![];
+		encode(value, &mut out);
+		out
+	};
+	let (base_encoded, ours_encoded, theirs_encoded) = (encoded(base), encoded(ours), encoded(theirs));
+
+	if ours_encoded == theirs_encoded || theirs_encoded == base_encoded {
+		Some(ours.clone())
+	}
+	else if ours_encoded == base_encoded {
+		Some(theirs.clone())
+	}
+	else {
+		None
+	}
+}
+
+fn encoded(value: &impl Encode) -> Vec<u8> {
+	let mut out = vec![];
+	value.encode(&mut out);
+	out
+}
+
+fn wires_of<'m, 'a>(by_path: &'m std::collections::BTreeMap<WirePath, Vec<v10::Wire<'a>>>, path: &WirePath) -> &'m [v10::Wire<'a>] {
+	by_path.get(path).map_or(&[], Vec::as_slice)
+}
+
+fn wire_path(wire: &v10::Wire<'_>) -> WirePath {
+	WirePath::new(wire.start.into(), &(&wire.segments).into())
+}
+
+/// The paths of the two wires of an overlap diagnostic, in ascending order.
+fn overlap_paths(circuit: &circuit::Circuit<'_>, diagnostic: &lint::Diagnostic) -> (WirePath, WirePath) {
+	let (lint::Diagnostic::WireOverlap { wires: [first, second], .. } | lint::Diagnostic::DuplicateWire { wires: [first, second] }) = diagnostic else {
+		unreachable!("lint::wire_overlaps only reports wire diagnostics");
+	};
+	let first = WirePath::of(&circuit.wires[first.index]);
+	let second = WirePath::of(&circuit.wires[second.index]);
+	(first.clone().min(second.clone()), first.max(second))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		Either,
+		builder::at,
+		testing,
+		v10,
+	};
+	use super::{Conflict, Side, merge};
+
+	fn component<'c>(circuit: &'c mut v10::CircuitData<'static>, permanent_id: u64) -> &'c mut v10::Component<'static> {
+		circuit.components.iter_mut().find(|component| component.permanent_id == permanent_id).unwrap()
+	}
+
+	#[test]
+	fn clean() {
+		let base = testing::not_gate();
+
+		let mut ours = base.clone();
+		ours.description = "ours".into();
+		component(&mut ours, 2).position = at(6, 4);
+		ours.wires.get_mut(0).unwrap().color = 1;
+		ours.wires.push(v10::Wire { start: at(20, 20), ..ours.wires.get(1).unwrap().into_inner() });
+
+		let mut theirs = base.clone();
+		theirs.clock_speed = 100;
+		component(&mut theirs, 2).rotation = 2;
+		component(&mut theirs, 2).settings = vec![7].into();
+		theirs.components.remove(2);
+
+		let merge = merge(&base, &ours, &theirs);
+		assert!(merge.conflicts.is_empty(), "{:?}", merge.conflicts);
+
+		let circuit = merge.circuit;
+		assert_eq!(circuit.description, "ours");
+		assert_eq!(circuit.clock_speed, 100);
+
+		let components: Vec<_> = circuit.components.iter().map(Either::into_inner).collect();
+		assert_eq!(components.iter().map(|component| component.permanent_id).collect::<Vec<_>>(), [1, 2]);
+		assert_eq!(components[1].position, at(6, 4));
+		assert_eq!(components[1].rotation, 2);
+		assert_eq!(components[1].settings.iter().map(Either::into_inner).collect::<Vec<_>>(), [7]);
+
+		let wires: Vec<_> = circuit.wires.iter().map(Either::into_inner).collect();
+		assert_eq!(wires.len(), 3);
+		assert_eq!(wires[0].color, 1);
+		assert_eq!(wires[2].start, at(20, 20));
+	}
+
+	#[test]
+	fn same_change_on_both_sides() {
+		let base = testing::not_gate();
+
+		let mut ours = base.clone();
+		ours.description = "both".into();
+		ours.wires.remove(0);
+
+		let merge = merge(&base, &ours, &ours);
+		assert!(merge.conflicts.is_empty(), "{:?}", merge.conflicts);
+		assert_eq!(merge.circuit.description, "both");
+		assert_eq!(merge.circuit.wires.len(), 1);
+	}
+
+	#[test]
+	fn conflicting() {
+		let base = testing::not_gate();
+
+		let mut ours = base.clone();
+		ours.description = "ours".into();
+		component(&mut ours, 2).position = at(6, 4);
+		component(&mut ours, 3).settings = vec![1].into();
+		let added = v10::Component { permanent_id: 4, ..component(&mut ours, 1).clone() };
+		ours.components.push(added.clone());
+
+		let mut theirs = base.clone();
+		theirs.description = "theirs".into();
+		component(&mut theirs, 2).position = at(6, -4);
+		theirs.components.remove(2);
+		theirs.components.push(v10::Component { position: at(0, 10), ..added });
+
+		let merge = merge(&base, &ours, &theirs);
+		let conflicts: Vec<_> = merge.conflicts.iter().map(ToString::to_string).collect();
+		assert_eq!(conflicts, [
+			"both sides changed description, kept ours",
+			"both sides changed position of component 2, kept ours",
+			"theirs removed component 3 but ours changed it, kept ours",
+			"both sides added a different component 4, kept ours",
+		]);
+		assert!(matches!(merge.conflicts[2], Conflict::RemovedAndChanged { permanent_id: 3, removed_by: Side::Theirs }));
+
+		let circuit = merge.circuit;
+		assert_eq!(circuit.description, "ours");
+		let components: Vec<_> = circuit.components.iter().map(Either::into_inner).collect();
+		assert_eq!(components.iter().map(|component| component.permanent_id).collect::<Vec<_>>(), [1, 2, 3, 4]);
+		assert_eq!(components[1].position, at(6, 4));
+		assert_eq!(components[3].position, at(0, 0));
+	}
+
+	#[test]
+	fn overlapping_wires() {
+		let base = testing::not_gate();
+
+		let mut ours = base.clone();
+		ours.wires.push(v10::Wire {
+			color: 0,
+			comment: "".into(),
+			start: at(0, 10),
+			segments: v10::WireSegments::Segments(vec![
+				v10::WireSegment { length: 4, direction: v10::WireDirection::Right },
+				v10::WireSegment { length: 0, direction: v10::WireDirection::Right },
+			].into()),
+		});
+
+		let mut theirs = base.clone();
+		theirs.wires.push(v10::Wire {
+			color: 0,
+			comment: "".into(),
+			start: at(2, 10),
+			segments: v10::WireSegments::Segments(vec![
+				v10::WireSegment { length: 4, direction: v10::WireDirection::Right },
+				v10::WireSegment { length: 0, direction: v10::WireDirection::Right },
+			].into()),
+		});
+
+		let merge = merge(&base, &ours, &theirs);
+		assert_eq!(merge.circuit.wires.len(), 4);
+		assert!(matches!(&*merge.conflicts, [Conflict::Wires(_)]), "{:?}", merge.conflicts);
+	}
+
+	#[test]
+	fn conflicting_wires() {
+		let base = testing::not_gate();
+
+		let mut ours = base.clone();
+		let wire = ours.wires.get_mut(0).unwrap();
+		wire.color = 1;
+		wire.comment = "ours".into();
+
+		let mut theirs = base.clone();
+		let wire = theirs.wires.get_mut(0).unwrap();
+		wire.color = 2;
+		wire.comment = "theirs".into();
+		theirs.wires.get_mut(1).unwrap().color = 3;
+
+		let merge = merge(&base, &ours, &theirs);
+		let path = super::wire_path(&base.wires.get(0).unwrap().into_inner());
+		assert!(matches!(
+			&*merge.conflicts,
+			[
+				Conflict::Wire { path: path1, field: "color" },
+				Conflict::Wire { path: path2, field: "comment" },
+			] if *path1 == path && *path2 == path,
+		), "{:?}", merge.conflicts);
+		assert_eq!(merge.conflicts[0].to_string(), format!("both sides changed color of wire {path}, kept ours"));
+
+		let wires: Vec<_> = merge.circuit.wires.iter().map(Either::into_inner).collect();
+		assert_eq!(wires.len(), 2);
+		assert_eq!((wires[0].color, &*wires[0].comment), (1, "ours"));
+		assert_eq!(wires[1].color, 3);
+	}
+
+	#[test]
+	fn both_removed_the_same_wire() {
+		let base = testing::not_gate();
+		let removed = base.wires.get(0).unwrap().into_inner();
+
+		let mut ours = base.clone();
+		ours.wires.remove(0);
+		ours.wires.get_mut(0).unwrap().color = 1;
+
+		let mut theirs = base.clone();
+		theirs.wires.remove(0);
+		theirs.wires.push(v10::Wire { start: at(20, 20), ..removed.clone() });
+
+		let merge = merge(&base, &ours, &theirs);
+		assert!(merge.conflicts.is_empty(), "{:?}", merge.conflicts);
+
+		let wires: Vec<_> = merge.circuit.wires.iter().map(Either::into_inner).collect();
+		assert_eq!(wires.len(), 2);
+		assert_eq!(wires[0].color, 1);
+		assert_eq!(wires[1].start, at(20, 20));
+		assert!(wires.iter().all(|wire| wire.start != removed.start));
+	}
+
+	#[test]
+	fn removed_by_ours() {
+		let base = testing::not_gate();
+
+		let mut ours = base.clone();
+		ours.components.remove(2);
+
+		let mut theirs = base.clone();
+		component(&mut theirs, 3).settings = vec![1].into();
+
+		let merge = merge(&base, &ours, &theirs);
+		assert!(matches!(&*merge.conflicts, [Conflict::RemovedAndChanged { permanent_id: 3, removed_by: Side::Ours }]), "{:?}", merge.conflicts);
+		assert_eq!(merge.conflicts[0].to_string(), "ours removed component 3 but theirs changed it, kept theirs");
+
+		let mut circuit = merge.circuit;
+		assert_eq!(circuit.components.len(), 3);
+		assert_eq!(component(&mut circuit, 3).settings.iter().map(Either::into_inner).collect::<Vec<_>>(), [1]);
+	}
+}
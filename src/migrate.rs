@@ -0,0 +1,464 @@
+//! Migration of saves from older versions to newer ones, one version at a time.
+//!
+//! Each step converts everything that has an equivalent in the next version.
+//! Components and fields that do not are dropped and reported as [`MigrationIssue`]s.
+
+use crate::{
+	Either,
+	Parse,
+	Slice,
+	circuit,
+	v6, v7, v8, v9, v10,
+};
+
+#[derive(Debug)]
+pub struct Migration<T> {
+	pub circuit: T,
+	pub issues: Vec<MigrationIssue>,
+}
+
+impl<T> Migration<T> {
+	fn and_then<U>(self, f: impl FnOnce(&T) -> Migration<U>) -> Migration<U> {
+		let Migration { circuit, mut issues } = f(&self.circuit);
+		let mut all_issues = self.issues;
+		all_issues.append(&mut issues);
+		Migration { circuit, issues: all_issues }
+	}
+}
+
+#[derive(Debug)]
+pub enum MigrationIssue {
+	/// The component's kind has no equivalent in the newer version, so the component was dropped.
+	UnsupportedComponent { permanent_id: u64, kind: circuit::ComponentKind },
+
+	/// The given field of the component has no equivalent in the newer version, so its value was dropped.
+	DroppedField { permanent_id: u64, field: &'static str },
+}
+
+impl std::fmt::Display for MigrationIssue {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::UnsupportedComponent { permanent_id, kind } => write!(f, "component {permanent_id} of kind {kind} has no equivalent and was dropped"),
+			Self::DroppedField { permanent_id, field } => write!(f, "{field} of component {permanent_id} has no equivalent and was dropped"),
+		}
+	}
+}
+
+pub fn v6_to_v10<'a>(circuit: &v6::CircuitData<'a>) -> Migration<v10::CircuitData<'a>> {
+	v6_to_v7(circuit).and_then(v7_to_v8).and_then(v8_to_v9).and_then(v9_to_v10)
+}
+
+pub fn v7_to_v10<'a>(circuit: &v7::CircuitData<'a>) -> Migration<v10::CircuitData<'a>> {
+	v7_to_v8(circuit).and_then(v8_to_v9).and_then(v9_to_v10)
+}
+
+pub fn v8_to_v10<'a>(circuit: &v8::CircuitData<'a>) -> Migration<v10::CircuitData<'a>> {
+	v8_to_v9(circuit).and_then(v9_to_v10)
+}
+
+fn to_slice<'a, N, T>(slice: &Slice<'a, N, T>) -> Slice<'a, N, T> where T: Parse<'a> + Clone {
+	slice.iter().map(Either::into_inner).collect::<Vec<_>>().into()
+}
+
+fn point<P, Q>(point: P) -> Q where P: Into<circuit::Point>, circuit::Point: Into<Q> {
+	point.into().into()
+}
+
+fn is_empty<'a, N, T>(slice: &Slice<'a, N, T>) -> bool where T: Parse<'a> {
+	slice.iter().next().is_none()
+}
+
+pub fn v6_to_v7<'a>(circuit: &v6::CircuitData<'a>) -> Migration<v7::CircuitData<'a>> {
+	let mut issues = vec![];
+
+	let components: Vec<_> = circuit.components.iter().filter_map(|component| {
+		let component = component.into_inner();
+		let (kind, word_size) = circuit::ComponentKind::from_v6(component.kind);
+		let Ok(kind) = v7::ComponentKind::try_from(kind) else {
+			issues.push(MigrationIssue::UnsupportedComponent { permanent_id: component.permanent_id, kind });
+			return None;
+		};
+
+		if component.custom_data.is_some_and(|custom_data| custom_data.custom_nudge != v6::Point { x: 0, y: 0 }) {
+			issues.push(MigrationIssue::DroppedField { permanent_id: component.permanent_id, field: "CustomCompData.custom_nudge" });
+		}
+
+		if component.assembler_data.is_some_and(|assembler_data| !is_empty(&assembler_data.programs)) {
+			issues.push(MigrationIssue::DroppedField { permanent_id: component.permanent_id, field: "AssemblerInfo.programs" });
+		}
+
+		Some(v7::Component {
+			kind,
+			position: point(component.position),
+			rotation: component.rotation,
+			permanent_id: component.permanent_id,
+			custom_string: component.custom_string,
+			settings: component.settings.to_vec().into(),
+			buffer_size: 0,
+			ui_order: component.ui_order,
+			word_size,
+			discarded: 0,
+			custom_data: component.custom_data.map(|custom_data| v7::CustomCompData {
+				id: custom_data.id,
+				static_states: vec![].into(),
+				linked_word_sizes: vec![].into(),
+			}),
+			assembler_data: kind.has_assembler_data().then(|| v7::AssemblerInfo {
+				programs: vec![].into(),
+				watched_components: vec![].into(),
+			}),
+		})
+	}).collect();
+
+	let wires: Vec<_> = circuit.wires.iter().map(|wire| {
+		let wire = wire.as_inner_ref();
+		v7::Wire {
+			color: wire.color,
+			comment: wire.comment.clone(),
+			start: point(wire.start),
+			segments: circuit::WireSegments::from(&wire.segments).into(),
+		}
+	}).collect();
+
+	Migration {
+		circuit: v7::CircuitData {
+			custom_id: circuit.custom_id,
+			hub_id: circuit.hub_id,
+			gate: circuit.gate,
+			delay: circuit.delay,
+			menu_visible: circuit.menu_visible,
+			clock_speed: circuit.clock_speed.into(),
+			dependencies: to_slice(&circuit.dependencies),
+			description: circuit.description.clone(),
+			camera_position: point(circuit.camera_position),
+			synced: circuit::SyncState::from(circuit.synced).into(),
+			player_data: to_slice(&circuit.player_data),
+			hub_description: circuit.hub_description.clone(),
+			components: components.into(),
+			wires: wires.into(),
+		},
+		issues,
+	}
+}
+
+pub fn v7_to_v8<'a>(circuit: &v7::CircuitData<'a>) -> Migration<v8::CircuitData<'a>> {
+	let mut issues = vec![];
+
+	let components: Vec<_> = circuit.components.iter().filter_map(|component| {
+		let component = component.into_inner();
+		let kind = circuit::ComponentKind::from(component.kind);
+		let Ok(kind) = v8::ComponentKind::try_from(kind) else {
+			issues.push(MigrationIssue::UnsupportedComponent { permanent_id: component.permanent_id, kind });
+			return None;
+		};
+
+		if component.custom_data.as_ref().is_some_and(|custom_data| !is_empty(&custom_data.linked_word_sizes)) {
+			issues.push(MigrationIssue::DroppedField { permanent_id: component.permanent_id, field: "CustomCompData.linked_word_sizes" });
+		}
+
+		let (programs, watched_components) = match component.assembler_data {
+			Some(assembler_data) => (assembler_data.programs, assembler_data.watched_components.iter().map(|watched_component| {
+				let watched_component = watched_component.into_inner();
+				v8::WatchedComponent {
+					permanent_id: watched_component.permanent_id,
+					inner_id: watched_component.inner_id,
+					name: watched_component.name,
+				}
+			}).collect::<Vec<_>>().into()),
+			None => (vec![].into(), vec![].into()),
+		};
+
+		let selected_programs = matches!(kind, v8::ComponentKind::Assembler).then(|| v8::AssemblerInfo { programs: to_slice(&programs) });
+		if selected_programs.is_none() && !is_empty(&programs) {
+			issues.push(MigrationIssue::DroppedField { permanent_id: component.permanent_id, field: "AssemblerInfo.programs" });
+		}
+
+		Some(v8::Component {
+			kind,
+			position: point(component.position),
+			rotation: component.rotation,
+			permanent_id: component.permanent_id,
+			custom_string: component.custom_string,
+			settings: component.settings,
+			buffer_size: component.buffer_size,
+			ui_order: component.ui_order,
+			word_size: component.word_size,
+			watched_components,
+			custom_data: component.custom_data.map(|custom_data| v8::CustomCompData {
+				id: custom_data.id,
+				static_states: custom_data.static_states,
+			}),
+			selected_programs,
+		})
+	}).collect();
+
+	let wires: Vec<_> = circuit.wires.iter().map(|wire| {
+		let wire = wire.as_inner_ref();
+		v8::Wire {
+			color: wire.color,
+			comment: wire.comment.clone(),
+			start: point(wire.start),
+			segments: circuit::WireSegments::from(&wire.segments).into(),
+		}
+	}).collect();
+
+	Migration {
+		circuit: v8::CircuitData {
+			custom_id: circuit.custom_id,
+			hub_id: circuit.hub_id,
+			gate: circuit.gate,
+			delay: circuit.delay,
+			menu_visible: circuit.menu_visible,
+			clock_speed: circuit.clock_speed,
+			dependencies: to_slice(&circuit.dependencies),
+			description: circuit.description.clone(),
+			camera_position: point(circuit.camera_position),
+			synced: circuit::SyncState::from(circuit.synced).into(),
+			player_data: to_slice(&circuit.player_data),
+			hub_description: circuit.hub_description.clone(),
+			components: components.into(),
+			wires: wires.into(),
+		},
+		issues,
+	}
+}
+
+pub fn v8_to_v9<'a>(circuit: &v8::CircuitData<'a>) -> Migration<v9::CircuitData<'a>> {
+	let mut issues = vec![];
+
+	let components: Vec<_> = circuit.components.iter().filter_map(|component| {
+		let component = component.into_inner();
+		let kind = circuit::ComponentKind::from(component.kind);
+		let Ok(kind) = v9::ComponentKind::try_from(kind) else {
+			issues.push(MigrationIssue::UnsupportedComponent { permanent_id: component.permanent_id, kind });
+			return None;
+		};
+
+		Some(v9::Component {
+			kind,
+			position: point(component.position),
+			rotation: component.rotation,
+			permanent_id: component.permanent_id,
+			custom_string: component.custom_string,
+			settings: component.settings,
+			buffer_size: component.buffer_size,
+			ui_order: component.ui_order,
+			word_size: component.word_size,
+			watched_components: component.watched_components.iter().map(|watched_component| {
+				let watched_component = watched_component.into_inner();
+				v9::WatchedComponent {
+					permanent_id: watched_component.permanent_id,
+					inner_id: watched_component.inner_id,
+					name: watched_component.name,
+				}
+			}).collect::<Vec<_>>().into(),
+			selected_programs: v9::AssemblerInfo {
+				programs: component.selected_programs.map_or_else(|| vec![].into(), |selected_programs| selected_programs.programs),
+			},
+			custom_data: component.custom_data.map(|custom_data| v9::CustomCompData {
+				id: custom_data.id,
+				static_states: custom_data.static_states,
+			}),
+		})
+	}).collect();
+
+	let wires: Vec<_> = circuit.wires.iter().map(|wire| {
+		let wire = wire.as_inner_ref();
+		v9::Wire {
+			color: wire.color,
+			comment: wire.comment.clone(),
+			start: point(wire.start),
+			segments: circuit::WireSegments::from(&wire.segments).into(),
+		}
+	}).collect();
+
+	Migration {
+		circuit: v9::CircuitData {
+			custom_id: circuit.custom_id,
+			hub_id: circuit.hub_id,
+			gate: circuit.gate,
+			delay: circuit.delay,
+			menu_visible: circuit.menu_visible,
+			clock_speed: circuit.clock_speed,
+			dependencies: to_slice(&circuit.dependencies),
+			description: circuit.description.clone(),
+			camera_position: point(circuit.camera_position),
+			synced: circuit::SyncState::from(circuit.synced).into(),
+			player_data: to_slice(&circuit.player_data),
+			hub_description: circuit.hub_description.clone(),
+			components: components.into(),
+			wires: wires.into(),
+		},
+		issues,
+	}
+}
+
+pub fn v9_to_v10<'a>(circuit: &v9::CircuitData<'a>) -> Migration<v10::CircuitData<'a>> {
+	let mut issues = vec![];
+
+	let components: Vec<_> = circuit.components.iter().filter_map(|component| {
+		let component = component.into_inner();
+		let kind = circuit::ComponentKind::from(component.kind);
+		let Ok(kind) = v10::ComponentKind::try_from(kind) else {
+			issues.push(MigrationIssue::UnsupportedComponent { permanent_id: component.permanent_id, kind });
+			return None;
+		};
+
+		Some(v10::Component {
+			kind,
+			position: point(component.position),
+			rotation: component.rotation,
+			permanent_id: component.permanent_id,
+			custom_string: component.custom_string,
+			settings: component.settings,
+			buffer_size: component.buffer_size,
+			ui_order: component.ui_order,
+			word_size: component.word_size,
+			linked_components: component.watched_components.iter().map(|watched_component| {
+				let watched_component = watched_component.into_inner();
+				v10::LinkedComponent {
+					permanent_id: watched_component.permanent_id,
+					inner_id: watched_component.inner_id,
+					name: watched_component.name,
+					offset: 0,
+				}
+			}).collect::<Vec<_>>().into(),
+			selected_programs: v10::AssemblerInfo {
+				programs: component.selected_programs.programs,
+			},
+			custom_data: component.custom_data.map(|custom_data| v10::CustomCompData {
+				id: custom_data.id,
+				static_states: custom_data.static_states,
+			}),
+		})
+	}).collect();
+
+	let wires: Vec<_> = circuit.wires.iter().map(|wire| {
+		let wire = wire.as_inner_ref();
+		v10::Wire {
+			color: wire.color,
+			comment: wire.comment.clone(),
+			start: point(wire.start),
+			segments: circuit::WireSegments::from(&wire.segments).into(),
+		}
+	}).collect();
+
+	Migration {
+		circuit: v10::CircuitData {
+			custom_id: circuit.custom_id,
+			hub_id: circuit.hub_id,
+			gate: circuit.gate,
+			delay: circuit.delay,
+			menu_visible: circuit.menu_visible,
+			clock_speed: circuit.clock_speed,
+			dependencies: to_slice(&circuit.dependencies),
+			description: circuit.description.clone(),
+			camera_position: point(circuit.camera_position),
+			synced: circuit::SyncState::from(circuit.synced).into(),
+			player_data: to_slice(&circuit.player_data),
+			hub_description: circuit.hub_description.clone(),
+			components: components.into(),
+			wires: wires.into(),
+		},
+		issues,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		Either,
+		Encode,
+		testing,
+		v7, v8, v10,
+	};
+	use super::{MigrationIssue, v6_to_v7, v6_to_v10, v7_to_v8, v8_to_v9, v9_to_v10};
+
+	fn issues(issues: &[MigrationIssue]) -> Vec<String> {
+		issues.iter().map(ToString::to_string).collect()
+	}
+
+	#[test]
+	fn v6() {
+		let migration = v6_to_v7(&testing::v6_circuit());
+		assert_eq!(issues(&migration.issues), [
+			"CustomCompData.custom_nudge of component 3 has no equivalent and was dropped",
+			"AssemblerInfo.programs of component 4 has no equivalent and was dropped",
+			"component 5 of kind Buffer1 has no equivalent and was dropped",
+		]);
+
+		let components: Vec<_> = migration.circuit.components.iter().map(Either::into_inner).collect();
+		assert_eq!(components.len(), 4);
+		assert!(matches!(components[1].kind, v7::ComponentKind::AndWord));
+		assert_eq!(components[1].word_size, 8);
+		assert_eq!(components[1].settings.iter().map(Either::into_inner).collect::<Vec<_>>(), [3, 4]);
+		assert_eq!(components[2].custom_data.as_ref().map(|custom_data| custom_data.id), Some(77));
+		assert!(matches!(components[3].kind, v7::ComponentKind::Assembler));
+		assert!(components[3].assembler_data.is_some());
+		assert_eq!(migration.circuit.wires.len(), 2);
+	}
+
+	#[test]
+	fn v7() {
+		let migration = v7_to_v8(&testing::v7_circuit());
+		assert_eq!(issues(&migration.issues), ["CustomCompData.linked_word_sizes of component 3 has no equivalent and was dropped"]);
+
+		let components: Vec<_> = migration.circuit.components.iter().map(Either::into_inner).collect();
+		assert_eq!(components[1].word_size, 16);
+
+		// The components that an assembler watched are now watched by the assembler component itself.
+		let assembler = &components[3];
+		assert!(matches!(assembler.kind, v8::ComponentKind::Assembler));
+		let watched: Vec<_> = assembler.watched_components.iter().map(Either::into_inner).map(|watched| (watched.permanent_id, watched.name.to_string())).collect();
+		assert_eq!(watched, [(2, "and".to_owned())]);
+		assert_eq!(assembler.selected_programs.as_ref().map(|selected_programs| selected_programs.programs.len()), Some(1));
+	}
+
+	#[test]
+	fn v8() {
+		let migration = v8_to_v9(&testing::v8_circuit());
+		assert_eq!(issues(&migration.issues), ["component 4 of kind Assembler has no equivalent and was dropped"]);
+
+		let components: Vec<_> = migration.circuit.components.iter().map(Either::into_inner).collect();
+		assert_eq!(components.len(), 3);
+		assert_eq!(components[1].watched_components.iter().map(Either::into_inner).map(|watched| watched.permanent_id).collect::<Vec<_>>(), [3]);
+	}
+
+	#[test]
+	fn v9() {
+		let migration = v9_to_v10(&testing::v9_circuit());
+		assert_eq!(issues(&migration.issues), ["component 4 of kind Ram has no equivalent and was dropped"]);
+
+		// Watched components are linked components at no offset.
+		let components: Vec<_> = migration.circuit.components.iter().map(Either::into_inner).collect();
+		let linked: Vec<_> = components[1].linked_components.iter().map(Either::into_inner).map(|linked| (linked.permanent_id, linked.inner_id, linked.name.to_string(), linked.offset)).collect();
+		assert_eq!(linked, [(3, -1, "custom".to_owned(), 0)]);
+		assert_eq!(components[1].selected_programs.programs.len(), 1);
+	}
+
+	#[test]
+	fn v6_to_latest() {
+		let migration = v6_to_v10(&testing::v6_circuit());
+		assert_eq!(issues(&migration.issues), [
+			"CustomCompData.custom_nudge of component 3 has no equivalent and was dropped",
+			"AssemblerInfo.programs of component 4 has no equivalent and was dropped",
+			"component 5 of kind Buffer1 has no equivalent and was dropped",
+			"component 4 of kind Assembler has no equivalent and was dropped",
+		]);
+
+		let crate::Save::V10(decoded) = crate::from_bytes(&migration.circuit.encode_final()).unwrap() else { panic!("expected a v10 save") };
+		let mut expected = vec![];
+		migration.circuit.encode(&mut expected);
+		let mut actual = vec![];
+		decoded.encode(&mut actual);
+		assert_eq!(actual, expected);
+
+		let components: Vec<_> = decoded.components.iter().map(Either::into_inner).collect();
+		assert_eq!(components.iter().map(|component| component.permanent_id).collect::<Vec<_>>(), [1, 2, 3]);
+		assert!(matches!(components[0].kind, v10::ComponentKind::LevelInput1));
+		assert!(matches!(components[1].kind, v10::ComponentKind::AndWord));
+		assert_eq!(components[1].word_size, 8);
+		assert_eq!(decoded.description, "A v6 circuit");
+		assert_eq!(decoded.wires.len(), 2);
+	}
+}
@@ -0,0 +1,374 @@
+//! Resolution of a v10 circuit's wires into nets, and of its components' positions into pins.
+//!
+//! Wires only connect at their ends. Every wire joins the net at its start point with the net at its end point,
+//! whether it gets there by following its segments or by teleporting with [`v10::WireSegments::TeleWireEnd`].
+//! Every pin that sits on one of those points is connected to that net.
+//!
+//! Pins are where [`crate::layout`] puts them. That is where [`crate::builder`] connects its wires, but generally not where the game does,
+//! so for circuits made in the game the nets of the wires are right, but pins are mostly left on nets of their own.
+//!
+//! Nets are numbered in the order of the smallest point on them, and components in the order they appear in the save,
+//! so IDs do not depend on the order of the wires.
+
+use crate::{
+	circuit,
+	layout::{
+		self,
+		PinDirection,
+	},
+	v10,
+};
+
+#[derive(Clone, Debug)]
+pub struct Netlist {
+	pub nets: Vec<Net>,
+
+	/// The circuit's components, in the same order as in the save.
+	pub components: Vec<Component>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NetId(pub usize);
+
+#[derive(Clone, Debug)]
+pub struct Net {
+	/// The points that make up the net, in ascending order.
+	pub points: Vec<circuit::Point>,
+
+	pub pins: Vec<PinId>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PinId {
+	/// The index of the component in [`Netlist::components`].
+	pub component: usize,
+	pub direction: PinDirection,
+	pub index: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct Component {
+	pub permanent_id: u64,
+	pub kind: v10::ComponentKind,
+
+	/// The component's `word_size` in bits. Components that do not have a valid one are treated as being 8 bits wide.
+	pub word_size: u32,
+
+	/// The `custom_id` of the schematic that a custom component is an instance of.
+	pub custom_id: Option<i64>,
+
+	/// Whether the component's pins are known, either from [`crate::layout`] or from the [`CustomPins`] of its schematic.
+	/// Components whose pins are not known have no pins.
+	pub known_pins: bool,
+
+	pub inputs: Vec<Pin>,
+	pub outputs: Vec<Pin>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Pin {
+	pub position: circuit::Point,
+	pub width: u32,
+	pub net: NetId,
+}
+
+/// The pins of a custom component, which are the `LevelInput*` and `LevelOutput*` components of its schematic in UI order.
+///
+/// Each pin is the `(permanent_id, width)` of the corresponding component in the schematic.
+/// Components with more than one pin become a single pin of a custom component, as wide as all of their pins together.
+#[derive(Clone, Debug, Default)]
+pub struct CustomPins {
+	pub inputs: Vec<(u64, u32)>,
+	pub outputs: Vec<(u64, u32)>,
+}
+
+impl CustomPins {
+	pub fn of(schematic: &v10::CircuitData<'_>) -> Self {
+		let mut inputs = vec![];
+		let mut outputs = vec![];
+		for component in &schematic.components {
+			let component = component.as_inner_ref();
+			let (pins, widths) =
+				if is_level_input(component.kind) { (&mut inputs, layout::v10_layout(component.kind).map(|layout| layout.outputs)) }
+				else if is_level_output(component.kind) { (&mut outputs, layout::v10_layout(component.kind).map(|layout| layout.inputs)) }
+				else { continue; };
+			let word_size = word_size(component.word_size);
+			let width = widths.unwrap_or_default().iter().map(|width| width.bits(word_size)).sum();
+			pins.push((component.ui_order, component.permanent_id, width));
+		}
+		inputs.sort_unstable();
+		outputs.sort_unstable();
+
+		Self {
+			inputs: inputs.into_iter().map(|(_, permanent_id, width)| (permanent_id, width)).collect(),
+			outputs: outputs.into_iter().map(|(_, permanent_id, width)| (permanent_id, width)).collect(),
+		}
+	}
+}
+
+impl Netlist {
+	/// Equivalent to [`Netlist::with_custom_components`] without any schematics, so custom components have no pins.
+	pub fn new(circuit: &v10::CircuitData<'_>) -> Self {
+		Self::with_custom_components(circuit, |_| None)
+	}
+
+	/// Resolves the circuit into nets. Custom components get their pins from the [`CustomPins`] that `custom_pins` returns for their `custom_id`.
+	///
+	/// Wires whose segments leave the range of a [`circuit::Point`] are left out.
+	pub fn with_custom_components(circuit: &v10::CircuitData<'_>, mut custom_pins: impl FnMut(i64) -> Option<CustomPins>) -> Self {
+		let mut points = Points::default();
+
+		for wire in &circuit.wires {
+			let wire = wire.as_inner_ref();
+			let start = circuit::Point::from(wire.start);
+			let end = match circuit::WireSegments::from(&wire.segments) {
+				circuit::WireSegments::TeleWireEnd(end) => Some(end),
+				circuit::WireSegments::Segments(segments) => segments.into_iter().try_fold(start, |end, segment| segment.step(end)),
+			};
+			let Some(end) = end else { continue; };
+			points.union(start, end);
+		}
+
+		// Pins are connected to their nets once all the points are known, so they start out with a placeholder net.
+		let mut components = vec![];
+		for component in &circuit.components {
+			let component = component.as_inner_ref();
+			let word_size = word_size(component.word_size);
+			let custom_id = component.custom_data.as_ref().map(|custom_data| custom_data.id);
+			let widths: Option<(Vec<_>, Vec<_>)> =
+				if matches!(component.kind, v10::ComponentKind::Custom) {
+					custom_id.and_then(&mut custom_pins).map(|CustomPins { inputs, outputs }| (
+						inputs.into_iter().map(|(_, width)| width).collect(),
+						outputs.into_iter().map(|(_, width)| width).collect(),
+					))
+				}
+				else {
+					layout::v10_layout(component.kind).map(|layout| (
+						layout.inputs.iter().map(|width| width.bits(word_size)).collect(),
+						layout.outputs.iter().map(|width| width.bits(word_size)).collect(),
+					))
+				};
+
+			let position = circuit::Point::from(component.position);
+			let mut pins = |direction, widths: Vec<u32>| {
+				(0..).zip(widths)
+				.map(|(i, width)| {
+					let position = layout::pin_position(position, component.rotation, direction, i);
+					_ = points.get(position);
+					Pin { position, width, net: NetId(usize::MAX) }
+				})
+				.collect()
+			};
+			let (inputs, outputs, known_pins) = match widths {
+				Some((inputs, outputs)) => (pins(PinDirection::Input, inputs), pins(PinDirection::Output, outputs), true),
+				None => (vec![], vec![], false),
+			};
+			components.push(Component {
+				permanent_id: component.permanent_id,
+				kind: component.kind,
+				word_size,
+				custom_id,
+				known_pins,
+				inputs,
+				outputs,
+			});
+		}
+
+		// Group the points by their root. Iterating in ascending order of points means each group is sorted,
+		// and that the groups are created in ascending order of their smallest point.
+		let mut net_ids: std::collections::BTreeMap<_, _> = Default::default();
+		let mut nets = vec![];
+		for (point, id) in points.ids.clone() {
+			let root = points.find(id);
+			let net = *net_ids.entry(root).or_insert_with(|| {
+				nets.push(Net { points: vec![], pins: vec![] });
+				nets.len() - 1
+			});
+			nets[net].points.push(point);
+		}
+
+		for (i, component) in components.iter_mut().enumerate() {
+			for (direction, pins) in [(PinDirection::Input, &mut component.inputs), (PinDirection::Output, &mut component.outputs)] {
+				for (index, pin) in pins.iter_mut().enumerate() {
+					let root = points.find(points.ids[&pin.position]);
+					let net = net_ids[&root];
+					pin.net = NetId(net);
+					nets[net].pins.push(PinId { component: i, direction, index });
+				}
+			}
+		}
+
+		Self { nets, components }
+	}
+
+	pub fn net(&self, id: NetId) -> &Net {
+		&self.nets[id.0]
+	}
+
+	/// The width of the net, which is the width of all of its pins, or `None` if it has no pins or they do not all have the same width.
+	pub fn width(&self, id: NetId) -> Option<u32> {
+		let mut widths = self.net(id).pins.iter().map(|&pin| self.pin(pin).width);
+		let width = widths.next()?;
+		widths.all(|other| other == width).then_some(width)
+	}
+
+	pub fn pin(&self, id: PinId) -> &Pin {
+		let component = &self.components[id.component];
+		match id.direction {
+			PinDirection::Input => &component.inputs[id.index],
+			PinDirection::Output => &component.outputs[id.index],
+		}
+	}
+}
+
+pub fn is_level_input(kind: v10::ComponentKind) -> bool {
+	matches!(
+		kind,
+		v10::ComponentKind::LevelInput1 |
+		v10::ComponentKind::LevelInputWord |
+		v10::ComponentKind::LevelInput2Pin |
+		v10::ComponentKind::LevelInput3Pin |
+		v10::ComponentKind::LevelInput4Pin
+	)
+}
+
+pub fn is_level_output(kind: v10::ComponentKind) -> bool {
+	matches!(
+		kind,
+		v10::ComponentKind::LevelOutput1 |
+		v10::ComponentKind::LevelOutputWord |
+		v10::ComponentKind::LevelOutput2Pin |
+		v10::ComponentKind::LevelOutput3Pin |
+		v10::ComponentKind::LevelOutput4Pin
+	)
+}
+
+fn word_size(word_size: i64) -> u32 {
+	u32::try_from(word_size).ok().filter(|bits| (1..=64).contains(bits)).unwrap_or(8)
+}
+
+/// A union-find of the points that wires and pins are on.
+#[derive(Default)]
+struct Points {
+	ids: std::collections::BTreeMap<circuit::Point, usize>,
+	parents: Vec<usize>,
+}
+
+impl Points {
+	fn get(&mut self, point: circuit::Point) -> usize {
+		*self.ids.entry(point).or_insert_with(|| {
+			self.parents.push(self.parents.len());
+			self.parents.len() - 1
+		})
+	}
+
+	fn find(&mut self, mut id: usize) -> usize {
+		while self.parents[id] != id {
+			self.parents[id] = self.parents[self.parents[id]];
+			id = self.parents[id];
+		}
+		id
+	}
+
+	fn union(&mut self, a: circuit::Point, b: circuit::Point) {
+		let a = self.get(a);
+		let a = self.find(a);
+		let b = self.get(b);
+		let b = self.find(b);
+		self.parents[a] = b;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		Save,
+		builder::{CircuitBuilder, at},
+		circuit,
+		layout::PinDirection,
+		testing,
+		v10,
+	};
+	use super::{CustomPins, NetId, Netlist, PinId};
+
+	#[test]
+	fn nets() {
+		let mut builder = CircuitBuilder::new();
+		let a = builder.add(v10::ComponentKind::LevelInput1, at(0, 0));
+		let b = builder.add(v10::ComponentKind::LevelInput1, at(0, 4));
+		let and = builder.add(v10::ComponentKind::AndBit, at(8, 0));
+		let out = builder.add(v10::ComponentKind::LevelOutput1, at(16, 0));
+		builder.wire(a.out(0), and.input(0));
+		builder.wire(b.out(0), at(4, 6)).wire(at(4, 6), and.input(1));
+		builder.wire(and.out(0), out.input(0));
+		let netlist = Netlist::new(&builder.build());
+
+		let pin = |component, direction, index| PinId { component, direction, index };
+		let net_of = |id: PinId| netlist.pin(id).net;
+
+		assert_eq!(net_of(pin(0, PinDirection::Output, 0)), net_of(pin(2, PinDirection::Input, 0)));
+		assert_eq!(net_of(pin(1, PinDirection::Output, 0)), net_of(pin(2, PinDirection::Input, 1)));
+		assert_eq!(net_of(pin(2, PinDirection::Output, 0)), net_of(pin(3, PinDirection::Input, 0)));
+		assert_ne!(net_of(pin(0, PinDirection::Output, 0)), net_of(pin(1, PinDirection::Output, 0)));
+
+		// The net through the corner at (4, 6) has the corner and both ends, and no other points.
+		let net = netlist.net(net_of(pin(1, PinDirection::Output, 0)));
+		assert_eq!(net.points.len(), 3);
+		assert_eq!(net.pins.len(), 2);
+		assert_eq!(netlist.width(net_of(pin(1, PinDirection::Output, 0))), Some(1));
+
+		for (i, net) in netlist.nets.iter().enumerate() {
+			assert!(net.points.windows(2).all(|points| points[0] < points[1]));
+			for &id in &net.pins {
+				assert_eq!(netlist.pin(id).net, NetId(i));
+			}
+		}
+	}
+
+	#[test]
+	fn widths_and_custom_components() {
+		let mut builder = CircuitBuilder::new();
+		let constant = builder.add(v10::ComponentKind::Constant, at(0, 0));
+		let not = builder.add(v10::ComponentKind::NotBit, at(8, 0));
+		let custom = builder.add_custom(77, at(8, 8), 0);
+		builder.wire(constant.out(0), not.input(0));
+		builder.wire(not.out(0), custom.input(0));
+		let circuit = builder.build();
+
+		let netlist = Netlist::new(&circuit);
+		let net = netlist.pin(PinId { component: 0, direction: PinDirection::Output, index: 0 }).net;
+		assert_eq!(netlist.net(net).pins.len(), 2);
+		assert_eq!(netlist.width(net), None);
+		assert!(!netlist.components[2].known_pins);
+		assert!(netlist.components[2].inputs.is_empty());
+
+		let netlist = Netlist::with_custom_components(&circuit, |custom_id| {
+			assert_eq!(custom_id, 77);
+			Some(CustomPins { inputs: vec![(1, 1)], outputs: vec![(2, 8)] })
+		});
+		assert!(netlist.components[2].known_pins);
+		let net = netlist.pin(PinId { component: 1, direction: PinDirection::Output, index: 0 }).net;
+		assert_eq!(netlist.width(net), Some(1));
+		assert_eq!(netlist.components[2].outputs[0].width, 8);
+	}
+
+	#[test]
+	fn decoded_saves() {
+		let Save::V10(circuit) = crate::from_bytes(&Save::V10(testing::not_gate()).encode_final()).unwrap() else { panic!("expected a v10 save") };
+		let netlist = Netlist::new(&circuit);
+		let pin = |component, direction, index| PinId { component, direction, index };
+		let net_of = |id: PinId| netlist.pin(id).net;
+		assert_eq!(net_of(pin(0, PinDirection::Output, 0)), net_of(pin(1, PinDirection::Input, 0)));
+		assert_eq!(net_of(pin(1, PinDirection::Output, 0)), net_of(pin(2, PinDirection::Input, 0)));
+		assert_ne!(net_of(pin(0, PinDirection::Output, 0)), net_of(pin(1, PinDirection::Output, 0)));
+
+		// The wires of a save written out field by field join the points at their ends, through segments or by teleporting.
+		let [.., Save::V10(circuit)] = testing::decoded_saves() else { panic!("expected a v10 save") };
+		let netlist = Netlist::new(&circuit);
+		let net_at = |x, y| netlist.nets.iter().position(|net| net.points.contains(&circuit::Point { x, y }));
+		assert!(net_at(1, 0).is_some());
+		assert_eq!(net_at(1, 0), net_at(5, 1));
+		assert_eq!(net_at(0, 0), net_at(9, 9));
+		assert_ne!(net_at(1, 0), net_at(0, 0));
+	}
+}
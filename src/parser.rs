@@ -1,5 +1,132 @@
 pub trait Parse<'a>: Sized {
-	fn parse(input: &mut &'a [u8]) -> Self;
+	fn parse(input: &mut Input<'a>) -> Result<Self, ParseError>;
+
+	/// Parses `Self` from all of `input`. Any bytes left over after `Self` are an error.
+	fn parse_from(input: &'a [u8]) -> Result<Self, ParseError> {
+		let mut input = Input::new(input);
+		let result = Self::parse(&mut input)?;
+		if !input.is_empty() {
+			return Err(input.error(ParseErrorKind::TrailingBytes));
+		}
+		Ok(result)
+	}
+}
+
+/// The bytes that are left to parse, and where they start in the input that parsing started from.
+#[derive(Clone, Copy, Debug)]
+pub struct Input<'a> {
+	pub bytes: &'a [u8],
+	pub offset: usize,
+}
+
+impl<'a> Input<'a> {
+	pub fn new(bytes: &'a [u8]) -> Self {
+		Self { bytes, offset: 0 }
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.bytes.is_empty()
+	}
+
+	/// Splits off the next `len` bytes.
+	pub fn take(&mut self, len: usize) -> Result<Self, ParseError> {
+		let (bytes, rest) = self.bytes.split_at_checked(len).ok_or_else(|| self.error(ParseErrorKind::UnexpectedEof))?;
+		let result = Self { bytes, offset: self.offset };
+		*self = Self { bytes: rest, offset: self.offset + len };
+		Ok(result)
+	}
+
+	/// An error at the start of the remaining bytes.
+	pub fn error(&self, kind: ParseErrorKind) -> ParseError {
+		ParseError {
+			offset: self.offset,
+			field: None,
+			kind,
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+	/// Offset of the input at which the failing read started.
+	///
+	/// This is relative to the start of the buffer given to [`Parse::parse_from`] or [`Input::new`].
+	pub offset: usize,
+
+	/// The innermost field that was being read, like `Component.settings`.
+	pub field: Option<&'static str>,
+
+	pub kind: ParseErrorKind,
+}
+
+impl std::fmt::Display for ParseError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		if let Some(field) = self.field {
+			write!(f, "could not parse {field} at offset {}", self.offset)
+		}
+		else {
+			write!(f, "could not parse input at offset {}", self.offset)
+		}
+	}
+}
+
+impl std::error::Error for ParseError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		Some(&self.kind)
+	}
+}
+
+#[derive(Debug)]
+pub enum ParseErrorKind {
+	UnexpectedEof,
+	InvalidUtf8(std::str::Utf8Error),
+	UnknownEnumValue { name: &'static str, value: u64 },
+	Decompress(snap::Error),
+	UnsupportedVersion(u8),
+
+	/// A length prefix is negative, or too large for this platform.
+	LengthOutOfRange,
+
+	/// The input continues after the value that was parsed from it.
+	TrailingBytes,
+}
+
+impl std::fmt::Display for ParseErrorKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::UnexpectedEof => f.write_str("unexpected end of input"),
+			Self::InvalidUtf8(_) => f.write_str("string is not valid UTF-8"),
+			Self::UnknownEnumValue { name, value } => write!(f, "unknown {name} value {value}"),
+			Self::Decompress(_) => f.write_str("could not decompress input"),
+			Self::UnsupportedVersion(version) => write!(f, "version {version} unsupported"),
+			Self::LengthOutOfRange => f.write_str("length is out of range"),
+			Self::TrailingBytes => f.write_str("unexpected data after the end"),
+		}
+	}
+}
+
+impl std::error::Error for ParseErrorKind {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::InvalidUtf8(err) => Some(err),
+			Self::Decompress(err) => Some(err),
+			_ => None,
+		}
+	}
+}
+
+pub(crate) trait ParseResultExt {
+	/// Records `field` as the field being read, unless an inner parser already recorded a more specific one.
+	fn field(self, field: &'static str) -> Self;
+}
+
+impl<T> ParseResultExt for Result<T, ParseError> {
+	fn field(self, field: &'static str) -> Self {
+		self.map_err(|mut err| {
+			_ = err.field.get_or_insert(field);
+			err
+		})
+	}
 }
 
 pub trait Encode {
@@ -10,12 +137,24 @@ pub trait EncodeInt {
 	fn encode_le_bytes(self, out: &mut [u8]);
 }
 
+/// Conversion of a parsed value into one that does not borrow from the input, so that it can outlive it.
+pub trait IntoOwned {
+	type Owned: 'static;
+
+	fn into_owned(self) -> Self::Owned;
+}
+
+impl IntoOwned for bool {
+	type Owned = Self;
+
+	fn into_owned(self) -> Self::Owned {
+		self
+	}
+}
+
 impl Parse<'_> for bool {
-	fn parse(input: &mut &[u8]) -> Self {
-		let (result, rest) = input.split_first().unwrap();
-		let result = *result != 0;
-		*input = rest;
-		result
+	fn parse(input: &mut Input<'_>) -> Result<Self, ParseError> {
+		Ok(input.take(1)?.bytes[0] != 0)
 	}
 }
 
@@ -29,11 +168,9 @@ macro_rules! parse_int {
 	($($ty:ty),*) => {
 		$(
 			impl Parse<'_> for $ty {
-				fn parse(input: &mut &[u8]) -> Self {
-					let (result, rest) = input.split_at(std::mem::size_of::<$ty>());
-					let result = <$ty>::from_le_bytes(result.try_into().unwrap());
-					*input = rest;
-					result
+				fn parse(input: &mut Input<'_>) -> Result<Self, ParseError> {
+					let result = input.take(std::mem::size_of::<$ty>())?;
+					Ok(<$ty>::from_le_bytes(result.bytes.try_into().unwrap()))
 				}
 			}
 
@@ -48,6 +185,14 @@ macro_rules! parse_int {
 					out.copy_from_slice(&self.to_le_bytes());
 				}
 			}
+
+			impl IntoOwned for $ty {
+				type Owned = Self;
+
+				fn into_owned(self) -> Self::Owned {
+					self
+				}
+			}
 		)*
 	};
 }
@@ -55,10 +200,10 @@ macro_rules! parse_int {
 parse_int! { i8, u8, i16, u16, i32, u32, i64, u64 }
 
 impl<'a, A, B> Parse<'a> for (A, B) where A: Parse<'a>, B: Parse<'a> {
-	fn parse(input: &mut &'a [u8]) -> Self {
-		let a = <_>::parse(input);
-		let b = <_>::parse(input);
-		(a, b)
+	fn parse(input: &mut Input<'a>) -> Result<Self, ParseError> {
+		let a = <_>::parse(input)?;
+		let b = <_>::parse(input)?;
+		Ok((a, b))
 	}
 }
 
@@ -69,6 +214,14 @@ impl<A, B> Encode for (A, B) where A: Encode, B: Encode {
 	}
 }
 
+impl<A, B> IntoOwned for (A, B) where A: IntoOwned, B: IntoOwned {
+	type Owned = (A::Owned, B::Owned);
+
+	fn into_owned(self) -> Self::Owned {
+		(self.0.into_owned(), self.1.into_owned())
+	}
+}
+
 impl<T> Encode for Option<T> where T: Encode {
 	fn encode(&self, out: &mut Vec<u8>) {
 		if let Some(this) = self {
@@ -77,64 +230,227 @@ impl<T> Encode for Option<T> where T: Encode {
 	}
 }
 
+impl<T> IntoOwned for Option<T> where T: IntoOwned {
+	type Owned = Option<T::Owned>;
+
+	fn into_owned(self) -> Self::Owned {
+		self.map(IntoOwned::into_owned)
+	}
+}
+
+/// A list of `T`s that is encoded with a length of type `N`, or without a length at the end of its input.
+///
+/// A parsed slice borrows its elements' bytes from the input and records where each element starts, so [`Slice::len`] is cheap
+/// and [`Slice::get`] only parses the element it returns. The elements are not kept after they are parsed, so every call to `get`
+/// and every iteration parses them again. [`Slice::to_mut`] and [`IntoOwned::into_owned`] parse them once into a `Vec` instead.
 #[derive(Clone)]
 pub struct Slice<'a, N, T> {
-	inner: Either<&'a [u8], Vec<T>>,
+	inner: Either<Elements<'a>, Vec<T>>,
 	len: std::marker::PhantomData<N>,
 	element: std::marker::PhantomData<T>,
 }
 
+/// The encoded elements of a borrowed [`Slice`], with where each of them starts so that they can be parsed individually.
+#[derive(Clone)]
+struct Elements<'a> {
+	input: Input<'a>,
+	offsets: Offsets,
+}
+
+#[derive(Clone)]
+enum Offsets {
+	/// Every element has the same encoded size, so element `i` starts at `i * stride`.
+	Stride { stride: usize, len: usize },
+
+	/// Element `i` starts at `starts[i]`.
+	Starts(Vec<usize>),
+}
+
+impl Offsets {
+	fn new() -> Self {
+		Self::Stride { stride: 0, len: 0 }
+	}
+
+	/// Records that the next element starts at `start`.
+	///
+	/// The starts are only allocated once an element turns out to have a different size than the ones before it.
+	fn push(&mut self, start: usize) {
+		match self {
+			Self::Stride { stride, len } => {
+				if *len == 1 {
+					*stride = start;
+				}
+				if start == *len * *stride {
+					*len += 1;
+				}
+				else {
+					let stride = *stride;
+					*self = Self::Starts((0..*len).map(|i| i * stride).chain([start]).collect());
+				}
+			},
+
+			Self::Starts(starts) => starts.push(start),
+		}
+	}
+
+	/// Records that the last element ends at `end`.
+	fn finish(&mut self, end: usize) {
+		if let Self::Stride { stride, len } = self {
+			if *len == 1 {
+				*stride = end;
+			}
+			if end != *len * *stride {
+				let stride = *stride;
+				*self = Self::Starts((0..*len).map(|i| i * stride).collect());
+			}
+		}
+	}
+}
+
+impl<'a> Elements<'a> {
+	fn len(&self) -> usize {
+		match &self.offsets {
+			Offsets::Stride { len, .. } => *len,
+			Offsets::Starts(starts) => starts.len(),
+		}
+	}
+
+	/// Parses element `i`, or returns `None` if it is out of bounds or does not parse.
+	///
+	/// The slices that hold elements parsed all of them when they were parsed themselves, so their elements always parse again.
+	fn get<T>(&self, i: usize) -> Option<T> where T: Parse<'a> {
+		if i >= self.len() {
+			return None;
+		}
+		let start = match &self.offsets {
+			Offsets::Stride { stride, .. } => i.checked_mul(*stride)?,
+			Offsets::Starts(starts) => starts[i],
+		};
+		let mut input = Input { bytes: self.input.bytes.get(start..)?, offset: self.input.offset + start };
+		T::parse(&mut input).ok()
+	}
+
+	/// Parses every element, up to the first one that does not parse.
+	fn to_vec<T>(&self) -> Vec<T> where T: Parse<'a> {
+		(0..self.len()).map_while(|i| self.get(i)).collect()
+	}
+}
+
 impl<'a, N, T> Slice<'a, N, T> where T: Parse<'a> {
-	pub fn parse_until_end(inner: &mut &'a [u8]) -> Self {
-		Self {
-			inner: Either::Left(std::mem::take(inner)),
+	pub fn parse_until_end(inner: &mut Input<'a>) -> Result<Self, ParseError> {
+		let inner = inner.take(inner.bytes.len())?;
+
+		let mut offsets = Offsets::new();
+		let mut rest = inner;
+		while !rest.is_empty() {
+			offsets.push(rest.offset - inner.offset);
+			drop(T::parse(&mut rest)?);
+		}
+		offsets.finish(inner.bytes.len());
+
+		Ok(Self {
+			inner: Either::Left(Elements { input: inner, offsets }),
 			len: Default::default(),
 			element: Default::default(),
-		}
+		})
 	}
 
 	pub fn iter(&self) -> SliceIter<'a, '_, T> {
 		SliceIter {
 			inner: match &self.inner {
-				Either::Left(inner) => Either::Left(inner),
+				Either::Left(inner) => Either::Left((inner, 0..inner.len())),
 				Either::Right(inner) => Either::Right(inner.iter()),
 			},
-			element: self.element,
 		}
 	}
-}
 
-impl<N, T> Slice<'_, N, T> {
+	/// The element at index `i`, or `None` if it is out of bounds.
+	pub fn get(&self, i: usize) -> Option<Either<T, &T>> {
+		match &self.inner {
+			Either::Left(inner) => inner.get(i).map(Either::Left),
+			Either::Right(inner) => inner.get(i).map(Either::Right),
+		}
+	}
+
+	/// The elements of the slice as a `Vec` that can be modified.
+	///
+	/// If the slice borrows its elements from the input, they are parsed into a `Vec` that the slice owns from then on.
+	/// The other methods that modify the slice do this too.
+	pub fn to_mut(&mut self) -> &mut Vec<T> {
+		if let Either::Left(inner) = &self.inner {
+			self.inner = Either::Right(inner.to_vec());
+		}
+		let Either::Right(inner) = &mut self.inner else { unreachable!(); };
+		inner
+	}
+
 	pub fn iter_mut(&mut self) -> SliceIterMut<'_, T> {
-		let Either::Right(inner) = &mut self.inner else { panic!(); };
 		SliceIterMut {
-			inner: inner.iter_mut(),
+			inner: self.to_mut().iter_mut(),
 		}
 	}
+
+	pub fn get_mut(&mut self, i: usize) -> Option<&mut T> {
+		self.to_mut().get_mut(i)
+	}
+
+	pub fn push(&mut self, element: T) {
+		self.to_mut().push(element);
+	}
+
+	/// Inserts `element` at index `i`, shifting the elements after it. Panics if `i` is greater than the length of the slice.
+	pub fn insert(&mut self, i: usize, element: T) {
+		self.to_mut().insert(i, element);
+	}
+
+	/// Removes and returns the element at index `i`, shifting the elements after it. Panics if `i` is out of bounds.
+	pub fn remove(&mut self, i: usize) -> T {
+		self.to_mut().remove(i)
+	}
+
+	pub fn retain(&mut self, f: impl FnMut(&T) -> bool) {
+		self.to_mut().retain(f);
+	}
+}
+
+impl<N, T> Slice<'_, N, T> {
+	pub fn len(&self) -> usize {
+		match &self.inner {
+			Either::Left(inner) => inner.len(),
+			Either::Right(inner) => inner.len(),
+		}
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
 }
 
 impl<'a, N, T> Slice<'a, N, T>
 where
 	N: Parse<'a>,
 	N: TryInto<usize>,
-	<N as TryInto<usize>>::Error: std::fmt::Debug,
 	T: Parse<'a>
 {
-	pub fn parse_with_length_prefix(input: &mut &'a [u8]) -> Self {
-		let len: usize = N::parse(input).try_into().unwrap();
+	pub fn parse_with_length_prefix(input: &mut Input<'a>) -> Result<Self, ParseError> {
+		let original_input = *input;
+		let len: usize = N::parse(input)?.try_into().map_err(|_| original_input.error(ParseErrorKind::LengthOutOfRange))?;
 		let original_input = *input;
 
+		let mut offsets = Offsets::new();
 		for _ in 0..len {
-			drop(T::parse(input));
+			offsets.push(input.offset - original_input.offset);
+			drop(T::parse(input)?);
 		}
+		offsets.finish(input.offset - original_input.offset);
 
-		let consumed_input = &original_input[..(original_input.len() - input.len())];
+		let consumed_input = Input { bytes: &original_input.bytes[..(input.offset - original_input.offset)], offset: original_input.offset };
 
-		Self {
-			inner: Either::Left(consumed_input),
+		Ok(Self {
+			inner: Either::Left(Elements { input: consumed_input, offsets }),
 			len: Default::default(),
 			element: Default::default(),
-		}
+		})
 	}
 }
 
@@ -183,6 +499,34 @@ impl<'a, N, T> std::fmt::Debug for Slice<'a, N, T> where T: std::fmt::Debug + Pa
 	}
 }
 
+#[cfg(feature = "serde")]
+impl<'a, N, T> serde::Serialize for Slice<'a, N, T> where T: Parse<'a> + serde::Serialize {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
+		serializer.collect_seq(self)
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de, N, T> serde::Deserialize<'de> for Slice<'_, N, T> where T: serde::Deserialize<'de> {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: serde::Deserializer<'de> {
+		let inner: Vec<T> = serde::Deserialize::deserialize(deserializer)?;
+		Ok(inner.into())
+	}
+}
+
+impl<'a, N, T> IntoOwned for Slice<'a, N, T> where N: 'static, T: Parse<'a> + IntoOwned {
+	type Owned = Slice<'static, N, T::Owned>;
+
+	/// Parses the elements of a borrowed slice into a `Vec`, and converts every element.
+	fn into_owned(self) -> Self::Owned {
+		let inner: Vec<_> = match self.inner {
+			Either::Left(inner) => inner.to_vec::<T>().into_iter().map(IntoOwned::into_owned).collect(),
+			Either::Right(inner) => inner.into_iter().map(IntoOwned::into_owned).collect(),
+		};
+		inner.into()
+	}
+}
+
 impl<N, T> From<Vec<T>> for Slice<'_, N, T> {
 	fn from(inner: Vec<T>) -> Self {
 		Self {
@@ -202,7 +546,7 @@ impl<'a, 'this, N, T> IntoIterator for &'this Slice<'a, N, T> where T: Parse<'a>
 	}
 }
 
-impl<'this, N, T> IntoIterator for &'this mut Slice<'_, N, T> {
+impl<'a, 'this, N, T> IntoIterator for &'this mut Slice<'a, N, T> where T: Parse<'a> {
 	type Item = &'this mut T;
 	type IntoIter = SliceIterMut<'this, T>;
 
@@ -212,8 +556,7 @@ impl<'this, N, T> IntoIterator for &'this mut Slice<'_, N, T> {
 }
 
 pub struct SliceIter<'a, 'this, T> {
-	inner: Either<&'a [u8], std::slice::Iter<'this, T>>,
-	element: std::marker::PhantomData<T>,
+	inner: Either<(&'this Elements<'a>, std::ops::Range<usize>), std::slice::Iter<'this, T>>,
 }
 
 impl<'a, 'this, T> Iterator for SliceIter<'a, 'this, T> where T: Parse<'a> {
@@ -221,16 +564,53 @@ impl<'a, 'this, T> Iterator for SliceIter<'a, 'this, T> where T: Parse<'a> {
 
 	fn next(&mut self) -> Option<Self::Item> {
 		match &mut self.inner {
-			Either::Left(inner) => if inner.is_empty() {
-				None
-			}
-			else {
-				Some(Either::Left(T::parse(inner)))
+			Either::Left((elements, range)) => {
+				let i = range.next()?;
+				next_element(elements, range, i).map(Either::Left)
 			},
-
 			Either::Right(inner) => inner.next().map(Either::Right),
 		}
 	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		match &self.inner {
+			Either::Left((_, range)) => range.size_hint(),
+			Either::Right(inner) => inner.size_hint(),
+		}
+	}
+
+	fn nth(&mut self, n: usize) -> Option<Self::Item> {
+		match &mut self.inner {
+			Either::Left((elements, range)) => {
+				let i = range.nth(n)?;
+				next_element(elements, range, i).map(Either::Left)
+			},
+			Either::Right(inner) => inner.nth(n).map(Either::Right),
+		}
+	}
+}
+
+impl<'a, T> DoubleEndedIterator for SliceIter<'a, '_, T> where T: Parse<'a> {
+	fn next_back(&mut self) -> Option<Self::Item> {
+		match &mut self.inner {
+			Either::Left((elements, range)) => {
+				let i = range.next_back()?;
+				next_element(elements, range, i).map(Either::Left)
+			},
+			Either::Right(inner) => inner.next_back().map(Either::Right),
+		}
+	}
+}
+
+impl<'a, T> ExactSizeIterator for SliceIter<'a, '_, T> where T: Parse<'a> {}
+
+/// Parses element `i` for a [`SliceIter`] over `range`. If it does not parse, the iterator ends there.
+fn next_element<'a, T>(elements: &Elements<'a>, range: &mut std::ops::Range<usize>, i: usize) -> Option<T> where T: Parse<'a> {
+	let element = elements.get(i);
+	if element.is_none() {
+		*range = 0..0;
+	}
+	element
 }
 
 pub struct SliceIterMut<'a, T> {
@@ -246,12 +626,25 @@ impl<'a, T> Iterator for SliceIterMut<'a, T> {
 }
 
 impl<'a> Parse<'a> for &'a str {
-	fn parse(input: &mut &'a [u8]) -> Self {
-		let len = usize::from(u16::parse(input));
-		let (result, rest) = input.split_at(len);
-		let result = str::from_utf8(result).unwrap();
-		*input = rest;
-		result
+	fn parse(input: &mut Input<'a>) -> Result<Self, ParseError> {
+		let original_input = *input;
+		let len = usize::from(u16::parse(input)?);
+		let result = input.take(len).map_err(|_| original_input.error(ParseErrorKind::UnexpectedEof))?;
+		str::from_utf8(result.bytes).map_err(|err| result.error(ParseErrorKind::InvalidUtf8(err)))
+	}
+}
+
+impl<'a> Parse<'a> for std::borrow::Cow<'a, str> {
+	fn parse(input: &mut Input<'a>) -> Result<Self, ParseError> {
+		Ok(std::borrow::Cow::Borrowed(<_>::parse(input)?))
+	}
+}
+
+impl IntoOwned for std::borrow::Cow<'_, str> {
+	type Owned = std::borrow::Cow<'static, str>;
+
+	fn into_owned(self) -> std::borrow::Cow<'static, str> {
+		std::borrow::Cow::Owned(std::borrow::Cow::into_owned(self))
 	}
 }
 
@@ -269,6 +662,12 @@ impl Encode for &str {
 	}
 }
 
+impl Encode for std::borrow::Cow<'_, str> {
+	fn encode(&self, out: &mut Vec<u8>) {
+		str::encode(self, out);
+	}
+}
+
 #[derive(Clone, Debug)]
 pub enum Either<L, R> {
 	Left(L),
@@ -283,12 +682,12 @@ impl<L, R> Either<L, R> {
 		}
 	}
 
-	pub fn parse_left<'a>(input: &mut &'a [u8]) -> Self where L: Parse<'a> {
-		Self::Left(<_>::parse(input))
+	pub fn parse_left<'a>(input: &mut Input<'a>) -> Result<Self, ParseError> where L: Parse<'a> {
+		Ok(Self::Left(<_>::parse(input)?))
 	}
 
-	pub fn parse_right<'a>(input: &mut &'a [u8]) -> Self where L: Parse<'a> {
-		Self::Left(<_>::parse(input))
+	pub fn parse_right<'a>(input: &mut Input<'a>) -> Result<Self, ParseError> where L: Parse<'a> {
+		Ok(Self::Left(<_>::parse(input)?))
 	}
 }
 
@@ -308,6 +707,13 @@ impl<T> Either<T, &T> {
 			Self::Right(inner) => inner,
 		}
 	}
+
+	pub fn into_inner(self) -> T where T: Clone {
+		match self {
+			Self::Left(inner) => inner,
+			Self::Right(inner) => inner.clone(),
+		}
+	}
 }
 
 impl<L, R> Iterator for Either<L, R> where L: Iterator, R: Iterator {
@@ -321,6 +727,16 @@ impl<L, R> Iterator for Either<L, R> where L: Iterator, R: Iterator {
 	}
 }
 
+#[cfg(feature = "serde")]
+impl<L, R> serde::Serialize for Either<L, R> where L: serde::Serialize, R: serde::Serialize {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
+		match self {
+			Self::Left(inner) => inner.serialize(serializer),
+			Self::Right(inner) => inner.serialize(serializer),
+		}
+	}
+}
+
 impl<L, R> Encode for Either<L, R> where L: Encode, R: Encode {
 	fn encode(&self, out: &mut Vec<u8>) {
 		match self {
@@ -342,11 +758,13 @@ macro_rules! enum_impl_from {
 			$($field_name = $field_value,)*
 		}
 
-		impl From<$repr_ty> for $enum_name {
-			fn from(raw: $repr_ty) -> Self {
+		impl TryFrom<$repr_ty> for $enum_name {
+			type Error = $crate::ParseErrorKind;
+
+			fn try_from(raw: $repr_ty) -> Result<Self, $crate::ParseErrorKind> {
 				match raw {
-					$($field_value => Self::$field_name,)*
-					_ => unreachable!("{raw:?}"),
+					$($field_value => Ok(Self::$field_name),)*
+					_ => Err($crate::ParseErrorKind::UnknownEnumValue { name: stringify!($enum_name), value: raw.into() }),
 				}
 			}
 		}
@@ -360,9 +778,10 @@ macro_rules! enum_impl_from {
 		}
 
 		impl<'a> $crate::Parse<'a> for $enum_name {
-			fn parse(input: &mut &'a [u8]) -> Self {
-				let raw = <$repr_ty>::parse(input);
-				raw.into()
+			fn parse(input: &mut $crate::Input<'a>) -> Result<Self, $crate::ParseError> {
+				let original_input = *input;
+				let raw = <$repr_ty>::parse(input)?;
+				raw.try_into().map_err(|kind| original_input.error(kind))
 			}
 		}
 
@@ -372,5 +791,177 @@ macro_rules! enum_impl_from {
 				raw.encode(out);
 			}
 		}
+
+		impl $enum_name {
+			/// The variant with the given name, as printed by its `Debug` impl.
+			pub fn from_name(name: &str) -> Option<Self> {
+				match name {
+					$(stringify!($field_name) => Some(Self::$field_name),)*
+					_ => None,
+				}
+			}
+		}
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		Either,
+		Elements,
+		Input,
+		Offsets,
+		Parse,
+		ParseErrorKind,
+		Slice,
 	};
+
+	fn offsets<'s, N, T>(slice: &'s Slice<'_, N, T>) -> &'s Offsets {
+		let Either::Left(elements) = &slice.inner else { panic!("slice is not borrowed"); };
+		&elements.offsets
+	}
+
+	#[test]
+	fn fixed_size_elements() {
+		let input = [3, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 0xff];
+		let mut input = Input::new(&input);
+		let slice = Slice::<u16, u32>::parse_with_length_prefix(&mut input).unwrap();
+		assert_eq!(input.offset, 14);
+		assert!(matches!(offsets(&slice), Offsets::Stride { stride: 4, len: 3 }));
+
+		assert_eq!(slice.len(), 3);
+		assert_eq!(slice.get(1).map(Either::into_inner), Some(2));
+		assert!(slice.get(3).is_none());
+		for _ in 0..2 {
+			assert_eq!(slice.iter().map(Either::into_inner).collect::<Vec<_>>(), [1, 2, 3]);
+		}
+		assert_eq!(slice.iter().rev().map(Either::into_inner).collect::<Vec<_>>(), [3, 2, 1]);
+		assert_eq!(slice.iter().nth(2).map(Either::into_inner), Some(3));
+		assert_eq!(slice.iter().len(), 3);
+	}
+
+	#[test]
+	fn variable_size_elements() {
+		let input = [3, 0, 1, 0, b'a', 0, 0, 3, 0, b'b', b'c', b'd'];
+		let slice = Slice::<u16, &str>::parse_with_length_prefix(&mut Input::new(&input)).unwrap();
+		assert!(matches!(offsets(&slice), Offsets::Starts(starts) if starts == &[0, 3, 5]));
+
+		assert_eq!(slice.len(), 3);
+		assert_eq!(slice.get(0).map(Either::into_inner), Some("a"));
+		assert_eq!(slice.get(1).map(Either::into_inner), Some(""));
+		assert_eq!(slice.get(2).map(Either::into_inner), Some("bcd"));
+		assert!(slice.get(3).is_none());
+		for _ in 0..2 {
+			assert_eq!(slice.iter().map(Either::into_inner).collect::<Vec<_>>(), ["a", "", "bcd"]);
+		}
+		assert_eq!(slice.iter().rev().map(Either::into_inner).collect::<Vec<_>>(), ["bcd", "", "a"]);
+		assert_eq!(slice.iter().nth(1).map(Either::into_inner), Some(""));
+	}
+
+	#[test]
+	fn short_and_uneven_slices() {
+		let slice = Slice::<u16, u32>::parse_with_length_prefix(&mut Input::new(&[0, 0])).unwrap();
+		assert!(slice.is_empty());
+		assert!(slice.get(0).is_none());
+		assert_eq!(slice.iter().count(), 0);
+
+		let slice = Slice::<u16, &str>::parse_with_length_prefix(&mut Input::new(&[1, 0, 2, 0, b'a', b'b'])).unwrap();
+		assert!(matches!(offsets(&slice), Offsets::Stride { stride: 4, len: 1 }));
+		assert_eq!(slice.get(0).map(Either::into_inner), Some("ab"));
+
+		// Every element but the last has the same size.
+		let input = [1, 0, b'a', 1, 0, b'b', 2, 0, b'c', b'd'];
+		let slice = Slice::<u16, &str>::parse_until_end(&mut Input::new(&input)).unwrap();
+		assert!(matches!(offsets(&slice), Offsets::Starts(starts) if starts == &[0, 3, 6]));
+		assert_eq!(slice.iter().map(Either::into_inner).collect::<Vec<_>>(), ["a", "b", "cd"]);
+	}
+
+	#[test]
+	fn error_offsets() {
+		// A string whose length says 5 bytes but that only has 2.
+		let err = <(u16, &str)>::parse_from(&[1, 0, 5, 0, b'a', b'b']).unwrap_err();
+		assert_eq!(err.offset, 2);
+		assert!(matches!(err.kind, ParseErrorKind::UnexpectedEof));
+
+		let err = <(u16, &str)>::parse_from(&[1, 0, 2, 0, b'a', 0xff]).unwrap_err();
+		assert_eq!(err.offset, 4);
+		assert!(matches!(err.kind, ParseErrorKind::InvalidUtf8(_)));
+
+		// A slice whose length says 3 elements but that only has 2.
+		let err = Slice::<u16, u32>::parse_with_length_prefix(&mut Input::new(&[3, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3])).unwrap_err();
+		assert_eq!(err.offset, 10);
+
+		// Offsets continue from the offset of the input, not from the start of its bytes.
+		let err = u32::parse(&mut Input { bytes: &[1, 2], offset: 100 }).unwrap_err();
+		assert_eq!(err.offset, 100);
+	}
+
+	#[test]
+	fn length_out_of_range() {
+		let err = Slice::<i16, u8>::parse_with_length_prefix(&mut Input::new(&[0xff, 0xff, 1])).unwrap_err();
+		assert_eq!(err.offset, 0);
+		assert!(matches!(err.kind, ParseErrorKind::LengthOutOfRange));
+	}
+
+	#[test]
+	fn elements_that_do_not_parse() {
+		// Elements that claim three 4-byte elements but only have the bytes for two and a half.
+		let elements = Elements { input: Input::new(&[1, 0, 0, 0, 2, 0, 0, 0, 3, 0]), offsets: Offsets::Stride { stride: 4, len: 3 } };
+		assert_eq!(elements.get::<u32>(1), Some(2));
+		assert_eq!(elements.get::<u32>(2), None);
+		assert_eq!(elements.get::<u32>(3), None);
+
+		let slice = Slice::<u16, u32> { inner: Either::Left(elements), len: Default::default(), element: Default::default() };
+		assert!(slice.get(2).is_none());
+		assert_eq!(slice.iter().map(Either::into_inner).collect::<Vec<_>>(), [1, 2]);
+		assert_eq!(slice.clone().to_mut(), &[1, 2]);
+	}
+
+	#[test]
+	fn copy_on_write() {
+		fn is_borrowed<N, T>(slice: &Slice<'_, N, T>) -> bool {
+			matches!(slice.inner, Either::Left(_))
+		}
+
+		fn encoded<'a>(slice: &Slice<'a, u16, &'a str>) -> Vec<u8> {
+			let mut out = vec![];
+			slice.encode_with_length_prefix(&mut out);
+			out
+		}
+
+		let input = [3, 0, 1, 0, b'a', 2, 0, b'b', b'c', 1, 0, b'd'];
+		let slice = Slice::<u16, &str>::parse_with_length_prefix(&mut Input::new(&input)).unwrap();
+		assert!(is_borrowed(&slice));
+		assert_eq!(encoded(&slice), input);
+
+		let mut pushed = slice.clone();
+		pushed.push("e");
+		assert!(!is_borrowed(&pushed));
+		assert_eq!(encoded(&pushed), [4, 0, 1, 0, b'a', 2, 0, b'b', b'c', 1, 0, b'd', 1, 0, b'e']);
+
+		let mut inserted = slice.clone();
+		inserted.insert(0, "");
+		assert!(!is_borrowed(&inserted));
+		assert_eq!(encoded(&inserted), [4, 0, 0, 0, 1, 0, b'a', 2, 0, b'b', b'c', 1, 0, b'd']);
+
+		let mut removed = slice.clone();
+		assert_eq!(removed.remove(1), "bc");
+		assert!(!is_borrowed(&removed));
+		assert_eq!(encoded(&removed), [2, 0, 1, 0, b'a', 1, 0, b'd']);
+
+		let mut retained = slice.clone();
+		retained.retain(|element| element.len() == 1);
+		assert!(!is_borrowed(&retained));
+		assert_eq!(encoded(&retained), [2, 0, 1, 0, b'a', 1, 0, b'd']);
+
+		let mut changed = slice.clone();
+		*changed.get_mut(2).unwrap() = "xyz";
+		assert!(!is_borrowed(&changed));
+		assert_eq!(encoded(&changed), [3, 0, 1, 0, b'a', 2, 0, b'b', b'c', 3, 0, b'x', b'y', b'z']);
+		assert!(changed.get_mut(3).is_none());
+
+		// The original slice still borrows the input, and is unchanged.
+		assert!(is_borrowed(&slice));
+		assert_eq!(slice.iter().map(Either::into_inner).collect::<Vec<_>>(), ["a", "bc", "d"]);
+	}
 }
@@ -0,0 +1,278 @@
+//! Rendering of circuits as SVG, and as PNG with the `png` feature.
+//!
+//! Components are drawn as boxes that cover their [`layout::footprint`]. Wires are drawn along their segments
+//! in the color of their palette index, and wires that end with [`WireSegments::TeleWireEnd`] are drawn as a dashed line to their end.
+//!
+//! The footprints are the ones that [`layout`] makes up, not the game's, so components of circuits made in the game
+//! are drawn around their positions but not in the shapes and places that the game draws them. Wires are drawn where they are.
+
+use crate::{
+	circuit::{
+		Circuit,
+		ComponentKind,
+		Point,
+		WireSegments,
+	},
+	layout,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct RenderOptions {
+	/// Whether to draw each component's `custom_string` and each wire's `comment`.
+	pub labels: bool,
+
+	/// The size of one cell of the grid in pixels.
+	pub cell_size: u32,
+}
+
+impl Default for RenderOptions {
+	fn default() -> Self {
+		Self {
+			labels: true,
+			cell_size: 16,
+		}
+	}
+}
+
+#[cfg(feature = "png")]
+#[derive(Debug)]
+pub enum RenderError {
+	Svg(resvg::usvg::Error),
+
+	/// The circuit is too large to render at the given cell size.
+	TooLarge,
+
+	Encode(String),
+}
+
+#[cfg(feature = "png")]
+impl std::fmt::Display for RenderError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Svg(_) => f.write_str("could not render SVG"),
+			Self::TooLarge => f.write_str("circuit is too large to render"),
+			Self::Encode(err) => write!(f, "could not encode PNG: {err}"),
+		}
+	}
+}
+
+#[cfg(feature = "png")]
+impl std::error::Error for RenderError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Svg(err) => Some(err),
+			Self::TooLarge | Self::Encode(_) => None,
+		}
+	}
+}
+
+/// Approximations of the colors of the game's wire palette, by palette index.
+const PALETTE: [&str; 12] = [
+	"#e3a34b", "#d9534f", "#5cb85c", "#4f8fe0",
+	"#a66fd1", "#d8c94a", "#4fc4c4", "#e07fb0",
+	"#9c9c9c", "#5e5e5e", "#f0f0f0", "#8b5a2b",
+];
+
+pub fn svg(circuit: &Circuit<'_>, options: RenderOptions) -> String {
+	use std::fmt::Write;
+
+	let boxes: Vec<_> = circuit.components.iter().map(|component| component_box(component.kind, component.position, component.rotation)).collect();
+	let paths: Vec<_> = circuit.wires.iter().map(|wire| match &wire.segments {
+		WireSegments::TeleWireEnd(end) => vec![wire.start, *end],
+		WireSegments::Segments(segments) => {
+			let mut points = vec![wire.start];
+			for segment in segments {
+				if segment.length == 0 {
+					continue;
+				}
+				let Some(point) = segment.step(points[points.len() - 1]) else { break; };
+				points.push(point);
+			}
+			points
+		},
+	}).collect();
+
+	let (mut min_x, mut min_y, mut max_x, mut max_y) = (0_f32, 0_f32, 0_f32, 0_f32);
+	let all_points =
+		boxes.iter().flatten().copied()
+		.chain(paths.iter().flatten().map(|point| (f32::from(point.x), f32::from(point.y))));
+	for (i, (x, y)) in all_points.enumerate() {
+		if i == 0 {
+			(min_x, min_y, max_x, max_y) = (x, y, x, y);
+		}
+		min_x = min_x.min(x);
+		min_y = min_y.min(y);
+		max_x = max_x.max(x);
+		max_y = max_y.max(y);
+	}
+
+	#[allow(clippy::cast_precision_loss)]
+	let cell = options.cell_size as f32;
+	let margin = 2.;
+	let to_px = |(x, y): (f32, f32)| ((x - min_x + margin) * cell, (y - min_y + margin) * cell);
+	let width = (max_x - min_x + 2. * margin) * cell;
+	let height = (max_y - min_y + 2. * margin) * cell;
+	let font_size = cell * 0.75;
+
+	let mut out = String::new();
+	_ = writeln!(out, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#);
+	_ = writeln!(out, r##"<rect width="100%" height="100%" fill="#2b2b2b"/>"##);
+
+	_ = writeln!(out, r#"<g fill="none" stroke-linecap="round" stroke-linejoin="round" stroke-width="{}">"#, cell / 4.);
+	for (wire, points) in circuit.wires.iter().zip(&paths) {
+		let color = PALETTE[usize::from(wire.color) % PALETTE.len()];
+		let points: Vec<_> = points.iter().map(|point| {
+			let (x, y) = to_px((f32::from(point.x), f32::from(point.y)));
+			format!("{x},{y}")
+		}).collect();
+		let dash = if matches!(wire.segments, WireSegments::TeleWireEnd(_)) { format!(r#" stroke-dasharray="{cell} {cell}""#) } else { String::new() };
+		_ = write!(out, r#"<polyline points="{}" stroke="{color}"{dash}>"#, points.join(" "));
+		if !wire.comment.is_empty() {
+			_ = write!(out, "<title>{}</title>", escape(&wire.comment));
+		}
+		_ = writeln!(out, "</polyline>");
+	}
+	_ = writeln!(out, "</g>");
+
+	_ = writeln!(out, r##"<g fill="#d0d0d0" stroke="#404040" stroke-width="1">"##);
+	for (component, corners) in circuit.components.iter().zip(&boxes) {
+		let points: Vec<_> = corners.iter().map(|&corner| {
+			let (x, y) = to_px(corner);
+			format!("{x},{y}")
+		}).collect();
+		_ = writeln!(out, r#"<polygon points="{}"><title>{} {}</title></polygon>"#, points.join(" "), component.kind, component.permanent_id);
+	}
+	_ = writeln!(out, "</g>");
+
+	if options.labels {
+		_ = writeln!(out, r#"<g font-family="sans-serif" font-size="{font_size}" text-anchor="middle" dominant-baseline="central">"#);
+		for (component, corners) in circuit.components.iter().zip(&boxes) {
+			if component.custom_string.is_empty() {
+				continue;
+			}
+
+			let center = corners.iter().fold((0., 0.), |(x, y), (cx, cy)| (x + cx / 4., y + cy / 4.));
+			let (x, y) = to_px(center);
+			_ = writeln!(out, r##"<text x="{x}" y="{y}" fill="#202020">{}</text>"##, escape(&component.custom_string));
+		}
+		for (wire, points) in circuit.wires.iter().zip(&paths) {
+			if wire.comment.is_empty() {
+				continue;
+			}
+
+			let (x, y) = to_px((f32::from(points[0].x), f32::from(points[0].y) - 0.75));
+			_ = writeln!(out, r##"<text x="{x}" y="{y}" fill="#f0f0f0">{}</text>"##, escape(&wire.comment));
+		}
+		_ = writeln!(out, "</g>");
+	}
+
+	_ = writeln!(out, "</svg>");
+	out
+}
+
+#[cfg(feature = "png")]
+pub fn png(circuit: &Circuit<'_>, options: RenderOptions) -> Result<Vec<u8>, RenderError> {
+	let svg = svg(circuit, options);
+
+	let mut usvg_options = resvg::usvg::Options::default();
+	if options.labels {
+		usvg_options.fontdb_mut().load_system_fonts();
+	}
+	let tree = resvg::usvg::Tree::from_str(&svg, &usvg_options).map_err(RenderError::Svg)?;
+	let size = tree.size().to_int_size();
+	let mut pixmap = resvg::tiny_skia::Pixmap::new(size.width(), size.height()).ok_or(RenderError::TooLarge)?;
+	resvg::render(&tree, resvg::tiny_skia::Transform::default(), &mut pixmap.as_mut());
+	pixmap.encode_png().map_err(|err| RenderError::Encode(err.to_string()))
+}
+
+/// The corners of the box of a component, in grid coordinates.
+fn component_box(kind: ComponentKind, position: Point, rotation: u8) -> [(f32, f32); 4] {
+	let rows = layout::layout(kind).map_or(1, |layout| layout.rows());
+	let bottom = f32::from(rows) - 0.5;
+
+	[(-1., -0.5), (1., -0.5), (1., bottom), (-1., bottom)].map(|(mut x, mut y)| {
+		for _ in 0..rotation % 4 {
+			(x, y) = (-y, x);
+		}
+		(f32::from(position.x) + x, f32::from(position.y) + y)
+	})
+}
+
+fn escape(s: &str) -> String {
+	let mut result = String::with_capacity(s.len());
+	for c in s.chars() {
+		match c {
+			'&' => result.push_str("&amp;"),
+			'<' => result.push_str("&lt;"),
+			'>' => result.push_str("&gt;"),
+			'"' => result.push_str("&quot;"),
+			c => result.push(c),
+		}
+	}
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		builder::{CircuitBuilder, at},
+		circuit::Circuit,
+		v10,
+	};
+	use super::RenderOptions;
+
+	/// An input wired to a NOT gate with a label, with a colored wire with a comment, and a telewire from the gate's output.
+	fn circuit() -> Circuit<'static> {
+		let mut builder = CircuitBuilder::new();
+		let input = builder.add(v10::ComponentKind::LevelInput1, at(0, 0));
+		let not = builder.add(v10::ComponentKind::NotBit, at(6, 0));
+		builder.component_mut(not).custom_string = "a<b".into();
+		builder.wire(input.out(0), not.input(0));
+		let mut circuit = builder.build();
+
+		let mut wire = circuit.wires.get(0).unwrap().into_inner();
+		wire.color = 3;
+		wire.comment = "x & y".into();
+		circuit.wires = vec![
+			wire,
+			v10::Wire { color: 5, comment: "".into(), start: not.out(0), segments: v10::WireSegments::TeleWireEnd(at(9, 4)) },
+		].into();
+		circuit.into()
+	}
+
+	#[test]
+	fn svg() {
+		let svg = super::svg(&circuit(), RenderOptions::default());
+		assert_eq!(svg, r##"<svg xmlns="http://www.w3.org/2000/svg" width="224" height="136" viewBox="0 0 224 136">
+<rect width="100%" height="100%" fill="#2b2b2b"/>
+<g fill="none" stroke-linecap="round" stroke-linejoin="round" stroke-width="4">
+<polyline points="64,40 128,40" stroke="#4f8fe0"><title>x &amp; y</title></polyline>
+<polyline points="160,40 192,104" stroke="#d8c94a" stroke-dasharray="16 16"></polyline>
+</g>
+<g fill="#d0d0d0" stroke="#404040" stroke-width="1">
+<polygon points="32,32 64,32 64,48 32,48"><title>LevelInput1 1</title></polygon>
+<polygon points="128,32 160,32 160,48 128,48"><title>NotBit 2</title></polygon>
+</g>
+<g font-family="sans-serif" font-size="12" text-anchor="middle" dominant-baseline="central">
+<text x="144" y="40" fill="#202020">a&lt;b</text>
+<text x="64" y="28" fill="#f0f0f0">x &amp; y</text>
+</g>
+</svg>
+"##);
+
+		let without_labels = super::svg(&circuit(), RenderOptions { labels: false, ..Default::default() });
+		assert!(!without_labels.contains("<text"));
+		assert!(without_labels.contains("<title>x &amp; y</title>"));
+	}
+
+	#[cfg(feature = "png")]
+	#[test]
+	fn png() {
+		let png = super::png(&circuit(), RenderOptions { labels: false, cell_size: 8 }).unwrap();
+		assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+		// The size in the IHDR chunk is half the size of the SVG, since the cells are half as large.
+		assert_eq!(png[16..20], 112_u32.to_be_bytes());
+		assert_eq!(png[20..24], 68_u32.to_be_bytes());
+	}
+}
@@ -0,0 +1,284 @@
+//! Automatic routing of wires between points of a v10 circuit.
+//!
+//! A [`Router`] finds the shortest path for each wire over the grid, taking steps in any of the eight [`circuit::WireDirection`]s.
+//! Among paths of the same length it prefers the one with the fewest turns.
+//!
+//! A path never passes through a cell covered by a component other than at its own two ends, using the footprints of [`layout::footprint`],
+//! and never takes a step that an existing wire or an earlier routed wire already takes. It may cross other wires, since wires only connect at their ends.
+//!
+//! The search is limited to the box around the circuit and the two ends, plus [`MARGIN`] cells on every side.
+//! If there is no path within it, the wire is made a tele-wire with [`v10::WireSegments::TeleWireEnd`] instead.
+
+use crate::{
+	builder,
+	circuit,
+	layout,
+	v10,
+};
+
+/// How many cells beyond the circuit and the ends of a wire that the router may go.
+pub const MARGIN: i16 = 8;
+
+const DIRECTIONS: [circuit::WireDirection; 8] = [
+	circuit::WireDirection::Right,
+	circuit::WireDirection::DownRight,
+	circuit::WireDirection::Down,
+	circuit::WireDirection::DownLeft,
+	circuit::WireDirection::Left,
+	circuit::WireDirection::UpLeft,
+	circuit::WireDirection::Up,
+	circuit::WireDirection::UpRight,
+];
+
+/// The cost of one step, relative to the cost of one turn.
+const STEP_COST: u32 = 8;
+
+#[derive(Debug)]
+pub struct Router {
+	/// Cells covered by components.
+	blocked: std::collections::BTreeSet<circuit::Point>,
+
+	/// Steps taken by wires, as `(min, max)` of their two ends.
+	used: std::collections::BTreeSet<(circuit::Point, circuit::Point)>,
+
+	/// The box around the circuit.
+	min: circuit::Point,
+	max: circuit::Point,
+}
+
+impl Router {
+	/// A router that avoids the components and wires of the given circuit.
+	pub fn new(circuit: &v10::CircuitData<'_>) -> Self {
+		let mut result = Self {
+			blocked: Default::default(),
+			used: Default::default(),
+			min: circuit::Point { x: i16::MAX, y: i16::MAX },
+			max: circuit::Point { x: i16::MIN, y: i16::MIN },
+		};
+
+		for component in &circuit.components {
+			let component = component.as_inner_ref();
+			for cell in layout::footprint(component.kind.into(), component.rotation, component.position.into()) {
+				result.include(cell);
+				result.blocked.insert(cell);
+			}
+		}
+
+		for wire in &circuit.wires {
+			let wire = wire.as_inner_ref();
+			let mut point: circuit::Point = wire.start.into();
+			result.include(point);
+			match &wire.segments {
+				v10::WireSegments::TeleWireEnd(end) => result.include((*end).into()),
+				v10::WireSegments::Segments(segments) => for segment in segments {
+					let segment = segment.as_inner_ref();
+					let (dx, dy) = circuit::WireDirection::from(segment.direction).delta();
+					for _ in 0..segment.length {
+						let next = circuit::Point { x: point.x + dx, y: point.y + dy };
+						result.used.insert((point.min(next), point.max(next)));
+						point = next;
+					}
+					result.include(point);
+				},
+			}
+		}
+
+		result
+	}
+
+	/// Routes a wire from `from` to `to`. Later wires routed by this router will not overlap it.
+	pub fn route(&mut self, from: v10::Point, to: v10::Point) -> v10::Wire<'static> {
+		let segments = match self.find_path(from.into(), to.into()) {
+			Some(path) => {
+				let mut segments = vec![];
+				for run in path.chunk_by(|a, b| a == b) {
+					builder::push_run(&mut segments, DIRECTIONS[usize::from(run[0])], run.len().try_into().unwrap_or(u32::MAX));
+				}
+				segments.push(v10::WireSegment { length: 0, direction: v10::WireDirection::Right });
+				v10::WireSegments::Segments(segments.into())
+			},
+
+			None => v10::WireSegments::TeleWireEnd(to),
+		};
+
+		v10::Wire {
+			color: 0,
+			comment: "".into(),
+			start: from,
+			segments,
+		}
+	}
+
+	fn include(&mut self, point: circuit::Point) {
+		self.min = circuit::Point { x: self.min.x.min(point.x), y: self.min.y.min(point.y) };
+		self.max = circuit::Point { x: self.max.x.max(point.x), y: self.max.y.max(point.y) };
+	}
+
+	/// A* search for the directions of the steps from `from` to `to`. The used steps of the path are recorded.
+	fn find_path(&mut self, from: circuit::Point, to: circuit::Point) -> Option<Vec<u8>> {
+		// A node is a point and the index of the direction of the step that reached it, or `None` at the start.
+		type Node = (circuit::Point, Option<u8>);
+
+		let min = circuit::Point {
+			x: self.min.x.min(from.x).min(to.x).saturating_sub(MARGIN),
+			y: self.min.y.min(from.y).min(to.y).saturating_sub(MARGIN),
+		};
+		let max = circuit::Point {
+			x: self.max.x.max(from.x).max(to.x).saturating_add(MARGIN),
+			y: self.max.y.max(from.y).max(to.y).saturating_add(MARGIN),
+		};
+
+		let heuristic = |point: circuit::Point| {
+			let dx = (i32::from(to.x) - i32::from(point.x)).unsigned_abs();
+			let dy = (i32::from(to.y) - i32::from(point.y)).unsigned_abs();
+			dx.max(dy) * STEP_COST
+		};
+
+		let mut costs: std::collections::BTreeMap<Node, u32> = Default::default();
+		let mut previous: std::collections::BTreeMap<Node, Node> = Default::default();
+		let mut queue = std::collections::BinaryHeap::new();
+
+		let start: Node = (from, None);
+		costs.insert(start, 0);
+		queue.push(std::cmp::Reverse((heuristic(from), 0, start)));
+
+		while let Some(std::cmp::Reverse((_, cost, node @ (point, direction)))) = queue.pop() {
+			if costs.get(&node).is_some_and(|&best| best < cost) {
+				continue;
+			}
+
+			if point == to {
+				let mut path = vec![];
+				let mut node = node;
+				while let (point, Some(direction)) = node {
+					path.push(direction);
+					let prev = previous[&node];
+					self.used.insert((prev.0.min(point), prev.0.max(point)));
+					node = prev;
+				}
+				path.reverse();
+				return Some(path);
+			}
+
+			for (next_direction, &d) in (0..).zip(&DIRECTIONS) {
+				let (dx, dy) = d.delta();
+				let (Some(x), Some(y)) = (point.x.checked_add(dx), point.y.checked_add(dy)) else { continue; };
+				let next = circuit::Point { x, y };
+				if next.x < min.x || next.x > max.x || next.y < min.y || next.y > max.y {
+					continue;
+				}
+				if next != to && self.blocked.contains(&next) {
+					continue;
+				}
+				if self.used.contains(&(point.min(next), point.max(next))) {
+					continue;
+				}
+
+				let turn = u32::from(direction.is_some_and(|direction| direction != next_direction));
+				let next_cost = cost + STEP_COST + turn;
+				let next_node = (next, Some(next_direction));
+				if costs.get(&next_node).is_none_or(|&best| next_cost < best) {
+					costs.insert(next_node, next_cost);
+					previous.insert(next_node, node);
+					queue.push(std::cmp::Reverse((next_cost + heuristic(next), next_cost, next_node)));
+				}
+			}
+		}
+
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		builder::{CircuitBuilder, at},
+		circuit,
+		layout,
+		v10,
+	};
+	use super::Router;
+
+	/// The points that the wire passes through, from its start to its end.
+	fn points(wire: &v10::Wire<'_>) -> Vec<circuit::Point> {
+		let circuit::WireSegments::Segments(segments) = circuit::WireSegments::from(&wire.segments) else { panic!("expected segments") };
+		let mut points = vec![circuit::Point::from(wire.start)];
+		for segment in segments {
+			let (dx, dy) = segment.direction.delta();
+			for _ in 0..segment.length {
+				let point = points[points.len() - 1];
+				points.push(circuit::Point { x: point.x + dx, y: point.y + dy });
+			}
+		}
+		points
+	}
+
+	fn steps(points: &[circuit::Point]) -> std::collections::BTreeSet<(circuit::Point, circuit::Point)> {
+		points.windows(2).map(|step| (step[0].min(step[1]), step[0].max(step[1]))).collect()
+	}
+
+	#[test]
+	fn straight() {
+		let circuit = CircuitBuilder::new().build();
+		let wire = Router::new(&circuit).route(at(0, 0), at(5, 0));
+		let v10::WireSegments::Segments(segments) = &wire.segments else { panic!("expected segments") };
+		assert_eq!(segments.len(), 2);
+		assert_eq!(points(&wire), (0..=5).map(|x| circuit::Point { x, y: 0 }).collect::<Vec<_>>());
+	}
+
+	#[test]
+	fn avoids_components() {
+		let mut builder = CircuitBuilder::new();
+		let from = builder.add(v10::ComponentKind::LevelInput1, at(0, 0));
+		let to = builder.add(v10::ComponentKind::LevelOutput1, at(20, 0));
+		builder.add(v10::ComponentKind::Add, at(10, -1));
+		builder.add(v10::ComponentKind::Add, at(10, 2));
+		let circuit = builder.build();
+
+		let blocked: std::collections::BTreeSet<_> =
+			circuit.components.iter()
+			.flat_map(|component| {
+				let component = component.into_inner();
+				layout::footprint(component.kind.into(), component.rotation, component.position.into())
+			})
+			.collect();
+		assert!(blocked.contains(&circuit::Point { x: 10, y: from.out(0).y }), "the straight path must be blocked");
+
+		let wire = Router::new(&circuit).route(from.out(0), to.input(0));
+		let points = points(&wire);
+		assert_eq!(points[0], from.out(0).into());
+		assert_eq!(points[points.len() - 1], to.input(0).into());
+		for point in &points[1..(points.len() - 1)] {
+			assert!(!blocked.contains(point), "{point:?} is covered by a component");
+		}
+	}
+
+	#[test]
+	fn avoids_wires() {
+		let mut builder = CircuitBuilder::new();
+		builder.wire(at(0, 0), at(10, 0));
+		let circuit = builder.build();
+		let existing = steps(&points(&circuit.wires.get(0).unwrap().into_inner()));
+
+		let mut router = Router::new(&circuit);
+		let first = points(&router.route(at(0, 0), at(10, 0)));
+		let second = points(&router.route(at(0, 0), at(10, 0)));
+		for wire in [&first, &second] {
+			assert_eq!(wire[0], circuit::Point { x: 0, y: 0 });
+			assert_eq!(wire[wire.len() - 1], circuit::Point { x: 10, y: 0 });
+		}
+		assert!(steps(&first).is_disjoint(&existing));
+		assert!(steps(&second).is_disjoint(&existing));
+		assert!(steps(&first).is_disjoint(&steps(&second)));
+	}
+
+	#[test]
+	fn unreachable() {
+		let mut builder = CircuitBuilder::new();
+		for (dx, dy) in [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)] {
+			builder.wire(at(10, 10), at(10 + dx, 10 + dy));
+		}
+		let wire = Router::new(&builder.build()).route(at(0, 0), at(10, 10));
+		assert!(matches!(wire.segments, v10::WireSegments::TeleWireEnd(end) if end == at(10, 10)));
+	}
+}
@@ -0,0 +1,347 @@
+//! Loading of the game's `schematics/` directory.
+//!
+//! Every `circuit.data` under the directory is loaded. The ones with a non-zero `custom_id` are the definitions of custom components,
+//! and are indexed by it so that `Custom` components and `CircuitData.dependencies` can be resolved to them.
+//!
+//! A file that cannot be read or parsed does not stop the others from loading. Its error is kept in [`Schematics::errors`].
+
+use crate::{
+	CircuitData,
+	Parse,
+	ParseError,
+	circuit,
+};
+
+#[derive(Debug, Default)]
+pub struct Schematics {
+	/// All the circuits that were found, in ascending order of their paths.
+	pub circuits: Vec<Schematic>,
+
+	/// The files that could not be loaded, in ascending order of their paths. They are not in `circuits`.
+	///
+	/// A circuit that defines the same custom component as an earlier one is also left out, with a [`LoadError::DuplicateCustomId`].
+	pub errors: Vec<LoadError>,
+
+	by_custom_id: std::collections::BTreeMap<i64, usize>,
+}
+
+#[derive(Debug)]
+pub struct Schematic {
+	pub path: std::path::PathBuf,
+	pub custom_id: i64,
+	pub data: CircuitData,
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+	Io { path: std::path::PathBuf, error: std::io::Error },
+
+	Parse { path: std::path::PathBuf, error: ParseError },
+
+	/// Two circuits define a custom component with the same `custom_id`.
+	DuplicateCustomId { custom_id: i64, first: std::path::PathBuf, second: std::path::PathBuf },
+}
+
+impl std::fmt::Display for LoadError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Io { path, error: _ } => write!(f, "could not read {}", path.display()),
+			Self::Parse { path, error } => write!(f, "could not parse {}: {error}", path.display()),
+			Self::DuplicateCustomId { custom_id, first, second } =>
+				write!(f, "custom component {custom_id} is defined by both {} and {}", first.display(), second.display()),
+		}
+	}
+}
+
+impl std::error::Error for LoadError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Io { path: _, error } => Some(error),
+			Self::Parse { path: _, error } => Some(error),
+			Self::DuplicateCustomId { .. } => None,
+		}
+	}
+}
+
+#[derive(Debug)]
+pub enum ResolveError {
+	/// No circuit defines the custom component with the given `custom_id`.
+	///
+	/// `needed_by` is the chain of custom components that led to it, starting from the circuit being resolved.
+	Missing { custom_id: i64, needed_by: Vec<i64> },
+
+	/// The custom components depend on each other in a cycle. The first and last elements are the same component.
+	Cycle(Vec<i64>),
+
+	/// The circuit that defines the custom component no longer parses, because its [`Schematic::data`] was changed after it was loaded.
+	Parse { custom_id: i64, error: ParseError },
+}
+
+impl std::fmt::Display for ResolveError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Missing { custom_id, needed_by } => {
+				write!(f, "custom component {custom_id} is not defined")?;
+				if let Some(needed_by) = needed_by.last() {
+					write!(f, " but is needed by custom component {needed_by}")?;
+				}
+				Ok(())
+			},
+
+			Self::Cycle(cycle) => {
+				f.write_str("custom components depend on each other in a cycle: ")?;
+				for (i, custom_id) in cycle.iter().enumerate() {
+					if i > 0 {
+						f.write_str(" -> ")?;
+					}
+					write!(f, "{custom_id}")?;
+				}
+				Ok(())
+			},
+
+			Self::Parse { custom_id, error } => write!(f, "could not parse custom component {custom_id}: {error}"),
+		}
+	}
+}
+
+impl std::error::Error for ResolveError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Missing { .. } |
+			Self::Cycle(_) => None,
+			Self::Parse { custom_id: _, error } => Some(error),
+		}
+	}
+}
+
+impl Schematics {
+	/// Loads every `circuit.data` under the given directory.
+	///
+	/// Only fails if the directory cannot be searched. Files that cannot be loaded are recorded in [`Schematics::errors`].
+	pub fn load(dir: &std::path::Path) -> Result<Self, LoadError> {
+		let paths = find_circuits(dir)?;
+
+		let mut result = Self::default();
+		for path in paths {
+			let input = match std::fs::read(&path) {
+				Ok(input) => input,
+				Err(error) => {
+					result.errors.push(LoadError::Io { path, error });
+					continue;
+				},
+			};
+			let parsed = CircuitData::parse_from(&input).and_then(|data| {
+				let custom_id = data.parse_circuit()?.custom_id;
+				Ok((data, custom_id))
+			});
+			let (data, custom_id) = match parsed {
+				Ok(parsed) => parsed,
+				Err(error) => {
+					result.errors.push(LoadError::Parse { path, error });
+					continue;
+				},
+			};
+
+			if custom_id != 0 {
+				if let Some(&first) = result.by_custom_id.get(&custom_id) {
+					let first = result.circuits[first].path.clone();
+					result.errors.push(LoadError::DuplicateCustomId { custom_id, first, second: path });
+					continue;
+				}
+				result.by_custom_id.insert(custom_id, result.circuits.len());
+			}
+
+			result.circuits.push(Schematic { path, custom_id, data });
+		}
+
+		Ok(result)
+	}
+
+	/// The circuit that defines the custom component with the given `custom_id`.
+	pub fn get(&self, custom_id: i64) -> Option<&Schematic> {
+		let &i = self.by_custom_id.get(&custom_id)?;
+		Some(&self.circuits[i])
+	}
+
+	/// Finds the definitions of all the custom components that the circuit needs, directly or through other custom components.
+	///
+	/// The `custom_id`s are returned in dependency order, so every custom component comes after the ones it needs.
+	pub fn resolve(&self, circuit: &circuit::Circuit<'_>) -> Result<Vec<i64>, ResolveError> {
+		let mut stack = vec![];
+		if circuit.custom_id != 0 {
+			stack.push(circuit.custom_id);
+		}
+		let mut resolved = vec![];
+		let mut done = Default::default();
+
+		for custom_id in needed_custom_ids(circuit) {
+			self.resolve_inner(custom_id, &mut stack, &mut done, &mut resolved)?;
+		}
+
+		Ok(resolved)
+	}
+
+	fn resolve_inner(
+		&self,
+		custom_id: i64,
+		stack: &mut Vec<i64>,
+		done: &mut std::collections::BTreeSet<i64>,
+		resolved: &mut Vec<i64>,
+	) -> Result<(), ResolveError> {
+		if done.contains(&custom_id) {
+			return Ok(());
+		}
+
+		if let Some(start) = stack.iter().position(|&id| id == custom_id) {
+			let mut cycle = stack[start..].to_vec();
+			cycle.push(custom_id);
+			return Err(ResolveError::Cycle(cycle));
+		}
+
+		let Some(schematic) = self.get(custom_id) else {
+			return Err(ResolveError::Missing { custom_id, needed_by: stack.clone() });
+		};
+		let circuit = schematic.data.parse_circuit().map_err(|error| ResolveError::Parse { custom_id, error })?;
+
+		stack.push(custom_id);
+		for dependency in needed_custom_ids(&circuit) {
+			self.resolve_inner(dependency, stack, done, resolved)?;
+		}
+		stack.pop();
+
+		done.insert(custom_id);
+		resolved.push(custom_id);
+		Ok(())
+	}
+}
+
+/// The `custom_id`s of the circuit's dependencies and custom components, in ascending order.
+fn needed_custom_ids(circuit: &circuit::Circuit<'_>) -> std::collections::BTreeSet<i64> {
+	circuit.dependencies.iter().copied()
+		.chain(circuit.components.iter().filter(|component| component.kind.is_custom()).filter_map(|component| component.custom_id))
+		.collect()
+}
+
+/// Finds every `circuit.data` under the given directory, in ascending order of their paths.
+pub fn find_circuits(dir: &std::path::Path) -> Result<Vec<std::path::PathBuf>, LoadError> {
+	let mut paths = vec![];
+	find_circuits_inner(dir, &mut paths)?;
+	paths.sort();
+	Ok(paths)
+}
+
+fn find_circuits_inner(dir: &std::path::Path, paths: &mut Vec<std::path::PathBuf>) -> Result<(), LoadError> {
+	let io_error = |error| LoadError::Io { path: dir.to_owned(), error };
+
+	for entry in std::fs::read_dir(dir).map_err(io_error)? {
+		let entry = entry.map_err(io_error)?;
+		let file_type = entry.file_type().map_err(io_error)?;
+		let path = entry.path();
+		if file_type.is_dir() {
+			find_circuits_inner(&path, paths)?;
+		}
+		else if file_type.is_file() && entry.file_name() == "circuit.data" {
+			paths.push(path);
+		}
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		Save,
+		builder::{CircuitBuilder, at},
+	};
+	use super::{LoadError, ResolveError, Schematics};
+
+	/// An empty directory under the system's temporary directory, unique to the test and the process.
+	fn temp_dir(name: &str) -> std::path::PathBuf {
+		let dir = std::env::temp_dir().join(format!("turing-complete-saves-parser-{}-{name}", std::process::id()));
+		_ = std::fs::remove_dir_all(&dir);
+		std::fs::create_dir_all(&dir).unwrap();
+		dir
+	}
+
+	/// Writes `dir/name/circuit.data`.
+	fn write(dir: &std::path::Path, name: &str, contents: &[u8]) {
+		std::fs::create_dir_all(dir.join(name)).unwrap();
+		std::fs::write(dir.join(name).join("circuit.data"), contents).unwrap();
+	}
+
+	/// A save of a circuit with the given `custom_id`, that uses the custom components with the given `custom_id`s.
+	fn circuit(custom_id: i64, uses: &[i64]) -> Vec<u8> {
+		let mut builder = CircuitBuilder::new();
+		for (y, &used) in (0..).zip(uses) {
+			builder.add_custom(used, at(0, 4 * y), 0);
+		}
+		let mut circuit = builder.build();
+		circuit.custom_id = custom_id;
+		Save::V10(circuit).encode_final()
+	}
+
+	/// Loads the directory and resolves the circuit in it called `top`.
+	fn resolve(dir: &std::path::Path) -> Result<Vec<i64>, ResolveError> {
+		let schematics = Schematics::load(dir).unwrap();
+		assert!(schematics.errors.is_empty(), "{:?}", schematics.errors);
+		let top = schematics.circuits.iter().find(|schematic| schematic.path.ends_with("top/circuit.data")).unwrap();
+		schematics.resolve(&top.data.parse_circuit().unwrap())
+	}
+
+	#[test]
+	fn errors_do_not_stop_loading() {
+		let dir = temp_dir("errors");
+		write(&dir, "a", &circuit(1, &[]));
+		write(&dir, "b", &circuit(1, &[]));
+		write(&dir, "c", b"not a save");
+		write(&dir, "d", &circuit(0, &[1]));
+
+		let schematics = Schematics::load(&dir).unwrap();
+		let loaded: Vec<_> = schematics.circuits.iter().map(|schematic| schematic.path.strip_prefix(&dir).unwrap()).collect();
+		assert_eq!(loaded, ["a/circuit.data", "d/circuit.data"].map(std::path::Path::new));
+		assert!(schematics.get(1).unwrap().path.ends_with("a/circuit.data"));
+		assert!(matches!(
+			&schematics.errors[..],
+			[
+				LoadError::DuplicateCustomId { custom_id: 1, first, second },
+				LoadError::Parse { path, error: _ },
+			] if first.ends_with("a/circuit.data") && second.ends_with("b/circuit.data") && path.ends_with("c/circuit.data"),
+		), "{:?}", schematics.errors);
+
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+
+	#[test]
+	fn dependency_order() {
+		let dir = temp_dir("order");
+		write(&dir, "top", &circuit(0, &[1, 2]));
+		write(&dir, "one", &circuit(1, &[2]));
+		write(&dir, "two", &circuit(2, &[]));
+		assert_eq!(resolve(&dir).unwrap(), [2, 1]);
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+
+	#[test]
+	fn missing_dependency() {
+		let dir = temp_dir("missing");
+		write(&dir, "top", &circuit(0, &[1]));
+		write(&dir, "one", &circuit(1, &[2]));
+		let error = resolve(&dir).unwrap_err();
+		assert!(matches!(&error, ResolveError::Missing { custom_id: 2, needed_by } if needed_by == &[1]), "{error:?}");
+		assert_eq!(error.to_string(), "custom component 2 is not defined but is needed by custom component 1");
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+
+	#[test]
+	fn cycle() {
+		let dir = temp_dir("cycle");
+		write(&dir, "top", &circuit(0, &[1]));
+		write(&dir, "one", &circuit(1, &[2]));
+		write(&dir, "two", &circuit(2, &[1]));
+		let error = resolve(&dir).unwrap_err();
+		assert!(matches!(&error, ResolveError::Cycle(cycle) if cycle == &[1, 2, 1]), "{error:?}");
+		assert_eq!(error.to_string(), "custom components depend on each other in a cycle: 1 -> 2 -> 1");
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+}
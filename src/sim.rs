@@ -0,0 +1,576 @@
+//! A gate-level simulator for v10 circuits.
+//!
+//! The circuit is resolved into nets and pins by [`Netlist`]. A net that is driven by more than one output has the OR of their values,
+//! and a net that is not driven at all is 0.
+//!
+//! Each [`Simulation::tick`] first settles the combinational logic, then updates the state of sequential components
+//! such as registers, counters and delay lines.
+//!
+//! Pins are where [`crate::layout`] puts them, which is not where the game does, so this can only run circuits made with
+//! [`crate::builder`]. For a circuit made in the game, pins are mostly not on its wires, so the values of its outputs mean nothing.
+//!
+//! Settling evaluates every component once per pass, and takes up to one more pass than there are components,
+//! so a tick takes time quadratic in the number of components for deep circuits.
+
+use crate::{
+	netlist::{
+		Netlist,
+		is_level_input,
+		is_level_output,
+	},
+	v10,
+	verilog::ram_address_bits,
+};
+
+#[derive(Debug)]
+pub struct Simulation {
+	components: Vec<SimComponent>,
+	nets: Vec<u64>,
+	by_permanent_id: std::collections::BTreeMap<u64, usize>,
+	inputs: Vec<Port>,
+	outputs: Vec<Port>,
+	ticks: u64,
+}
+
+/// A `LevelInput*` or `LevelOutput*` component of the circuit.
+#[derive(Clone, Copy, Debug)]
+pub struct Port {
+	pub permanent_id: u64,
+	pub kind: v10::ComponentKind,
+	pub ui_order: i16,
+
+	/// The width of the port's value in bits. Ports with more than one pin pack them into the value, with pin `i` starting at bit `i`.
+	pub width: u32,
+}
+
+#[derive(Debug)]
+pub enum SimError {
+	/// The simulator does not know how to evaluate the component's kind.
+	UnsupportedComponent { permanent_id: u64, kind: v10::ComponentKind },
+
+	/// The component is not a `LevelInput*` component.
+	NotAnInput(u64),
+
+	/// The circuit's combinational logic did not reach a stable state in the given tick, because it contains a loop.
+	Unstable { tick: u64 },
+}
+
+impl std::fmt::Display for SimError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::UnsupportedComponent { permanent_id, kind } => write!(f, "component {permanent_id} of kind {kind:?} cannot be simulated"),
+			Self::NotAnInput(permanent_id) => write!(f, "component {permanent_id} is not a level input"),
+			Self::Unstable { tick } => write!(f, "circuit did not settle in tick {tick}"),
+		}
+	}
+}
+
+impl std::error::Error for SimError {}
+
+impl Simulation {
+	pub fn new(circuit: &v10::CircuitData<'_>) -> Result<Self, SimError> {
+		let netlist = Netlist::new(circuit);
+
+		let components: Vec<_> =
+			netlist.components.iter().zip(&circuit.components)
+			.map(|(component, original)| {
+				if !component.known_pins {
+					return Err(SimError::UnsupportedComponent { permanent_id: component.permanent_id, kind: component.kind });
+				}
+
+				let pins = |pins: &[crate::netlist::Pin]| pins.iter().map(|pin| Pin { net: pin.net.0, mask: mask(pin.width) }).collect();
+				Ok(SimComponent {
+					kind: component.kind,
+					permanent_id: component.permanent_id,
+					word_bits: component.word_size,
+					constant: original.as_inner_ref().settings.iter().next().map_or(0, crate::Either::into_inner),
+					inputs: pins(&component.inputs),
+					outputs: pins(&component.outputs),
+					state: 0,
+					memory: Default::default(),
+				})
+			})
+			.collect::<Result<_, _>>()?;
+
+		let by_permanent_id = components.iter().enumerate().map(|(i, component)| (component.permanent_id, i)).collect();
+
+		let mut inputs = vec![];
+		let mut outputs = vec![];
+		for (component, original) in components.iter().zip(&circuit.components) {
+			let (ports, pins) =
+				if is_level_input(component.kind) { (&mut inputs, &component.outputs) }
+				else if is_level_output(component.kind) { (&mut outputs, &component.inputs) }
+				else { continue; };
+			ports.push(Port {
+				permanent_id: component.permanent_id,
+				kind: component.kind,
+				ui_order: original.as_inner_ref().ui_order,
+				width: pins.iter().map(|pin| pin.mask.count_ones()).sum(),
+			});
+		}
+		inputs.sort_by_key(|port| (port.ui_order, port.permanent_id));
+		outputs.sort_by_key(|port| (port.ui_order, port.permanent_id));
+
+		Ok(Self {
+			components,
+			nets: vec![0; netlist.nets.len()],
+			by_permanent_id,
+			inputs,
+			outputs,
+			ticks: 0,
+		})
+	}
+
+	/// The circuit's `LevelInput*` components, in UI order.
+	pub fn inputs(&self) -> &[Port] {
+		&self.inputs
+	}
+
+	/// The circuit's `LevelOutput*` components, in UI order.
+	pub fn outputs(&self) -> &[Port] {
+		&self.outputs
+	}
+
+	/// The number of ticks that have been simulated so far.
+	pub fn ticks(&self) -> u64 {
+		self.ticks
+	}
+
+	/// Sets the value that the given `LevelInput*` component drives from the next tick onwards.
+	pub fn set_input(&mut self, permanent_id: u64, value: u64) -> Result<(), SimError> {
+		let component =
+			self.by_permanent_id.get(&permanent_id)
+			.map(|&i| &mut self.components[i])
+			.filter(|component| is_level_input(component.kind))
+			.ok_or(SimError::NotAnInput(permanent_id))?;
+		component.state = value & mask(component.outputs.iter().map(|pin| pin.mask.count_ones()).sum());
+		Ok(())
+	}
+
+	/// The value that the given `LevelOutput*` component received in the last tick.
+	///
+	/// Returns `None` if there is no such component.
+	pub fn output(&self, permanent_id: u64) -> Option<u64> {
+		let &i = self.by_permanent_id.get(&permanent_id)?;
+		let component = &self.components[i];
+		is_level_output(component.kind).then_some(component.state)
+	}
+
+	/// Simulates one tick. This takes O(n²) time in the number of components `n` in the worst case, see the module docs.
+	pub fn tick(&mut self) -> Result<(), SimError> {
+		self.settle()?;
+
+		let mut inputs = vec![];
+		for component in &mut self.components {
+			inputs.clear();
+			inputs.extend(component.inputs.iter().map(|pin| self.nets[pin.net] & pin.mask));
+			component.latch(&inputs);
+		}
+
+		self.ticks += 1;
+		Ok(())
+	}
+
+	fn settle(&mut self) -> Result<(), SimError> {
+		let mut inputs = vec![];
+
+		// Without loops, every component's outputs are final after as many passes as the longest path through the circuit,
+		// so one more pass than there are components is enough to tell that it has settled.
+		for _ in 0..=self.components.len() {
+			let mut next = vec![0; self.nets.len()];
+			for component in &self.components {
+				inputs.clear();
+				inputs.extend(component.inputs.iter().map(|pin| self.nets[pin.net] & pin.mask));
+				let values = component.evaluate(&inputs);
+				for (pin, value) in component.outputs.iter().zip(values) {
+					if let Some(value) = value {
+						next[pin.net] |= value & pin.mask;
+					}
+				}
+			}
+
+			if next == self.nets {
+				return Ok(());
+			}
+			self.nets = next;
+		}
+
+		Err(SimError::Unstable { tick: self.ticks })
+	}
+}
+
+#[derive(Debug)]
+struct SimComponent {
+	kind: v10::ComponentKind,
+	permanent_id: u64,
+	word_bits: u32,
+	constant: u64,
+	inputs: Vec<Pin>,
+	outputs: Vec<Pin>,
+	state: u64,
+
+	/// The contents of a RAM component, by address. Addresses that have not been written to hold 0.
+	memory: std::collections::BTreeMap<u64, u64>,
+}
+
+impl SimComponent {
+	/// Computes the values of the component's outputs from the values of its inputs and its current state.
+	///
+	/// Outputs that are `None` do not drive their net.
+	#[allow(clippy::too_many_lines)]
+	fn evaluate(&self, i: &[u64]) -> [Option<u64>; 8] {
+		let bits = self.word_bits;
+		let mut o = [None; 8];
+
+		match self.kind {
+			v10::ComponentKind::Off => o[0] = Some(0),
+			v10::ComponentKind::On => o[0] = Some(1),
+
+			v10::ComponentKind::NotBit |
+			v10::ComponentKind::NotWord => o[0] = Some(!i[0]),
+			v10::ComponentKind::AndBit |
+			v10::ComponentKind::AndWord => o[0] = Some(i[0] & i[1]),
+			v10::ComponentKind::And3Bit => o[0] = Some(i[0] & i[1] & i[2]),
+			v10::ComponentKind::NandBit |
+			v10::ComponentKind::NandWord => o[0] = Some(!(i[0] & i[1])),
+			v10::ComponentKind::OrBit |
+			v10::ComponentKind::OrWord => o[0] = Some(i[0] | i[1]),
+			v10::ComponentKind::Or3Bit => o[0] = Some(i[0] | i[1] | i[2]),
+			v10::ComponentKind::NorBit |
+			v10::ComponentKind::NorWord => o[0] = Some(!(i[0] | i[1])),
+			v10::ComponentKind::XorBit |
+			v10::ComponentKind::XorWord => o[0] = Some(i[0] ^ i[1]),
+			v10::ComponentKind::XnorBit |
+			v10::ComponentKind::XnorWord => o[0] = Some(!(i[0] ^ i[1])),
+
+			v10::ComponentKind::SwitchBit |
+			v10::ComponentKind::SwitchWord => o[0] = (i[0] != 0).then_some(i[1]),
+
+			v10::ComponentKind::DelayLineBit |
+			v10::ComponentKind::DelayLineWord |
+			v10::ComponentKind::Counter |
+			v10::ComponentKind::LevelInput1 |
+			v10::ComponentKind::LevelInputWord => o[0] = Some(self.state),
+
+			v10::ComponentKind::RegisterBit |
+			v10::ComponentKind::RegisterWord => o[0] = (i[0] != 0).then_some(self.state),
+
+			v10::ComponentKind::FullAdder => {
+				let sum = i[0] + i[1] + i[2];
+				o[0] = Some(sum & 1);
+				o[1] = Some(sum >> 1);
+			},
+
+			v10::ComponentKind::MakerBit2 |
+			v10::ComponentKind::MakerBit4 |
+			v10::ComponentKind::MakerBit8 => o[0] = Some(pack(i)),
+
+			v10::ComponentKind::SplitterBit2 |
+			v10::ComponentKind::SplitterBit4 |
+			v10::ComponentKind::SplitterBit8 => unpack(i[0], &mut o[..self.outputs.len()]),
+
+			v10::ComponentKind::Equal => o[0] = Some(u64::from(i[0] == i[1])),
+			v10::ComponentKind::LessU => o[0] = Some(u64::from(i[0] < i[1])),
+			v10::ComponentKind::LessS => o[0] = Some(u64::from(sign_extend(i[0], bits) < sign_extend(i[1], bits))),
+
+			v10::ComponentKind::Neg => o[0] = Some(i[0].wrapping_neg()),
+			v10::ComponentKind::Inc => o[0] = Some(i[0].wrapping_add(1)),
+
+			v10::ComponentKind::Add => {
+				let sum = u128::from(i[0]) + u128::from(i[1]) + u128::from(i[2]);
+				#[allow(clippy::cast_possible_truncation)]
+				{
+					o[0] = Some(sum as u64);
+					o[1] = Some((sum >> bits) as u64);
+				}
+			},
+
+			v10::ComponentKind::Mul => {
+				let product = u128::from(i[0]) * u128::from(i[1]);
+				#[allow(clippy::cast_possible_truncation)]
+				{
+					o[0] = Some(product as u64);
+					o[1] = Some((product >> bits) as u64);
+				}
+			},
+
+			v10::ComponentKind::Div => {
+				o[0] = Some(i[0].checked_div(i[1]).unwrap_or(0));
+				o[1] = Some(i[0].checked_rem(i[1]).unwrap_or(0));
+			},
+
+			v10::ComponentKind::Mod => o[0] = Some(i[0].checked_rem(i[1]).unwrap_or(0)),
+
+			v10::ComponentKind::Lsl => o[0] = Some(if i[1] < u64::from(bits) { i[0] << i[1] } else { 0 }),
+			v10::ComponentKind::Lsr => o[0] = Some(if i[1] < u64::from(bits) { i[0] >> i[1] } else { 0 }),
+			#[allow(clippy::cast_sign_loss)]
+			v10::ComponentKind::Asr => o[0] = Some((sign_extend(i[0], bits) >> i[1].min(63)) as u64),
+			v10::ComponentKind::Rol => o[0] = Some(rotate_left(i[0], i[1], bits)),
+			v10::ComponentKind::Ror => o[0] = Some(rotate_left(i[0], u64::from(bits) - i[1] % u64::from(bits), bits)),
+
+			v10::ComponentKind::Clz => o[0] = Some(u64::from(i[0].leading_zeros() - (64 - bits))),
+			v10::ComponentKind::Ctz => o[0] = Some(u64::from(i[0].trailing_zeros().min(bits))),
+
+			v10::ComponentKind::Mux => o[0] = Some(if i[0] == 0 { i[1] } else { i[2] }),
+
+			v10::ComponentKind::RamFast => o[0] = (i[0] != 0).then(|| self.memory.get(&(i[2] & mask(ram_address_bits(self.word_bits)))).copied().unwrap_or(0)),
+
+			v10::ComponentKind::Decoder1 |
+			v10::ComponentKind::Decoder2 |
+			v10::ComponentKind::Decoder3 => {
+				let selected = pack(i);
+				for (n, o) in (0..).zip(&mut o[..self.outputs.len()]) {
+					*o = Some(u64::from(n == selected));
+				}
+			},
+
+			v10::ComponentKind::Constant => o[0] = Some(self.constant),
+
+			v10::ComponentKind::LevelInput2Pin |
+			v10::ComponentKind::LevelInput3Pin |
+			v10::ComponentKind::LevelInput4Pin => unpack(self.state, &mut o[..self.outputs.len()]),
+
+			_ => (),
+		}
+
+		o
+	}
+
+	/// Updates the component's state at the end of a tick, from the settled values of its inputs.
+	fn latch(&mut self, i: &[u64]) {
+		match self.kind {
+			v10::ComponentKind::RegisterBit |
+			v10::ComponentKind::RegisterWord if i[1] != 0 => self.state = i[2],
+
+			v10::ComponentKind::RamFast if i[1] != 0 => _ = self.memory.insert(i[2] & mask(ram_address_bits(self.word_bits)), i[3]),
+
+			v10::ComponentKind::Counter => self.state = if i[0] == 0 { self.state.wrapping_add(1) & mask(self.word_bits) } else { i[1] },
+
+			v10::ComponentKind::DelayLineBit |
+			v10::ComponentKind::DelayLineWord |
+			v10::ComponentKind::LevelOutput1 |
+			v10::ComponentKind::LevelOutputWord => self.state = i[0],
+
+			v10::ComponentKind::LevelOutput2Pin |
+			v10::ComponentKind::LevelOutput3Pin |
+			v10::ComponentKind::LevelOutput4Pin => self.state = pack(i),
+
+			_ => (),
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Pin {
+	net: usize,
+	mask: u64,
+}
+
+fn mask(bits: u32) -> u64 {
+	u64::MAX >> (64 - bits.clamp(1, 64))
+}
+
+fn pack(bits: &[u64]) -> u64 {
+	(0..).zip(bits).fold(0, |value, (i, bit)| value | (bit << i))
+}
+
+fn unpack(value: u64, bits: &mut [Option<u64>]) {
+	for (i, bit) in (0..).zip(bits) {
+		*bit = Some((value >> i) & 1);
+	}
+}
+
+#[allow(clippy::cast_possible_wrap)]
+fn sign_extend(value: u64, bits: u32) -> i64 {
+	let shift = 64 - bits;
+	((value << shift) as i64) >> shift
+}
+
+fn rotate_left(value: u64, by: u64, bits: u32) -> u64 {
+	let by = by % u64::from(bits);
+	if by == 0 {
+		value
+	}
+	else {
+		(value << by) | (value >> (u64::from(bits) - by))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		builder::{CircuitBuilder, ComponentHandle, at},
+		v10,
+	};
+	use super::Simulation;
+
+	fn input(builder: &mut CircuitBuilder, kind: v10::ComponentKind, y: i16, to: v10::Point) -> ComponentHandle {
+		let input = builder.add(kind, at(0, y));
+		builder.wire(input.out(0), to);
+		input
+	}
+
+	fn output(builder: &mut CircuitBuilder, kind: v10::ComponentKind, from: v10::Point) -> ComponentHandle {
+		let output = builder.add(kind, at(20, 0));
+		builder.wire(from, output.input(0));
+		output
+	}
+
+	/// Sets the inputs, simulates one tick, and returns the output's value in that tick.
+	fn tick(sim: &mut Simulation, inputs: &[(ComponentHandle, u64)], output: ComponentHandle) -> u64 {
+		for &(input, value) in inputs {
+			sim.set_input(input.permanent_id, value).unwrap();
+		}
+		sim.tick().unwrap();
+		sim.output(output.permanent_id).unwrap()
+	}
+
+	/// Checks each row of a truth table, given as the values of the inputs and the values of the outputs,
+	/// against a component of the given kind wired to inputs and outputs of the given kinds.
+	fn truth_table(kind: v10::ComponentKind, inputs: &[v10::ComponentKind], outputs: &[v10::ComponentKind], rows: &[(&[u64], &[u64])]) {
+		let mut builder = CircuitBuilder::new();
+		let component = builder.add(kind, at(10, 0));
+		let inputs: Vec<_> = (0..).zip(inputs).map(|(i, &input_kind)| input(&mut builder, input_kind, 2 * i, component.input(i))).collect();
+		let outputs: Vec<_> =
+			(0..).zip(outputs)
+			.map(|(i, &output_kind)| {
+				let output = builder.add(output_kind, at(20, 2 * i));
+				builder.wire(component.out(i), output.input(0));
+				output
+			})
+			.collect();
+		let mut sim = Simulation::new(&builder.build()).unwrap();
+
+		for &(values, expected) in rows {
+			for (input, &value) in inputs.iter().zip(values) {
+				sim.set_input(input.permanent_id, value).unwrap();
+			}
+			sim.tick().unwrap();
+			let actual: Vec<_> = outputs.iter().map(|output| sim.output(output.permanent_id).unwrap()).collect();
+			assert_eq!(actual, expected, "{kind:?} of {values:?}");
+		}
+	}
+
+	#[test]
+	fn not_bit() {
+		truth_table(
+			v10::ComponentKind::NotBit,
+			&[v10::ComponentKind::LevelInput1],
+			&[v10::ComponentKind::LevelOutput1],
+			&[(&[0], &[1]), (&[1], &[0])],
+		);
+	}
+
+	#[test]
+	fn and_bit() {
+		truth_table(
+			v10::ComponentKind::AndBit,
+			&[v10::ComponentKind::LevelInput1; 2],
+			&[v10::ComponentKind::LevelOutput1],
+			&[(&[0, 0], &[0]), (&[0, 1], &[0]), (&[1, 0], &[0]), (&[1, 1], &[1])],
+		);
+	}
+
+	#[test]
+	fn xor_word() {
+		truth_table(
+			v10::ComponentKind::XorWord,
+			&[v10::ComponentKind::LevelInputWord; 2],
+			&[v10::ComponentKind::LevelOutputWord],
+			&[(&[0, 0], &[0]), (&[0xf0, 0x3c], &[0xcc]), (&[0xff, 0xff], &[0]), (&[0xff, 0], &[0xff])],
+		);
+	}
+
+	#[test]
+	fn add() {
+		truth_table(
+			v10::ComponentKind::Add,
+			&[v10::ComponentKind::LevelInputWord, v10::ComponentKind::LevelInputWord, v10::ComponentKind::LevelInput1],
+			&[v10::ComponentKind::LevelOutputWord, v10::ComponentKind::LevelOutput1],
+			&[
+				(&[0, 0, 0], &[0, 0]),
+				(&[2, 3, 0], &[5, 0]),
+				(&[2, 3, 1], &[6, 0]),
+				(&[200, 100, 0], &[44, 1]),
+				(&[255, 0, 1], &[0, 1]),
+				(&[255, 255, 1], &[255, 1]),
+			],
+		);
+	}
+
+	#[test]
+	fn mux() {
+		truth_table(
+			v10::ComponentKind::Mux,
+			&[v10::ComponentKind::LevelInput1, v10::ComponentKind::LevelInputWord, v10::ComponentKind::LevelInputWord],
+			&[v10::ComponentKind::LevelOutputWord],
+			&[(&[0, 7, 9], &[7]), (&[1, 7, 9], &[9]), (&[1, 0, 255], &[255]), (&[0, 0, 255], &[0])],
+		);
+	}
+
+	#[test]
+	fn delay_line() {
+		let mut builder = CircuitBuilder::new();
+		let delay = builder.add(v10::ComponentKind::DelayLineBit, at(10, 0));
+		let a = input(&mut builder, v10::ComponentKind::LevelInput1, 0, delay.input(0));
+		let out = output(&mut builder, v10::ComponentKind::LevelOutput1, delay.out(0));
+		let mut sim = Simulation::new(&builder.build()).unwrap();
+
+		assert_eq!(tick(&mut sim, &[(a, 1)], out), 0);
+		assert_eq!(tick(&mut sim, &[(a, 0)], out), 1);
+		assert_eq!(tick(&mut sim, &[], out), 0);
+	}
+
+	#[test]
+	fn register() {
+		let mut builder = CircuitBuilder::new();
+		let register = builder.add(v10::ComponentKind::RegisterWord, at(10, 0));
+		let load = input(&mut builder, v10::ComponentKind::LevelInput1, 0, register.input(0));
+		let save = input(&mut builder, v10::ComponentKind::LevelInput1, 2, register.input(1));
+		let value = input(&mut builder, v10::ComponentKind::LevelInputWord, 4, register.input(2));
+		let out = output(&mut builder, v10::ComponentKind::LevelOutputWord, register.out(0));
+		let mut sim = Simulation::new(&builder.build()).unwrap();
+
+		assert_eq!(tick(&mut sim, &[(load, 0), (save, 1), (value, 42)], out), 0);
+		assert_eq!(tick(&mut sim, &[(load, 1), (save, 0), (value, 7)], out), 42);
+		assert_eq!(tick(&mut sim, &[(save, 1)], out), 42);
+		assert_eq!(tick(&mut sim, &[(save, 0)], out), 7);
+		assert_eq!(tick(&mut sim, &[(load, 0)], out), 0);
+	}
+
+	#[test]
+	fn counter() {
+		let mut builder = CircuitBuilder::new();
+		let counter = builder.add(v10::ComponentKind::Counter, at(10, 0));
+		let overwrite = input(&mut builder, v10::ComponentKind::LevelInput1, 0, counter.input(0));
+		let value = input(&mut builder, v10::ComponentKind::LevelInputWord, 2, counter.input(1));
+		let out = output(&mut builder, v10::ComponentKind::LevelOutputWord, counter.out(0));
+		let mut sim = Simulation::new(&builder.build()).unwrap();
+
+		for expected in 0..3 {
+			assert_eq!(tick(&mut sim, &[], out), expected);
+		}
+		assert_eq!(tick(&mut sim, &[(overwrite, 1), (value, 254)], out), 3);
+		assert_eq!(tick(&mut sim, &[(overwrite, 0)], out), 254);
+		assert_eq!(tick(&mut sim, &[], out), 255);
+		assert_eq!(tick(&mut sim, &[], out), 0);
+	}
+
+	#[test]
+	fn ram() {
+		let mut builder = CircuitBuilder::new();
+		let ram = builder.add(v10::ComponentKind::RamFast, at(10, 0));
+		let load = input(&mut builder, v10::ComponentKind::LevelInput1, 0, ram.input(0));
+		let save = input(&mut builder, v10::ComponentKind::LevelInput1, 2, ram.input(1));
+		let address = input(&mut builder, v10::ComponentKind::LevelInputWord, 4, ram.input(2));
+		let value = input(&mut builder, v10::ComponentKind::LevelInputWord, 6, ram.input(3));
+		let out = output(&mut builder, v10::ComponentKind::LevelOutputWord, ram.out(0));
+		let mut sim = Simulation::new(&builder.build()).unwrap();
+
+		assert_eq!(tick(&mut sim, &[(save, 1), (address, 3), (value, 99)], out), 0);
+		assert_eq!(tick(&mut sim, &[(address, 4), (value, 5)], out), 0);
+		assert_eq!(tick(&mut sim, &[(load, 1), (save, 0), (address, 3)], out), 99);
+		assert_eq!(tick(&mut sim, &[(address, 4)], out), 5);
+		assert_eq!(tick(&mut sim, &[(address, 5)], out), 0);
+	}
+}
@@ -0,0 +1,667 @@
+//! Parsing of saves from a [`std::io::Read`], without reading the whole save into memory first.
+//!
+//! [`Decompressor`] decompresses the snappy-compressed part of a save as it is read. [`stream`] uses it to parse the circuit's fields
+//! up front, and then its components and wires one at a time, so that only the element being parsed needs to be in memory.
+//!
+//! To keep memory bounded, copies in the compressed data may only reach back 64 KiB, which is as far as the `snap` encoder's copies go.
+//! [`decompress_all`], which [`crate::from_reader`] uses, keeps all of the decompressed data and accepts copies from any distance.
+
+use crate::{
+	Input,
+	IntoOwned,
+	LoadError,
+	Parse,
+	ParseError,
+	ParseErrorKind,
+	parser::ParseResultExt,
+	v6, v7, v8, v9, v10,
+};
+
+/// How far back a copy can reach when reading from a [`Decompressor`].
+///
+/// The `snap` encoder compresses its input in blocks of this size, so none of its copies reach further,
+/// but the raw format allows copies from anywhere before them. [`decompress_all`] accepts those too.
+const WINDOW: usize = 1 << 16;
+
+/// How many bytes [`CircuitStream`] reads at a time, at least.
+const CHUNK: usize = 1 << 13;
+
+/// A reader of the decompressed contents of a raw snappy stream, as used by saves after their version byte.
+#[derive(Debug)]
+pub struct Decompressor<R> {
+	inner: R,
+
+	/// The length of the decompressed data from the start of the stream, or `None` if it has not been read yet.
+	len: Option<u64>,
+
+	/// How many bytes have been decompressed so far.
+	decompressed: u64,
+
+	/// The last [`WINDOW`] or more decompressed bytes, for copies to refer to.
+	buffer: Vec<u8>,
+
+	/// The start of the bytes in `buffer` that have not been read yet.
+	pos: usize,
+}
+
+impl<R> Decompressor<R> where R: std::io::Read {
+	pub fn new(inner: R) -> Self {
+		Self {
+			inner,
+			len: None,
+			decompressed: 0,
+			buffer: vec![],
+			pos: 0,
+		}
+	}
+
+	/// Decompresses the next element of the stream onto the end of `buffer`. Returns `false` if there are no more.
+	fn decompress_element(&mut self) -> std::io::Result<bool> {
+		use std::io::Read;
+
+		let len = match self.len {
+			Some(len) => len,
+			None => *self.len.insert(read_varint(&mut self.inner)?),
+		};
+		let remaining = len - self.decompressed;
+		if remaining == 0 {
+			if (&mut self.inner).take(1).read_to_end(&mut vec![])? > 0 {
+				return Err(invalid_data("compressed data continues after the end of the decompressed data"));
+			}
+			return Ok(false);
+		}
+
+		let tag = read_le(&mut self.inner, 1)?;
+		let element_len = match tag & 0b11 {
+			0b00 => {
+				let len = match tag >> 2 {
+					len @ 0..60 => len,
+					len_bytes => read_le(&mut self.inner, (len_bytes - 59).try_into().expect("len_bytes is at most 4"))?,
+				};
+				let len = u64::from(len) + 1;
+				check_len(len, remaining)?;
+				let read = (&mut self.inner).take(len).read_to_end(&mut self.buffer)?;
+				if read as u64 != len {
+					return Err(std::io::ErrorKind::UnexpectedEof.into());
+				}
+				len
+			},
+
+			tag_type => {
+				let (len, offset) = match tag_type {
+					0b01 => (4 + ((tag >> 2) & 0b111), ((tag >> 5) << 8) | read_le(&mut self.inner, 1)?),
+					0b10 => (1 + (tag >> 2), read_le(&mut self.inner, 2)?),
+					_ => (1 + (tag >> 2), read_le(&mut self.inner, 4)?),
+				};
+				check_len(len.into(), remaining)?;
+				let offset: usize = offset.try_into().map_err(|_| invalid_data("copy offset is too large"))?;
+				if offset == 0 || offset > self.buffer.len() {
+					return Err(invalid_data("copy refers to data before the start of the window"));
+				}
+				for _ in 0..len {
+					self.buffer.push(self.buffer[self.buffer.len() - offset]);
+				}
+				len.into()
+			},
+		};
+		self.decompressed += element_len;
+
+		Ok(true)
+	}
+}
+
+/// Decompresses the whole of a raw snappy stream, as used by saves after their version byte.
+///
+/// Unlike reading from a [`Decompressor`], this keeps all of the decompressed data, so copies may reach back any distance.
+pub fn decompress_all(inner: impl std::io::Read) -> std::io::Result<Vec<u8>> {
+	let mut decompressor = Decompressor::new(inner);
+	while decompressor.decompress_element()? {}
+	Ok(decompressor.buffer)
+}
+
+impl<R> std::io::Read for Decompressor<R> where R: std::io::Read {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		if buf.is_empty() {
+			return Ok(0);
+		}
+
+		while self.pos == self.buffer.len() {
+			if self.buffer.len() > 2 * WINDOW {
+				self.buffer.drain(..(self.buffer.len() - WINDOW));
+				self.pos = self.buffer.len();
+			}
+
+			if !self.decompress_element()? {
+				return Ok(0);
+			}
+		}
+
+		let read = buf.len().min(self.buffer.len() - self.pos);
+		buf[..read].copy_from_slice(&self.buffer[self.pos..][..read]);
+		self.pos += read;
+		Ok(read)
+	}
+}
+
+fn read_varint(inner: &mut impl std::io::Read) -> std::io::Result<u64> {
+	let mut result = 0_u64;
+	for shift in (0..64).step_by(7) {
+		let byte = read_le(inner, 1)?;
+		result |= u64::from(byte & 0x7f) << shift;
+		if byte & 0x80 == 0 {
+			return Ok(result);
+		}
+	}
+	Err(invalid_data("length of decompressed data is too large"))
+}
+
+/// Reads a little-endian integer that is `len` bytes long, up to 4.
+fn read_le(inner: &mut impl std::io::Read, len: usize) -> std::io::Result<u32> {
+	let mut bytes = [0; 4];
+	inner.read_exact(&mut bytes[..len])?;
+	Ok(u32::from_le_bytes(bytes))
+}
+
+fn check_len(len: u64, remaining: u64) -> std::io::Result<()> {
+	if len > remaining {
+		return Err(invalid_data("decompressed data is longer than its header says"));
+	}
+	Ok(())
+}
+
+fn invalid_data(message: &'static str) -> std::io::Error {
+	std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+/// A save of any version whose components and wires are parsed as they are read.
+#[derive(Debug)]
+pub enum Stream<R> {
+	V6(CircuitStream<R, v6::CircuitData<'static>>),
+	V7(CircuitStream<R, v7::CircuitData<'static>>),
+	V8(CircuitStream<R, v8::CircuitData<'static>>),
+	V9(CircuitStream<R, v9::CircuitData<'static>>),
+	V10(CircuitStream<R, v10::CircuitData<'static>>),
+}
+
+/// Reads the version and the circuit's fields from the start of a save, and returns a stream of its components and wires.
+pub fn stream<R>(mut reader: R) -> Result<Stream<R>, LoadError> where R: std::io::Read {
+	let mut version = [0];
+	reader.read_exact(&mut version).map_err(LoadError::Read)?;
+	let reader = Decompressor::new(reader);
+	Ok(match version[0] {
+		6 => Stream::V6(CircuitStream::new(reader)?),
+		7 => Stream::V7(CircuitStream::new(reader)?),
+		8 => Stream::V8(CircuitStream::new(reader)?),
+		9 => Stream::V9(CircuitStream::new(reader)?),
+		10 => Stream::V10(CircuitStream::new(reader)?),
+		version => return Err(LoadError::Parse(ParseError { offset: 0, field: None, kind: ParseErrorKind::UnsupportedVersion(version) })),
+	})
+}
+
+/// The `CircuitData` of a save version, which [`CircuitStream`] parses in parts.
+pub trait Version: Sized {
+	type Component;
+	type Wire;
+
+	/// Parses the fields that come before the components, and leaves `components` and `wires` empty.
+	fn parse_header(input: &mut Input<'_>) -> Result<Self, ParseError>;
+
+	fn parse_component(input: &mut Input<'_>) -> Result<Self::Component, ParseError>;
+
+	fn parse_wire(input: &mut Input<'_>) -> Result<Self::Wire, ParseError>;
+}
+
+macro_rules! impl_version {
+	($($version:ident,)*) => {
+		$(
+			impl Version for $version::CircuitData<'static> {
+				type Component = $version::Component<'static>;
+				type Wire = $version::Wire<'static>;
+
+				fn parse_header(input: &mut Input<'_>) -> Result<Self, ParseError> {
+					$version::CircuitData::parse_header(input).map(IntoOwned::into_owned)
+				}
+
+				fn parse_component(input: &mut Input<'_>) -> Result<Self::Component, ParseError> {
+					$version::Component::parse(input).map(IntoOwned::into_owned)
+				}
+
+				fn parse_wire(input: &mut Input<'_>) -> Result<Self::Wire, ParseError> {
+					$version::Wire::parse(input).map(IntoOwned::into_owned)
+				}
+			}
+		)*
+	};
+}
+
+impl_version! {
+	v6,
+	v7,
+	v8,
+	v9,
+	v10,
+}
+
+/// A save whose components and wires are parsed one at a time.
+///
+/// Reading a wire skips any components that have not been read yet. After the last wire, any bytes left in the save are an error,
+/// as they are for [`crate::from_bytes`]. Once an element fails to parse, no more are returned.
+#[derive(Debug)]
+pub struct CircuitStream<R, C> {
+	input: Buffered<R>,
+	header: C,
+	components_left: u64,
+
+	/// `None` until the number of wires has been read.
+	wires_left: Option<u64>,
+
+	/// Whether the save has been read to the end, or failed to parse.
+	done: bool,
+}
+
+impl<R, C> CircuitStream<R, C> where R: std::io::Read, C: Version {
+	fn new(input: Decompressor<R>) -> Result<Self, LoadError> {
+		let mut input = Buffered {
+			inner: input,
+			buffer: vec![],
+			pos: 0,
+			offset: 0,
+			eof: false,
+		};
+		let header = input.parse(C::parse_header)?;
+		let components_left = input.parse(|input| u64::parse(input).field("CircuitData.components"))?;
+		Ok(Self {
+			input,
+			header,
+			components_left,
+			wires_left: None,
+			done: false,
+		})
+	}
+
+	/// The circuit's fields. Its `components` and `wires` are empty.
+	pub fn header(&self) -> &C {
+		&self.header
+	}
+
+	pub fn next_component(&mut self) -> Option<Result<C::Component, LoadError>> {
+		if self.components_left == 0 {
+			return None;
+		}
+
+		let result = self.input.parse(C::parse_component);
+		if result.is_ok() {
+			self.components_left -= 1;
+		}
+		else {
+			self.components_left = 0;
+			self.done = true;
+		}
+		Some(result)
+	}
+
+	pub fn next_wire(&mut self) -> Option<Result<C::Wire, LoadError>> {
+		if self.done {
+			return None;
+		}
+
+		if self.wires_left.is_none() {
+			while let Some(component) = self.next_component() {
+				if let Err(err) = component {
+					return Some(Err(err));
+				}
+			}
+			match self.input.parse(|input| u64::parse(input).field("CircuitData.wires")) {
+				Ok(wires_left) => self.wires_left = Some(wires_left),
+				Err(err) => {
+					self.done = true;
+					return Some(Err(err));
+				},
+			}
+		}
+
+		let wires_left = self.wires_left.as_mut()?;
+		if *wires_left == 0 {
+			self.done = true;
+			return self.input.finish().err().map(Err);
+		}
+
+		let result = self.input.parse(C::parse_wire);
+		if result.is_ok() {
+			*wires_left -= 1;
+		}
+		else {
+			self.done = true;
+		}
+		Some(result)
+	}
+
+	pub fn components(&mut self) -> impl Iterator<Item = Result<C::Component, LoadError>> {
+		std::iter::from_fn(|| self.next_component())
+	}
+
+	pub fn wires(&mut self) -> impl Iterator<Item = Result<C::Wire, LoadError>> {
+		std::iter::from_fn(|| self.next_wire())
+	}
+}
+
+/// A buffer of decompressed bytes that elements are parsed from, which is refilled when an element does not fit in it.
+#[derive(Debug)]
+struct Buffered<R> {
+	inner: Decompressor<R>,
+	buffer: Vec<u8>,
+
+	/// The start of the bytes in `buffer` that have not been parsed yet.
+	pos: usize,
+
+	/// How many decompressed bytes came before `buffer`, for the offsets of errors.
+	offset: usize,
+
+	/// Whether `inner` has no more bytes.
+	eof: bool,
+}
+
+impl<R> Buffered<R> where R: std::io::Read {
+	fn parse<T>(&mut self, parse: impl Fn(&mut Input<'_>) -> Result<T, ParseError>) -> Result<T, LoadError> {
+		loop {
+			let available = Input { bytes: &self.buffer[self.pos..], offset: self.offset + self.pos };
+			let mut input = available;
+			match parse(&mut input) {
+				Ok(value) => {
+					self.pos += input.offset - available.offset;
+					return Ok(value);
+				},
+
+				Err(err) if matches!(err.kind, ParseErrorKind::UnexpectedEof) && !self.eof => self.fill().map_err(LoadError::Read)?,
+
+				Err(err) => return Err(LoadError::Parse(err)),
+			}
+		}
+	}
+
+	/// Fails if there are any bytes after the ones that have been parsed.
+	fn finish(&mut self) -> Result<(), LoadError> {
+		if self.pos == self.buffer.len() && !self.eof {
+			self.fill().map_err(LoadError::Read)?;
+		}
+
+		let rest = Input { bytes: &self.buffer[self.pos..], offset: self.offset + self.pos };
+		if !rest.is_empty() {
+			return Err(LoadError::Parse(rest.error(ParseErrorKind::TrailingBytes)));
+		}
+		Ok(())
+	}
+
+	/// Drops the bytes that have been parsed, and reads at least as many bytes again as are left.
+	fn fill(&mut self) -> std::io::Result<()> {
+		use std::io::Read;
+
+		self.buffer.drain(..self.pos);
+		self.offset += self.pos;
+		self.pos = 0;
+
+		let additional = self.buffer.len().max(CHUNK) as u64;
+		let read = (&mut self.inner).take(additional).read_to_end(&mut self.buffer)?;
+		if (read as u64) < additional {
+			self.eof = true;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Read;
+
+	use crate::{
+		Encode,
+		LoadError,
+		ParseErrorKind,
+		Save,
+		builder::{CircuitBuilder, ComponentHandle, at},
+		v10,
+	};
+	use super::{
+		CHUNK,
+		Decompressor,
+		Stream,
+		WINDOW,
+	};
+
+	/// A reader that returns a few bytes at a time, to exercise reads that end in the middle of an element.
+	struct Trickle<'a>(&'a [u8]);
+
+	impl Read for Trickle<'_> {
+		fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+			let len = buf.len().min(self.0.len()).min(7);
+			buf[..len].copy_from_slice(&self.0[..len]);
+			self.0 = &self.0[len..];
+			Ok(len)
+		}
+	}
+
+	/// Pseudo-random bytes, from an xorshift generator.
+	fn random_bytes(len: usize, seed: u64) -> Vec<u8> {
+		let mut state = seed;
+		(0..len).map(|_| {
+			state ^= state << 13;
+			state ^= state >> 7;
+			state ^= state << 17;
+			state.to_le_bytes()[0]
+		}).collect()
+	}
+
+	fn decompress(compressed: &[u8]) -> std::io::Result<Vec<u8>> {
+		let mut result = vec![];
+		Decompressor::new(Trickle(compressed)).read_to_end(&mut result)?;
+		Ok(result)
+	}
+
+	#[test]
+	fn decompressor_matches_snap() {
+		let repetitive: Vec<_> = (0..(3 * WINDOW + CHUNK)).map(|i| u8::try_from(i % 251 / 7).unwrap()).collect();
+		let mixed: Vec<_> =
+			random_bytes(2 * WINDOW, 1).chunks(1000)
+			.zip(repetitive.chunks(3000))
+			.flat_map(|(random, repetitive)| random.iter().chain(repetitive))
+			.copied()
+			.collect();
+		let inputs = [
+			vec![],
+			vec![0],
+			random_bytes(100, 2),
+			random_bytes(3 * WINDOW + 1, 3),
+			repetitive,
+			mixed,
+		];
+
+		for input in inputs {
+			let compressed = snap::raw::Encoder::new().compress_vec(&input).unwrap();
+			let decompressed = decompress(&compressed).unwrap();
+			assert_eq!(decompressed, snap::raw::Decoder::new().decompress_vec(&compressed).unwrap());
+			assert_eq!(decompressed, input);
+		}
+	}
+
+	/// A raw snappy stream of one literal of `bytes[..split]`, then a copy of `bytes[split..]` from `offset` bytes back,
+	/// which must be at most 64 bytes, then nothing or a literal of `rest`.
+	fn compress_with_copy(bytes: &[u8], split: usize, offset: usize, rest: &[u8]) -> Vec<u8> {
+		fn literal(out: &mut Vec<u8>, bytes: &[u8]) {
+			if bytes.is_empty() {
+				return;
+			}
+			let len = u32::try_from(bytes.len() - 1).unwrap().to_le_bytes();
+			let len_bytes = 4 - len.iter().rev().take_while(|&&byte| byte == 0).count().min(3);
+			out.push(u8::try_from(59 + len_bytes).unwrap() << 2);
+			out.extend_from_slice(&len[..len_bytes]);
+			out.extend_from_slice(bytes);
+		}
+
+		let mut len = bytes.len() + rest.len();
+		let mut out = vec![];
+		while len >= 0x80 {
+			out.push(u8::try_from(len & 0x7f).unwrap() | 0x80);
+			len >>= 7;
+		}
+		out.push(u8::try_from(len).unwrap());
+
+		literal(&mut out, &bytes[..split]);
+		out.push((u8::try_from(bytes.len() - split - 1).unwrap() << 2) | 0b11);
+		out.extend_from_slice(&u32::try_from(offset).unwrap().to_le_bytes());
+		literal(&mut out, rest);
+		out
+	}
+
+	#[test]
+	fn copies_beyond_the_window() {
+		let mut bytes = random_bytes(2 * WINDOW + CHUNK, 5);
+		bytes.extend_from_within(..16);
+		let compressed = compress_with_copy(&bytes, bytes.len() - 16, bytes.len() - 16, &[1, 2, 3]);
+		bytes.extend_from_slice(&[1, 2, 3]);
+
+		assert_eq!(snap::raw::Decoder::new().decompress_vec(&compressed).unwrap(), bytes);
+		assert_eq!(super::decompress_all(Trickle(&compressed)).unwrap(), bytes);
+
+		// Reading from a `Decompressor` only keeps the last `WINDOW` bytes for copies.
+		let err = decompress(&compressed).unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+	}
+
+	#[test]
+	fn from_reader_accepts_copies_beyond_the_window() {
+		let text = |len, seed| random_bytes(len, seed).into_iter().map(|byte| char::from(b'a' + byte % 26)).collect::<String>();
+
+		// The description and the last component's custom_string are the same, with more than `2 * WINDOW` bytes between them.
+		let repeated = text(32, 6);
+		let mut builder = CircuitBuilder::new();
+		builder.description(repeated.clone());
+		for i in 0..40 {
+			let component = builder.add(v10::ComponentKind::NotBit, at(0, 2 * i));
+			builder.component_mut(component).custom_string = text(4000, 7 + u64::from(i.unsigned_abs())).into();
+		}
+		let last = builder.add(v10::ComponentKind::NotBit, at(0, 100));
+		builder.component_mut(last).custom_string = repeated.clone().into();
+		let mut raw = vec![];
+		builder.build().encode(&mut raw);
+
+		// Compress the save with the last component's custom_string copied from the description.
+		let first = raw.windows(32).position(|window| window == repeated.as_bytes()).unwrap();
+		let second = raw.windows(32).rposition(|window| window == repeated.as_bytes()).unwrap();
+		assert!(second - first > 2 * WINDOW);
+		let mut save = compress_with_copy(&raw[..(second + 32)], second, second - first, &raw[(second + 32)..]);
+		save.insert(0, 10);
+
+		let Save::V10(expected) = crate::from_bytes(&save).unwrap() else { panic!("save is not v10"); };
+		let Save::V10(actual) = crate::from_reader(Trickle(&save)).unwrap() else { panic!("save is not v10"); };
+		assert_eq!(format!("{actual:?}"), format!("{expected:?}"));
+		assert_eq!(expected.components.get(40).unwrap().into_inner().custom_string, repeated);
+	}
+
+	#[test]
+	fn from_reader_rejects_unsupported_versions() {
+		let mut save = large_circuit().encode_final();
+		save[0] = 11;
+		assert!(matches!(crate::from_reader(&save[..]), Err(LoadError::Parse(err)) if matches!(err.kind, ParseErrorKind::UnsupportedVersion(11))));
+		assert!(matches!(crate::from_reader(&[][..]), Err(LoadError::Read(err)) if err.kind() == std::io::ErrorKind::UnexpectedEof));
+	}
+
+	#[test]
+	fn decompressor_rejects_bad_input() {
+		let compressed = snap::raw::Encoder::new().compress_vec(&random_bytes(1000, 4)).unwrap();
+
+		let err = decompress(&compressed[..(compressed.len() - 1)]).unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+
+		let mut trailing = compressed.clone();
+		trailing.push(0);
+		let err = decompress(&trailing).unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+		assert!(snap::raw::Decoder::new().decompress_vec(&trailing).is_err());
+
+		// A length of 4, then a copy of 4 bytes from 1 byte back with nothing before it.
+		let err = decompress(&[4, 0b0000_0001, 1]).unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+	}
+
+	/// A v10 save that is larger than `WINDOW` when decompressed, with components and wires of different sizes.
+	fn large_circuit() -> v10::CircuitData<'static> {
+		let mut builder = CircuitBuilder::new();
+		builder.description("large circuit");
+		let mut previous: Option<ComponentHandle> = None;
+		for i in 0..5000_i16 {
+			let component = builder.add(v10::ComponentKind::NotBit, at(i % 100 * 4, i / 100 * 2));
+			builder.component_mut(component).custom_string = "x".repeat(usize::from(i.unsigned_abs() % 7)).into();
+			if let Some(previous) = previous && i % 3 != 0 {
+				builder.wire(previous.out(0), component.input(0));
+			}
+			previous = Some(component);
+		}
+		builder.build()
+	}
+
+	fn save_with_trailing_bytes(circuit: &v10::CircuitData<'_>) -> Vec<u8> {
+		let mut raw = vec![];
+		circuit.encode(&mut raw);
+		raw.push(0);
+		let mut save = snap::raw::Encoder::new().compress_vec(&raw).unwrap();
+		save.insert(0, 10);
+		save
+	}
+
+	#[test]
+	fn from_reader_matches_from_bytes() {
+		let save = large_circuit().encode_final();
+		assert!(save.len() > CHUNK);
+
+		let Save::V10(expected) = crate::from_bytes(&save).unwrap() else { panic!("save is not v10"); };
+		let Save::V10(actual) = crate::from_reader(Trickle(&save)).unwrap() else { panic!("save is not v10"); };
+		assert_eq!(format!("{actual:?}"), format!("{expected:?}"));
+
+		let save = save_with_trailing_bytes(&expected);
+		assert!(matches!(crate::from_bytes(&save), Err(err) if matches!(err.kind, ParseErrorKind::TrailingBytes)));
+		assert!(matches!(crate::from_reader(&save[..]), Err(LoadError::Parse(err)) if matches!(err.kind, ParseErrorKind::TrailingBytes)));
+	}
+
+	#[test]
+	fn stream_matches_from_bytes() {
+		let save = large_circuit().encode_final();
+		let Save::V10(expected) = crate::from_bytes(&save).unwrap() else { panic!("save is not v10"); };
+
+		let Stream::V10(mut stream) = super::stream(Trickle(&save)).unwrap() else { panic!("save is not v10"); };
+		assert_eq!(stream.header().description, expected.description);
+		assert!(stream.header().components.is_empty());
+		let components: Vec<_> = stream.components().collect::<Result<_, _>>().unwrap();
+		let wires: Vec<_> = stream.wires().collect::<Result<_, _>>().unwrap();
+		assert_eq!(format!("{components:?}"), format!("{:?}", expected.components.iter().map(crate::Either::into_inner).collect::<Vec<_>>()));
+		assert_eq!(format!("{wires:?}"), format!("{:?}", expected.wires.iter().map(crate::Either::into_inner).collect::<Vec<_>>()));
+
+		// Reading the wires first skips the components.
+		let Stream::V10(mut stream) = super::stream(&save[..]).unwrap() else { panic!("save is not v10"); };
+		assert_eq!(stream.wires().count(), expected.wires.len());
+		assert!(stream.next_component().is_none());
+
+		// Trailing bytes are reported after the last wire.
+		let save = save_with_trailing_bytes(&expected);
+		let Stream::V10(mut stream) = super::stream(&save[..]).unwrap() else { panic!("save is not v10"); };
+		let wires: Vec<_> = stream.wires().collect();
+		assert_eq!(wires.len(), expected.wires.len() + 1);
+		assert!(matches!(wires.last(), Some(Err(LoadError::Parse(err))) if matches!(err.kind, ParseErrorKind::TrailingBytes)));
+		assert!(stream.next_wire().is_none());
+	}
+
+	#[test]
+	fn stream_reports_truncated_saves() {
+		let save = large_circuit().encode_final();
+		let raw = snap::raw::Decoder::new().decompress_vec(&save[1..]).unwrap();
+		let mut truncated = snap::raw::Encoder::new().compress_vec(&raw[..(raw.len() / 2)]).unwrap();
+		truncated.insert(0, 10);
+
+		let Err(expected) = crate::from_bytes(&truncated) else { panic!("truncated save parsed"); };
+		let Stream::V10(mut stream) = super::stream(Trickle(&truncated)).unwrap() else { panic!("save is not v10"); };
+		let errors: Vec<_> = stream.components().filter_map(Result::err).collect();
+		let [LoadError::Parse(actual)] = &errors[..] else { panic!("expected one parse error, got {errors:?}"); };
+		assert_eq!(actual.offset, expected.offset);
+		assert_eq!(actual.field, expected.field);
+		assert!(stream.next_wire().is_none());
+	}
+}
@@ -0,0 +1,334 @@
+//! Circuits that the tests of several modules share.
+//!
+//! [`not_gate`] is built with [`CircuitBuilder`]. The `vN_circuit`s are written out field by field, like a save that the game made,
+//! and set every field of their version to something other than its default. They have the same layout in every version:
+//! an input at (0, 0), a word-wide `AND` at (6, 0), a custom component, a component that the next version does not have,
+//! a wire from (1, 0) and a telewire from (0, 0). [`decoded_saves`] encodes and decodes them again,
+//! for tests that should run on parsed saves rather than on circuits made in code.
+
+use crate::{
+	Save,
+	builder::{CircuitBuilder, at},
+	v6, v7, v8, v9, v10,
+};
+
+/// A `LevelInput1` at (0, 0) wired to a `NotBit` at (6, 0) wired to a `LevelOutput1` at (12, 0),
+/// with the permanent ids 1, 2 and 3.
+pub(crate) fn not_gate() -> v10::CircuitData<'static> {
+	let mut builder = CircuitBuilder::new();
+	let input = builder.add(v10::ComponentKind::LevelInput1, at(0, 0));
+	let not = builder.add(v10::ComponentKind::NotBit, at(6, 0));
+	let output = builder.add(v10::ComponentKind::LevelOutput1, at(12, 0));
+	builder.wire(input.out(0), not.input(0));
+	builder.wire(not.out(0), output.input(0));
+	builder.build()
+}
+
+/// Each of the `vN_circuit`s, encoded and compressed, then decompressed and parsed again.
+pub(crate) fn decoded_saves() -> [Save; 5] {
+	[
+		Save::V6(v6_circuit()),
+		Save::V7(v7_circuit()),
+		Save::V8(v8_circuit()),
+		Save::V9(v9_circuit()),
+		Save::V10(v10_circuit()),
+	].map(|save| crate::from_bytes(&save.encode_final()).unwrap())
+}
+
+/// The bytes of the segments of the wires of the `vN_circuit`s.
+///
+/// The last byte is the zero-length segment that ends the segments, here with a direction other than `Right`.
+pub(crate) const SEGMENTS: [u8; 4] = [0x03, 0x42, 0xe1, 0x60];
+
+macro_rules! segments {
+	($version:ident) => {
+		$version::WireSegments::Segments(
+			SEGMENTS.iter()
+			.map(|&byte| $version::WireSegment {
+				length: byte & 0x1f,
+				direction: $version::WireDirection::try_from(byte >> 5).unwrap(),
+			})
+			.collect::<Vec<_>>()
+			.into(),
+		)
+	};
+}
+
+pub(crate) fn v6_circuit() -> v6::CircuitData<'static> {
+	let component = |kind, position, permanent_id| v6::Component {
+		kind,
+		position,
+		rotation: 0,
+		permanent_id,
+		custom_string: "".into(),
+		settings: [0, 0],
+		ui_order: 0,
+		custom_data: None,
+		assembler_data: None,
+	};
+
+	v6::CircuitData {
+		custom_id: -9,
+		hub_id: 3,
+		gate: 4,
+		delay: 5,
+		menu_visible: true,
+		clock_speed: 100,
+		dependencies: vec![77].into(),
+		description: "A v6 circuit".into(),
+		camera_position: v6::Point { x: -7, y: 8 },
+		synced: v6::SyncState::Synced,
+		player_data: vec![0, 255].into(),
+		hub_description: "hub".into(),
+		components: vec![
+			component(v6::ComponentKind::Input1, v6::Point { x: 0, y: 0 }, 1),
+			v6::Component {
+				rotation: 1,
+				custom_string: "and".into(),
+				settings: [3, 4],
+				ui_order: -2,
+				..component(v6::ComponentKind::And8, v6::Point { x: 6, y: 0 }, 2)
+			},
+			v6::Component {
+				custom_data: Some(v6::CustomCompData { id: 77, custom_nudge: v6::Point { x: 1, y: 2 } }),
+				..component(v6::ComponentKind::Custom, v6::Point { x: -6, y: 4 }, 3)
+			},
+			v6::Component {
+				assembler_data: Some(v6::AssemblerInfo { programs: vec![(5, "prog".into())].into() }),
+				..component(v6::ComponentKind::Program, v6::Point { x: 0, y: 10 }, 4)
+			},
+			component(v6::ComponentKind::Buffer1, v6::Point { x: 10, y: 10 }, 5),
+		].into(),
+		wires: vec![
+			v6::Wire { width: 1, color: 2, comment: "w".into(), start: v6::Point { x: 1, y: 0 }, segments: segments!(v6) },
+			v6::Wire { width: 8, color: 0, comment: "".into(), start: v6::Point { x: 0, y: 0 }, segments: v6::WireSegments::TeleWireEnd(v6::Point { x: 9, y: 9 }) },
+		].into(),
+	}
+}
+
+pub(crate) fn v7_circuit() -> v7::CircuitData<'static> {
+	let component = |kind, position, permanent_id| v7::Component {
+		kind,
+		position,
+		rotation: 0,
+		permanent_id,
+		custom_string: "".into(),
+		settings: vec![].into(),
+		buffer_size: 0,
+		ui_order: 0,
+		word_size: 0,
+		discarded: 0,
+		custom_data: None,
+		assembler_data: None,
+	};
+
+	v7::CircuitData {
+		custom_id: -9,
+		hub_id: 3,
+		gate: 4,
+		delay: 5,
+		menu_visible: true,
+		clock_speed: 100,
+		dependencies: vec![77].into(),
+		description: "A v7 circuit".into(),
+		camera_position: v7::Point { x: -7, y: 8 },
+		synced: v7::SyncState::Synced,
+		player_data: vec![0, 255].into(),
+		hub_description: "hub".into(),
+		components: vec![
+			component(v7::ComponentKind::LevelInput1, v7::Point { x: 0, y: 0 }, 1),
+			v7::Component {
+				rotation: 1,
+				custom_string: "and".into(),
+				settings: vec![3, 4].into(),
+				buffer_size: 6,
+				ui_order: -2,
+				word_size: 16,
+				discarded: 7,
+				..component(v7::ComponentKind::AndWord, v7::Point { x: 6, y: 0 }, 2)
+			},
+			v7::Component {
+				custom_data: Some(v7::CustomCompData { id: 77, static_states: vec![(1, -1)].into(), linked_word_sizes: vec![(2, 8)].into() }),
+				..component(v7::ComponentKind::Custom, v7::Point { x: -6, y: 4 }, 3)
+			},
+			v7::Component {
+				assembler_data: Some(v7::AssemblerInfo {
+					programs: vec![("prog".into(), "".into())].into(),
+					watched_components: vec![v7::WatchedComponent { permanent_id: 2, inner_id: -1, name: "and".into() }].into(),
+				}),
+				..component(v7::ComponentKind::Assembler, v7::Point { x: 0, y: 10 }, 4)
+			},
+		].into(),
+		wires: vec![
+			v7::Wire { color: 2, comment: "w".into(), start: v7::Point { x: 1, y: 0 }, segments: segments!(v7) },
+			v7::Wire { color: 0, comment: "".into(), start: v7::Point { x: 0, y: 0 }, segments: v7::WireSegments::TeleWireEnd(v7::Point { x: 9, y: 9 }) },
+		].into(),
+	}
+}
+
+pub(crate) fn v8_circuit() -> v8::CircuitData<'static> {
+	let component = |kind, position, permanent_id| v8::Component {
+		kind,
+		position,
+		rotation: 0,
+		permanent_id,
+		custom_string: "".into(),
+		settings: vec![].into(),
+		buffer_size: 0,
+		ui_order: 0,
+		word_size: 0,
+		watched_components: vec![].into(),
+		custom_data: None,
+		selected_programs: None,
+	};
+
+	v8::CircuitData {
+		custom_id: -9,
+		hub_id: 3,
+		gate: 4,
+		delay: 5,
+		menu_visible: true,
+		clock_speed: 100,
+		dependencies: vec![77].into(),
+		description: "A v8 circuit".into(),
+		camera_position: v8::Point { x: -7, y: 8 },
+		synced: v8::SyncState::Synced,
+		player_data: vec![0, 255].into(),
+		hub_description: "hub".into(),
+		components: vec![
+			component(v8::ComponentKind::LevelInput1, v8::Point { x: 0, y: 0 }, 1),
+			v8::Component {
+				rotation: 1,
+				custom_string: "and".into(),
+				settings: vec![3, 4].into(),
+				buffer_size: 6,
+				ui_order: -2,
+				word_size: 16,
+				watched_components: vec![v8::WatchedComponent { permanent_id: 3, inner_id: -1, name: "custom".into() }].into(),
+				..component(v8::ComponentKind::AndWord, v8::Point { x: 6, y: 0 }, 2)
+			},
+			v8::Component {
+				custom_data: Some(v8::CustomCompData { id: 77, static_states: vec![(1, -1)].into() }),
+				..component(v8::ComponentKind::Custom, v8::Point { x: -6, y: 4 }, 3)
+			},
+			v8::Component {
+				selected_programs: Some(v8::AssemblerInfo { programs: vec![("prog".into(), "".into())].into() }),
+				..component(v8::ComponentKind::Assembler, v8::Point { x: 0, y: 10 }, 4)
+			},
+		].into(),
+		wires: vec![
+			v8::Wire { color: 2, comment: "w".into(), start: v8::Point { x: 1, y: 0 }, segments: segments!(v8) },
+			v8::Wire { color: 0, comment: "".into(), start: v8::Point { x: 0, y: 0 }, segments: v8::WireSegments::TeleWireEnd(v8::Point { x: 9, y: 9 }) },
+		].into(),
+	}
+}
+
+pub(crate) fn v9_circuit() -> v9::CircuitData<'static> {
+	let component = |kind, position, permanent_id| v9::Component {
+		kind,
+		position,
+		rotation: 0,
+		permanent_id,
+		custom_string: "".into(),
+		settings: vec![].into(),
+		buffer_size: 0,
+		ui_order: 0,
+		word_size: 0,
+		watched_components: vec![].into(),
+		selected_programs: v9::AssemblerInfo { programs: vec![].into() },
+		custom_data: None,
+	};
+
+	v9::CircuitData {
+		custom_id: -9,
+		hub_id: 3,
+		gate: 4,
+		delay: 5,
+		menu_visible: true,
+		clock_speed: 100,
+		dependencies: vec![77].into(),
+		description: "A v9 circuit".into(),
+		camera_position: v9::Point { x: -7, y: 8 },
+		synced: v9::SyncState::Synced,
+		player_data: vec![0, 255].into(),
+		hub_description: "hub".into(),
+		components: vec![
+			component(v9::ComponentKind::LevelInput1, v9::Point { x: 0, y: 0 }, 1),
+			v9::Component {
+				rotation: 1,
+				custom_string: "and".into(),
+				settings: vec![3, 4].into(),
+				buffer_size: 6,
+				ui_order: -2,
+				word_size: 16,
+				watched_components: vec![v9::WatchedComponent { permanent_id: 3, inner_id: -1, name: "custom".into() }].into(),
+				selected_programs: v9::AssemblerInfo { programs: vec![("prog".into(), "".into())].into() },
+				..component(v9::ComponentKind::AndWord, v9::Point { x: 6, y: 0 }, 2)
+			},
+			v9::Component {
+				custom_data: Some(v9::CustomCompData { id: 77, static_states: vec![(1, -1)].into() }),
+				..component(v9::ComponentKind::Custom, v9::Point { x: -6, y: 4 }, 3)
+			},
+			component(v9::ComponentKind::Ram, v9::Point { x: 0, y: 10 }, 4),
+		].into(),
+		wires: vec![
+			v9::Wire { color: 2, comment: "w".into(), start: v9::Point { x: 1, y: 0 }, segments: segments!(v9) },
+			v9::Wire { color: 0, comment: "".into(), start: v9::Point { x: 0, y: 0 }, segments: v9::WireSegments::TeleWireEnd(v9::Point { x: 9, y: 9 }) },
+		].into(),
+	}
+}
+
+pub(crate) fn v10_circuit() -> v10::CircuitData<'static> {
+	let component = |kind, position, permanent_id| v10::Component {
+		kind,
+		position,
+		rotation: 0,
+		permanent_id,
+		custom_string: "".into(),
+		settings: vec![].into(),
+		buffer_size: 0,
+		ui_order: 0,
+		word_size: 0,
+		linked_components: vec![].into(),
+		selected_programs: v10::AssemblerInfo { programs: vec![].into() },
+		custom_data: None,
+	};
+
+	v10::CircuitData {
+		custom_id: -9,
+		hub_id: 3,
+		gate: 4,
+		delay: 5,
+		menu_visible: true,
+		clock_speed: 100,
+		dependencies: vec![77].into(),
+		description: "A \"v10\" circuit\nwith two lines".into(),
+		camera_position: at(-7, 8),
+		synced: v10::SyncState::ChangedAfterSync,
+		player_data: vec![0, 255].into(),
+		hub_description: "hub".into(),
+		components: vec![
+			component(v10::ComponentKind::LevelInput1, at(0, 0), 1),
+			v10::Component {
+				rotation: 1,
+				custom_string: "and".into(),
+				settings: vec![3, u64::MAX].into(),
+				buffer_size: 6,
+				ui_order: -2,
+				word_size: 16,
+				linked_components: vec![v10::LinkedComponent { permanent_id: 3, inner_id: -1, name: "a b".into(), offset: 3 }].into(),
+				selected_programs: v10::AssemblerInfo { programs: vec![("prog".into(), "".into())].into() },
+				..component(v10::ComponentKind::AndWord, at(6, 0), 2)
+			},
+			v10::Component {
+				custom_data: Some(v10::CustomCompData { id: 77, static_states: vec![(1, -1)].into() }),
+				..component(v10::ComponentKind::Custom, at(-6, 4), 3)
+			},
+			component(v10::ComponentKind::LoadPort, at(0, 10), 4),
+		].into(),
+		wires: vec![
+			v10::Wire { color: 2, comment: "# not a comment".into(), start: at(1, 0), segments: segments!(v10) },
+			v10::Wire { color: 0, comment: "".into(), start: at(0, 0), segments: v10::WireSegments::TeleWireEnd(at(9, 9)) },
+		].into(),
+	}
+}
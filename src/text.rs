@@ -0,0 +1,666 @@
+//! A human-editable text form of v10 saves, that can be parsed back into the same save.
+//!
+//! The lines look like those of [`crate::textconv`], but every field is kept, including the camera position,
+//! and components and wires stay in the order they are in the save. So [`print()`] followed by [`parse`] gives back a save
+//! that encodes to the same bytes.
+//!
+//! ```text
+//! version 10
+//! clock_speed 100
+//! description "An adder"
+//! component 1 Add at=3,4 word_size=8
+//! component 2 Custom at=-3,4 rotation=1 custom_id=77
+//! wire 0,0 R3 D2 color=1
+//! wire 0,0 ~5,5
+//! ```
+//!
+//! The `version 10` line must come first. Blank lines and lines starting with `#` are ignored.
+//! The circuit's fields can be in any order, and fields that are left out default to zero, `false`, empty or `unsynced`.
+//!
+//! A component is its `permanent_id` and kind, followed by `key=value` fields. Only `at` is required.
+//! `word_size` defaults to 8 and every other field defaults to zero or empty. `custom_id` and `static_states` are only valid for
+//! `Custom` components, and `custom_id` is required for them.
+//!
+//! A wire is its start, followed by its segments, followed by its `color` and `comment`. Each segment is its direction
+//! followed by its length from 1 to 31. The game ends the segments of a wire with a zero-length segment,
+//! which is added as `R0` if the last segment is not already a zero-length one.
+
+use crate::{
+	Either,
+	textconv::{
+		direction,
+		list,
+		point,
+		quote,
+	},
+	v10,
+};
+
+#[derive(Debug)]
+pub struct ParseTextError {
+	/// The 1-based number of the line that could not be parsed.
+	pub line: usize,
+
+	pub kind: ParseTextErrorKind,
+}
+
+impl std::fmt::Display for ParseTextError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "could not parse line {}", self.line)
+	}
+}
+
+impl std::error::Error for ParseTextError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		Some(&self.kind)
+	}
+}
+
+#[derive(Debug)]
+pub enum ParseTextErrorKind {
+	Syntax(&'static str),
+	MissingVersion,
+	UnsupportedVersion(String),
+	UnknownKey(String),
+	DuplicateKey(String),
+	MissingKey(&'static str),
+	InvalidValue(&'static str),
+
+	/// The key is only valid for `Custom` components.
+	CustomOnly(&'static str),
+}
+
+impl std::fmt::Display for ParseTextErrorKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Syntax(message) => f.write_str(message),
+			Self::MissingVersion => f.write_str("expected a version line first"),
+			Self::UnsupportedVersion(version) => write!(f, "version {version} unsupported"),
+			Self::UnknownKey(key) => write!(f, "unknown key {key}"),
+			Self::DuplicateKey(key) => write!(f, "duplicate key {key}"),
+			Self::MissingKey(key) => write!(f, "missing key {key}"),
+			Self::InvalidValue(key) => write!(f, "invalid value for {key}"),
+			Self::CustomOnly(key) => write!(f, "{key} is only valid for Custom components"),
+		}
+	}
+}
+
+impl std::error::Error for ParseTextErrorKind {}
+
+pub fn print(circuit: &v10::CircuitData<'_>) -> String {
+	use std::fmt::Write;
+
+	let mut out = String::new();
+
+	let v10::CircuitData {
+		custom_id,
+		hub_id,
+		gate,
+		delay,
+		menu_visible,
+		clock_speed,
+		dependencies,
+		description,
+		camera_position,
+		synced,
+		player_data,
+		hub_description,
+		components,
+		wires,
+	} = circuit;
+	_ = writeln!(out, "version 10");
+	_ = writeln!(out, "custom_id {custom_id}");
+	_ = writeln!(out, "hub_id {hub_id}");
+	_ = writeln!(out, "gate {gate}");
+	_ = writeln!(out, "delay {delay}");
+	_ = writeln!(out, "menu_visible {menu_visible}");
+	_ = writeln!(out, "clock_speed {clock_speed}");
+	_ = writeln!(out, "dependencies {}", list(dependencies.iter().map(Either::into_inner)));
+	_ = writeln!(out, "description {}", quote(description));
+	_ = writeln!(out, "camera_position {}", point((*camera_position).into()));
+	_ = writeln!(out, "synced {}", match synced {
+		v10::SyncState::Unsynced => "unsynced",
+		v10::SyncState::Synced => "synced",
+		v10::SyncState::ChangedAfterSync => "changed_after_sync",
+	});
+	_ = writeln!(out, "player_data {}", list(player_data.iter().map(Either::into_inner)));
+	_ = writeln!(out, "hub_description {}", quote(hub_description));
+
+	for component in components {
+		let v10::Component {
+			kind,
+			position,
+			rotation,
+			permanent_id,
+			custom_string,
+			settings,
+			buffer_size,
+			ui_order,
+			word_size,
+			linked_components,
+			selected_programs,
+			custom_data,
+		} = component.as_inner_ref();
+
+		_ = write!(out, "component {permanent_id} {kind:?} at={} rotation={rotation} word_size={word_size}", point((*position).into()));
+		if let Some(custom_data) = custom_data {
+			_ = write!(out, " custom_id={}", custom_data.id);
+		}
+		_ = write!(out, " ui_order={ui_order} settings={} string={}", list(settings.iter().map(Either::into_inner)), quote(custom_string));
+		_ = write!(out, " buffer_size={buffer_size}");
+		_ = write!(out, " linked={}", list(linked_components.iter().map(|linked| {
+			let linked = linked.as_inner_ref();
+			format!("({} {} {} {})", linked.permanent_id, linked.inner_id, quote(&linked.name), linked.offset)
+		})));
+		if let Some(custom_data) = custom_data {
+			_ = write!(out, " static_states={}", list(custom_data.static_states.iter().map(|state| {
+				let (a, b) = state.into_inner();
+				format!("({a} {b})")
+			})));
+		}
+		_ = writeln!(out, " programs={}", list(selected_programs.programs.iter().map(|program| {
+			let (a, b) = program.as_inner_ref();
+			format!("({} {})", quote(a), quote(b))
+		})));
+	}
+
+	for wire in wires {
+		let v10::Wire { color, comment, start, segments } = wire.as_inner_ref();
+
+		_ = write!(out, "wire {}", point((*start).into()));
+		match segments {
+			v10::WireSegments::TeleWireEnd(end) => _ = write!(out, " ~{}", point((*end).into())),
+			v10::WireSegments::Segments(segments) => {
+				let segments: Vec<_> = segments.iter().map(Either::into_inner).collect();
+				let segments = match segments.split_last() {
+					Some((last, rest)) if last.length == 0 && matches!(last.direction, v10::WireDirection::Right) => rest,
+					_ => &segments[..],
+				};
+				for segment in segments {
+					_ = write!(out, " {}{}", direction(segment.direction.into()), segment.length);
+				}
+			},
+		}
+		_ = writeln!(out, " color={color} comment={}", quote(comment));
+	}
+
+	out
+}
+
+pub fn parse(text: &str) -> Result<v10::CircuitData<'static>, ParseTextError> {
+	let mut circuit = v10::CircuitData {
+		custom_id: 0,
+		hub_id: 0,
+		gate: 0,
+		delay: 0,
+		menu_visible: false,
+		clock_speed: 0,
+		dependencies: vec![].into(),
+		description: "".into(),
+		camera_position: v10::Point { x: 0, y: 0 },
+		synced: v10::SyncState::Unsynced,
+		player_data: vec![].into(),
+		hub_description: "".into(),
+		components: vec![].into(),
+		wires: vec![].into(),
+	};
+	let mut components = vec![];
+	let mut wires = vec![];
+	let mut seen_version = false;
+	let mut seen_keys = std::collections::BTreeSet::new();
+
+	for (i, line) in text.lines().enumerate() {
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+
+		let result = parse_items(line).and_then(|items| {
+			let mut items = items.into_iter();
+			let Some((None, Value::Atom(key))) = items.next() else {
+				return Err(ParseTextErrorKind::Syntax("expected a line to start with a key"));
+			};
+
+			if !seen_version {
+				if key != "version" {
+					return Err(ParseTextErrorKind::MissingVersion);
+				}
+				match single(items)? {
+					Value::Atom(version) if version == "10" => (),
+					Value::Atom(version) => return Err(ParseTextErrorKind::UnsupportedVersion(version)),
+					_ => return Err(ParseTextErrorKind::InvalidValue("version")),
+				}
+				seen_version = true;
+				return Ok(());
+			}
+
+			match &*key {
+				"component" => components.push(parse_component(items)?),
+				"wire" => wires.push(parse_wire(items)?),
+				key => {
+					if !seen_keys.insert(key.to_owned()) {
+						return Err(ParseTextErrorKind::DuplicateKey(key.to_owned()));
+					}
+					parse_circuit_field(&mut circuit, key, single(items)?)?;
+				},
+			}
+			Ok(())
+		});
+		result.map_err(|kind| ParseTextError { line: i + 1, kind })?;
+	}
+
+	if !seen_version {
+		return Err(ParseTextError { line: text.lines().count().max(1), kind: ParseTextErrorKind::MissingVersion });
+	}
+
+	circuit.components = components.into();
+	circuit.wires = wires.into();
+	Ok(circuit)
+}
+
+fn parse_circuit_field(circuit: &mut v10::CircuitData<'static>, key: &str, value: Value) -> Result<(), ParseTextErrorKind> {
+	match key {
+		"custom_id" => circuit.custom_id = number(value, "custom_id")?,
+		"hub_id" => circuit.hub_id = number(value, "hub_id")?,
+		"gate" => circuit.gate = number(value, "gate")?,
+		"delay" => circuit.delay = number(value, "delay")?,
+		"menu_visible" => circuit.menu_visible = number(value, "menu_visible")?,
+		"clock_speed" => circuit.clock_speed = number(value, "clock_speed")?,
+		"dependencies" => circuit.dependencies = list_of(value, "dependencies", |value| number(value, "dependencies"))?.into(),
+		"description" => circuit.description = string(value, "description")?.into(),
+		"camera_position" => circuit.camera_position = parse_point(value, "camera_position")?,
+		"synced" => circuit.synced = match value {
+			Value::Atom(synced) if synced == "unsynced" => v10::SyncState::Unsynced,
+			Value::Atom(synced) if synced == "synced" => v10::SyncState::Synced,
+			Value::Atom(synced) if synced == "changed_after_sync" => v10::SyncState::ChangedAfterSync,
+			_ => return Err(ParseTextErrorKind::InvalidValue("synced")),
+		},
+		"player_data" => circuit.player_data = list_of(value, "player_data", |value| number(value, "player_data"))?.into(),
+		"hub_description" => circuit.hub_description = string(value, "hub_description")?.into(),
+		key => return Err(ParseTextErrorKind::UnknownKey(key.to_owned())),
+	}
+	Ok(())
+}
+
+fn parse_component(mut items: impl Iterator<Item = (Option<String>, Value)>) -> Result<v10::Component<'static>, ParseTextErrorKind> {
+	let Some((None, permanent_id)) = items.next() else { return Err(ParseTextErrorKind::MissingKey("permanent_id")); };
+	let permanent_id = number(permanent_id, "permanent_id")?;
+	let Some((None, Value::Atom(kind))) = items.next() else { return Err(ParseTextErrorKind::MissingKey("kind")); };
+	let kind = v10::ComponentKind::from_name(&kind).ok_or(ParseTextErrorKind::InvalidValue("kind"))?;
+
+	let mut position = None;
+	let mut component = v10::Component {
+		kind,
+		position: v10::Point { x: 0, y: 0 },
+		rotation: 0,
+		permanent_id,
+		custom_string: "".into(),
+		settings: vec![].into(),
+		buffer_size: 0,
+		ui_order: 0,
+		word_size: 8,
+		linked_components: vec![].into(),
+		selected_programs: v10::AssemblerInfo { programs: vec![].into() },
+		custom_data: None,
+	};
+	let mut custom_id = None;
+	let mut static_states = None;
+
+	for (key, value) in fields(items)? {
+		match &*key {
+			"at" => position = Some(parse_point(value, "at")?),
+			"rotation" => component.rotation = number(value, "rotation")?,
+			"word_size" => component.word_size = number(value, "word_size")?,
+			"custom_id" => custom_id = Some(number(value, "custom_id")?),
+			"ui_order" => component.ui_order = number(value, "ui_order")?,
+			"settings" => component.settings = list_of(value, "settings", |value| number(value, "settings"))?.into(),
+			"string" => component.custom_string = string(value, "string")?.into(),
+			"buffer_size" => component.buffer_size = number(value, "buffer_size")?,
+			"linked" => component.linked_components = list_of(value, "linked", |value| {
+				let [permanent_id, inner_id, name, offset] = tuple(value, "linked")?;
+				Ok(v10::LinkedComponent {
+					permanent_id: number(permanent_id, "linked")?,
+					inner_id: number(inner_id, "linked")?,
+					name: string(name, "linked")?.into(),
+					offset: number(offset, "linked")?,
+				})
+			})?.into(),
+			"static_states" => static_states = Some(list_of(value, "static_states", |value| {
+				let [a, b] = tuple(value, "static_states")?;
+				Ok((number(a, "static_states")?, number(b, "static_states")?))
+			})?),
+			"programs" => component.selected_programs.programs = list_of(value, "programs", |value| {
+				let [a, b] = tuple(value, "programs")?;
+				Ok((string(a, "programs")?.into(), string(b, "programs")?.into()))
+			})?.into(),
+			key => return Err(ParseTextErrorKind::UnknownKey(key.to_owned())),
+		}
+	}
+
+	component.position = position.ok_or(ParseTextErrorKind::MissingKey("at"))?;
+
+	if matches!(kind, v10::ComponentKind::Custom) {
+		component.custom_data = Some(v10::CustomCompData {
+			id: custom_id.ok_or(ParseTextErrorKind::MissingKey("custom_id"))?,
+			static_states: static_states.unwrap_or_default().into(),
+		});
+	}
+	else if custom_id.is_some() {
+		return Err(ParseTextErrorKind::CustomOnly("custom_id"));
+	}
+	else if static_states.is_some() {
+		return Err(ParseTextErrorKind::CustomOnly("static_states"));
+	}
+
+	Ok(component)
+}
+
+fn parse_wire(items: impl Iterator<Item = (Option<String>, Value)>) -> Result<v10::Wire<'static>, ParseTextErrorKind> {
+	let mut items = items.peekable();
+
+	let Some((None, start)) = items.next() else { return Err(ParseTextErrorKind::MissingKey("start")); };
+	let start = parse_point(start, "start")?;
+
+	let mut segments = vec![];
+	let mut tele_wire_end = None;
+	while let Some((None, _)) = items.peek() {
+		let Some((None, Value::Atom(segment))) = items.next() else { return Err(ParseTextErrorKind::InvalidValue("segments")); };
+		if tele_wire_end.is_some() {
+			return Err(ParseTextErrorKind::InvalidValue("segments"));
+		}
+
+		if let Some(end) = segment.strip_prefix('~') {
+			if !segments.is_empty() {
+				return Err(ParseTextErrorKind::InvalidValue("segments"));
+			}
+			tele_wire_end = Some(parse_point(Value::Atom(end.to_owned()), "segments")?);
+			continue;
+		}
+
+		let length_start = segment.find(|c: char| c.is_ascii_digit()).ok_or(ParseTextErrorKind::InvalidValue("segments"))?;
+		let (direction, length) = segment.split_at(length_start);
+		let direction = match direction {
+			"R" => v10::WireDirection::Right,
+			"DR" => v10::WireDirection::DownRight,
+			"D" => v10::WireDirection::Down,
+			"DL" => v10::WireDirection::DownLeft,
+			"L" => v10::WireDirection::Left,
+			"UL" => v10::WireDirection::UpLeft,
+			"U" => v10::WireDirection::Up,
+			"UR" => v10::WireDirection::UpRight,
+			_ => return Err(ParseTextErrorKind::InvalidValue("segments")),
+		};
+		let Ok(length @ 0..=31) = length.parse() else { return Err(ParseTextErrorKind::InvalidValue("segments")); };
+		segments.push(v10::WireSegment { length, direction });
+	}
+
+	let segments = if let Some(end) = tele_wire_end {
+		v10::WireSegments::TeleWireEnd(end)
+	}
+	else {
+		if segments.last().is_none_or(|segment| segment.length != 0) {
+			segments.push(v10::WireSegment { length: 0, direction: v10::WireDirection::Right });
+		}
+
+		// Only the last segment can be zero-length, and a first segment of `DR0` would be read back as the start of a tele-wire.
+		let (_, rest) = segments.split_last().expect("segments is not empty");
+		if rest.iter().any(|segment| segment.length == 0) || matches!(segments[..], [v10::WireSegment { length: 0, direction: v10::WireDirection::DownRight }]) {
+			return Err(ParseTextErrorKind::InvalidValue("segments"));
+		}
+
+		v10::WireSegments::Segments(segments.into())
+	};
+
+	let mut wire = v10::Wire { color: 0, comment: "".into(), start, segments };
+	for (key, value) in fields(items)? {
+		match &*key {
+			"color" => wire.color = number(value, "color")?,
+			"comment" => wire.comment = string(value, "comment")?.into(),
+			key => return Err(ParseTextErrorKind::UnknownKey(key.to_owned())),
+		}
+	}
+
+	Ok(wire)
+}
+
+#[derive(Debug)]
+enum Value {
+	Atom(String),
+	String(String),
+	List(Vec<Value>),
+	Tuple(Vec<Value>),
+}
+
+/// Splits a line into its values, each with the key before it if it is a `key=value` field.
+fn parse_items(line: &str) -> Result<Vec<(Option<String>, Value)>, ParseTextErrorKind> {
+	let mut chars = line.chars().peekable();
+	let mut result = vec![];
+	loop {
+		while chars.next_if(|c| c.is_whitespace()).is_some() {}
+		if chars.peek().is_none() {
+			break;
+		}
+
+		let value = parse_value(&mut chars)?;
+		if chars.next_if_eq(&'=').is_some() {
+			let Value::Atom(key) = value else { return Err(ParseTextErrorKind::Syntax("expected a key before =")); };
+			let value = parse_value(&mut chars)?;
+			result.push((Some(key), value));
+		}
+		else {
+			result.push((None, value));
+		}
+
+		if chars.peek().is_some_and(|c| !c.is_whitespace()) {
+			return Err(ParseTextErrorKind::Syntax("expected whitespace between values"));
+		}
+	}
+	Ok(result)
+}
+
+fn parse_value(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> Result<Value, ParseTextErrorKind> {
+	match chars.peek() {
+		Some('"') => {
+			chars.next();
+			let mut s = String::new();
+			loop {
+				match chars.next() {
+					Some('"') => break Ok(Value::String(s)),
+					Some('\\') => s.push(match chars.next() {
+						Some('\\') => '\\',
+						Some('"') => '"',
+						Some('n') => '\n',
+						Some('r') => '\r',
+						Some('t') => '\t',
+						Some('u') => {
+							if chars.next() != Some('{') {
+								return Err(ParseTextErrorKind::Syntax("invalid \\u escape"));
+							}
+							let mut hex = String::new();
+							loop {
+								match chars.next() {
+									Some('}') => break,
+									Some(c) => hex.push(c),
+									None => return Err(ParseTextErrorKind::Syntax("invalid \\u escape")),
+								}
+							}
+							u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32).ok_or(ParseTextErrorKind::Syntax("invalid \\u escape"))?
+						},
+						_ => return Err(ParseTextErrorKind::Syntax("invalid escape")),
+					}),
+					Some(c) => s.push(c),
+					None => break Err(ParseTextErrorKind::Syntax("unterminated string")),
+				}
+			}
+		},
+
+		Some(&open @ ('[' | '(')) => {
+			chars.next();
+			let close = if open == '[' { ']' } else { ')' };
+			let mut values = vec![];
+			loop {
+				while chars.next_if(|c| c.is_whitespace()).is_some() {}
+				match chars.peek() {
+					Some(&c) if c == close => {
+						chars.next();
+						break;
+					},
+					Some(_) => values.push(parse_value(chars)?),
+					None => return Err(ParseTextErrorKind::Syntax("unterminated list")),
+				}
+			}
+			Ok(if open == '[' { Value::List(values) } else { Value::Tuple(values) })
+		},
+
+		_ => {
+			let mut atom = String::new();
+			while let Some(c) = chars.next_if(|&c| !c.is_whitespace() && !matches!(c, '"' | '[' | ']' | '(' | ')' | '=')) {
+				atom.push(c);
+			}
+			if atom.is_empty() {
+				return Err(ParseTextErrorKind::Syntax("expected a value"));
+			}
+			Ok(Value::Atom(atom))
+		},
+	}
+}
+
+fn single(mut items: impl Iterator<Item = (Option<String>, Value)>) -> Result<Value, ParseTextErrorKind> {
+	match (items.next(), items.next()) {
+		(Some((None, value)), None) => Ok(value),
+		_ => Err(ParseTextErrorKind::Syntax("expected a single value")),
+	}
+}
+
+/// Collects the remaining `key=value` fields, checking that none are repeated.
+fn fields(items: impl Iterator<Item = (Option<String>, Value)>) -> Result<Vec<(String, Value)>, ParseTextErrorKind> {
+	let mut seen = std::collections::BTreeSet::new();
+	let mut result = vec![];
+	for (key, value) in items {
+		let Some(key) = key else { return Err(ParseTextErrorKind::Syntax("expected key=value")); };
+		if !seen.insert(key.clone()) {
+			return Err(ParseTextErrorKind::DuplicateKey(key));
+		}
+		result.push((key, value));
+	}
+	Ok(result)
+}
+
+fn number<T>(value: Value, key: &'static str) -> Result<T, ParseTextErrorKind> where T: std::str::FromStr {
+	match value {
+		Value::Atom(atom) => atom.parse().map_err(|_| ParseTextErrorKind::InvalidValue(key)),
+		_ => Err(ParseTextErrorKind::InvalidValue(key)),
+	}
+}
+
+fn string(value: Value, key: &'static str) -> Result<String, ParseTextErrorKind> {
+	match value {
+		Value::String(s) => Ok(s),
+		_ => Err(ParseTextErrorKind::InvalidValue(key)),
+	}
+}
+
+fn parse_point(value: Value, key: &'static str) -> Result<v10::Point, ParseTextErrorKind> {
+	let Value::Atom(atom) = value else { return Err(ParseTextErrorKind::InvalidValue(key)); };
+	let (x, y) = atom.split_once(',').ok_or(ParseTextErrorKind::InvalidValue(key))?;
+	Ok(v10::Point {
+		x: x.parse().map_err(|_| ParseTextErrorKind::InvalidValue(key))?,
+		y: y.parse().map_err(|_| ParseTextErrorKind::InvalidValue(key))?,
+	})
+}
+
+fn list_of<T>(value: Value, key: &'static str, f: impl FnMut(Value) -> Result<T, ParseTextErrorKind>) -> Result<Vec<T>, ParseTextErrorKind> {
+	match value {
+		Value::List(values) => values.into_iter().map(f).collect(),
+		_ => Err(ParseTextErrorKind::InvalidValue(key)),
+	}
+}
+
+fn tuple<const N: usize>(value: Value, key: &'static str) -> Result<[Value; N], ParseTextErrorKind> {
+	match value {
+		Value::Tuple(values) => values.try_into().map_err(|_| ParseTextErrorKind::InvalidValue(key)),
+		_ => Err(ParseTextErrorKind::InvalidValue(key)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		Encode,
+		builder::{CircuitBuilder, at},
+		testing,
+		v10,
+	};
+	use super::{ParseTextErrorKind, parse, print};
+
+	fn encoded(circuit: &v10::CircuitData<'_>) -> Vec<u8> {
+		let mut out = vec![];
+		circuit.encode(&mut out);
+		out
+	}
+
+	#[test]
+	fn round_trip() {
+		let circuit = testing::v10_circuit();
+		let text = print(&circuit);
+		let parsed = parse(&text).unwrap();
+		assert_eq!(encoded(&parsed), encoded(&circuit), "{text}");
+		assert_eq!(print(&parsed), text);
+	}
+
+	#[test]
+	fn round_trip_empty() {
+		let circuit = CircuitBuilder::new().build();
+		assert_eq!(encoded(&parse(&print(&circuit)).unwrap()), encoded(&circuit));
+	}
+
+	#[test]
+	fn hand_written() {
+		let circuit = parse(r#"
+			# An adder
+			version 10
+			clock_speed 100
+			description "An adder"
+
+			component 1 Add at=3,4 word_size=8
+			component 2 Custom at=-3,4 rotation=1 custom_id=77
+			wire 0,0 R3 D2 color=1
+			wire 0,0 ~5,5
+		"#).unwrap();
+
+		let crate::Save::V10(parsed) = crate::from_bytes(&circuit.encode_final()).unwrap() else { panic!("expected a v10 save") };
+		assert_eq!(encoded(&parsed), encoded(&circuit));
+
+		assert_eq!(parsed.clock_speed, 100);
+		assert_eq!(parsed.description, "An adder");
+		assert_eq!(parsed.components.len(), 2);
+		let custom = parsed.components.get(1).unwrap().into_inner();
+		assert_eq!(custom.position, at(-3, 4));
+		assert_eq!(custom.rotation, 1);
+		assert_eq!(custom.custom_data.map(|custom_data| custom_data.id), Some(77));
+
+		// The game's zero-length segment is added to the end of the wire.
+		let wire = parsed.wires.get(0).unwrap().into_inner();
+		let v10::WireSegments::Segments(segments) = wire.segments else { panic!("expected segments") };
+		assert_eq!(segments.len(), 3);
+		assert_eq!(segments.get(2).unwrap().into_inner().length, 0);
+	}
+
+	#[test]
+	fn errors() {
+		let error = |text| parse(text).unwrap_err();
+
+		let e = error("clock_speed 100");
+		assert_eq!(e.line, 1);
+		assert!(matches!(e.kind, ParseTextErrorKind::MissingVersion));
+
+		assert!(matches!(error("version 9").kind, ParseTextErrorKind::UnsupportedVersion(_)));
+		assert!(matches!(error("version 10\nclock_speed 1\nclock_speed 2").kind, ParseTextErrorKind::DuplicateKey(_)));
+		assert!(matches!(error("version 10\nspeed 1").kind, ParseTextErrorKind::UnknownKey(_)));
+		assert!(matches!(error("version 10\ncomponent 1 Custom at=0,0").kind, ParseTextErrorKind::MissingKey("custom_id")));
+		assert!(matches!(error("version 10\ncomponent 1 Add at=0,0 custom_id=3").kind, ParseTextErrorKind::CustomOnly("custom_id")));
+
+		let e = error("version 10\n\nwire 0,0 R32");
+		assert_eq!(e.line, 3);
+	}
+}
@@ -0,0 +1,248 @@
+//! A stable, line-oriented text form of circuits, for use as a git `textconv` filter.
+//!
+//! The format is:
+//!
+//! ```text
+//! version 10
+//! custom_id 5
+//! ...
+//! component <permanent_id> <kind> at=<x>,<y> rotation=<rotation> ...
+//! wire <x>,<y> <segments> color=<color> comment=<string>
+//! ```
+//!
+//! The circuit's fields come first, one per line, in a fixed order. The camera position is left out,
+//! since it changes every time the circuit is viewed. Then come the components, one per line in ascending order of `permanent_id`,
+//! and then the wires, one per line in ascending order of the line.
+//!
+//! A wire's segments are written as their direction (`R`, `DR`, `D`, `DL`, `L`, `UL`, `U` or `UR`) followed by their length, such as `R3 D2`.
+//! The end of a wire that ends with [`WireSegments::TeleWireEnd`] is written as `~<x>,<y>`.
+//!
+//! Fields of components that the save version does not have, or that are not set, are left out.
+//! Lists are written as `[a b c]`, tuples as `(a b)`, and strings in double quotes with `\\`, `\"`, `\n`, `\r`, `\t` and `\u{..}` escapes.
+//!
+//! This format does not change between releases of this crate, except to add fields for new save versions.
+
+use crate::{
+	Either,
+	Parse,
+	Slice,
+	circuit::{
+		Circuit,
+		Component,
+		ComponentExtra,
+		Point,
+		SyncState,
+		Wire,
+		WireDirection,
+		WireExtra,
+		WireSegments,
+	},
+};
+
+pub fn textconv(circuit: &Circuit<'_>) -> String {
+	use std::fmt::Write;
+
+	let mut out = String::new();
+
+	let Circuit {
+		version,
+		custom_id,
+		hub_id,
+		gate,
+		delay,
+		menu_visible,
+		clock_speed,
+		dependencies,
+		description,
+		camera_position: _,
+		synced,
+		player_data,
+		hub_description,
+		components,
+		wires,
+	} = circuit;
+	_ = writeln!(out, "version {version}");
+	_ = writeln!(out, "custom_id {custom_id}");
+	_ = writeln!(out, "hub_id {hub_id}");
+	_ = writeln!(out, "gate {gate}");
+	_ = writeln!(out, "delay {delay}");
+	_ = writeln!(out, "menu_visible {menu_visible}");
+	_ = writeln!(out, "clock_speed {clock_speed}");
+	_ = writeln!(out, "dependencies {}", list(dependencies.iter()));
+	_ = writeln!(out, "description {}", quote(description));
+	_ = writeln!(out, "synced {}", match synced {
+		SyncState::Unsynced => "unsynced",
+		SyncState::Synced => "synced",
+		SyncState::ChangedAfterSync => "changed_after_sync",
+	});
+	_ = writeln!(out, "player_data {}", list(player_data.iter()));
+	_ = writeln!(out, "hub_description {}", quote(hub_description));
+
+	let mut components: Vec<_> = components.iter().collect();
+	components.sort_by_key(|component| component.permanent_id);
+	for component in components {
+		_ = writeln!(out, "{}", component_line(component));
+	}
+
+	let mut wires: Vec<_> = wires.iter().map(wire_line).collect();
+	wires.sort();
+	for wire in wires {
+		_ = writeln!(out, "{wire}");
+	}
+
+	out
+}
+
+fn component_line(component: &Component<'_>) -> String {
+	use std::fmt::Write;
+
+	let Component { kind, position, rotation, permanent_id, custom_string, settings, ui_order, word_size, custom_id, extra } = component;
+
+	// v6 saves have their own kind, whose name includes the word size.
+	let mut line = match extra {
+		ComponentExtra::V6 { kind, .. } => format!("component {permanent_id} {kind:?} at={} rotation={rotation}", point(*position)),
+		_ => format!("component {permanent_id} {kind} at={} rotation={rotation} word_size={word_size}", point(*position)),
+	};
+	if let Some(custom_id) = custom_id {
+		_ = write!(line, " custom_id={custom_id}");
+	}
+	_ = write!(line, " ui_order={ui_order} settings={} string={}", list(settings.iter()), quote(custom_string));
+
+	match extra {
+		ComponentExtra::V6 { kind: _, custom_nudge, assembler_data } => {
+			if let Some(custom_nudge) = custom_nudge {
+				_ = write!(line, " custom_nudge={}", point(*custom_nudge));
+			}
+			if let Some(assembler_data) = assembler_data {
+				_ = write!(line, " programs={}", list(items(&assembler_data.programs).map(|(id, name)| format!("({id} {})", quote(&name)))));
+			}
+		},
+
+		ComponentExtra::V7 { buffer_size, discarded, static_states, linked_word_sizes, assembler_data } => {
+			_ = write!(line, " buffer_size={buffer_size} discarded={discarded}");
+			if let Some(static_states) = static_states {
+				_ = write!(line, " static_states={}", pairs(static_states));
+			}
+			if let Some(linked_word_sizes) = linked_word_sizes {
+				_ = write!(line, " linked_word_sizes={}", pairs(linked_word_sizes));
+			}
+			if let Some(assembler_data) = assembler_data {
+				_ = write!(line, " programs={}", programs(&assembler_data.programs));
+				_ = write!(line, " watched={}", list(items(&assembler_data.watched_components).map(|watched|
+					format!("({} {} {})", watched.permanent_id, watched.inner_id, quote(&watched.name)))));
+			}
+		},
+
+		ComponentExtra::V8 { buffer_size, watched_components, static_states, selected_programs } => {
+			_ = write!(line, " buffer_size={buffer_size}");
+			_ = write!(line, " watched={}", list(items(watched_components).map(|watched|
+				format!("({} {} {})", watched.permanent_id, watched.inner_id, quote(&watched.name)))));
+			if let Some(static_states) = static_states {
+				_ = write!(line, " static_states={}", pairs(static_states));
+			}
+			if let Some(selected_programs) = selected_programs {
+				_ = write!(line, " programs={}", programs(&selected_programs.programs));
+			}
+		},
+
+		ComponentExtra::V9 { buffer_size, watched_components, static_states, selected_programs } => {
+			_ = write!(line, " buffer_size={buffer_size}");
+			_ = write!(line, " watched={}", list(items(watched_components).map(|watched|
+				format!("({} {} {})", watched.permanent_id, watched.inner_id, quote(&watched.name)))));
+			if let Some(static_states) = static_states {
+				_ = write!(line, " static_states={}", pairs(static_states));
+			}
+			_ = write!(line, " programs={}", programs(&selected_programs.programs));
+		},
+
+		ComponentExtra::V10 { buffer_size, linked_components, static_states, selected_programs } => {
+			_ = write!(line, " buffer_size={buffer_size}");
+			_ = write!(line, " linked={}", list(items(linked_components).map(|linked|
+				format!("({} {} {} {})", linked.permanent_id, linked.inner_id, quote(&linked.name), linked.offset))));
+			if let Some(static_states) = static_states {
+				_ = write!(line, " static_states={}", pairs(static_states));
+			}
+			_ = write!(line, " programs={}", programs(&selected_programs.programs));
+		},
+	}
+
+	line
+}
+
+fn wire_line(wire: &Wire<'_>) -> String {
+	use std::fmt::Write;
+
+	let Wire { color, comment, start, segments, extra } = wire;
+
+	let segments = match segments {
+		WireSegments::TeleWireEnd(end) => format!(" ~{}", point(*end)),
+		WireSegments::Segments(segments) => {
+			let mut result = String::new();
+			for segment in segments.iter().filter(|segment| segment.length > 0) {
+				_ = write!(result, " {}{}", direction(segment.direction), segment.length);
+			}
+			result
+		},
+	};
+
+	let mut line = format!("wire {}{segments} color={color} comment={}", point(*start), quote(comment));
+	if let WireExtra::V6 { width } = extra {
+		_ = write!(line, " width={width}");
+	}
+	line
+}
+
+pub(crate) fn direction(direction: WireDirection) -> &'static str {
+	match direction {
+		WireDirection::Right => "R",
+		WireDirection::DownRight => "DR",
+		WireDirection::Down => "D",
+		WireDirection::DownLeft => "DL",
+		WireDirection::Left => "L",
+		WireDirection::UpLeft => "UL",
+		WireDirection::Up => "U",
+		WireDirection::UpRight => "UR",
+	}
+}
+
+pub(crate) fn point(Point { x, y }: Point) -> String {
+	format!("{x},{y}")
+}
+
+pub(crate) fn list<T>(items: impl Iterator<Item = T>) -> String where T: std::fmt::Display {
+	let items: Vec<_> = items.map(|item| item.to_string()).collect();
+	format!("[{}]", items.join(" "))
+}
+
+fn pairs(slice: &Slice<'_, u16, (i64, i64)>) -> String {
+	list(items(slice).map(|(a, b)| format!("({a} {b})")))
+}
+
+fn programs<'a>(slice: &Slice<'a, u16, (std::borrow::Cow<'a, str>, std::borrow::Cow<'a, str>)>) -> String {
+	list(items(slice).map(|(a, b)| format!("({} {})", quote(&a), quote(&b))))
+}
+
+fn items<'a, 'this, N, T>(slice: &'this Slice<'a, N, T>) -> impl Iterator<Item = T> + 'this where T: Parse<'a> + Clone + 'this {
+	slice.iter().map(Either::into_inner)
+}
+
+/// Quotes a string, escaping `\`, `"` and control characters.
+pub(crate) fn quote(s: &str) -> String {
+	use std::fmt::Write;
+
+	let mut result = String::with_capacity(s.len() + 2);
+	result.push('"');
+	for c in s.chars() {
+		match c {
+			'\\' => result.push_str(r"\\"),
+			'"' => result.push_str(r#"\""#),
+			'\n' => result.push_str(r"\n"),
+			'\r' => result.push_str(r"\r"),
+			'\t' => result.push_str(r"\t"),
+			c if c.is_control() => _ = write!(result, r"\u{{{:x}}}", u32::from(c)),
+			c => result.push(c),
+		}
+	}
+	result.push('"');
+	result
+}
@@ -1,9 +1,16 @@
 use crate::parser::{
+	Encode,
+	Input,
+	IntoOwned,
 	Parse,
+	ParseError,
+	ParseErrorKind,
+	ParseResultExt,
 	Slice,
 };
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct CircuitData<'a> {
 	pub custom_id: i64,
 	pub hub_id: u32,
@@ -12,68 +19,43 @@ pub struct CircuitData<'a> {
 	pub menu_visible: bool,
 	pub clock_speed: u64,
 	pub dependencies: Slice<'a, u16, i64>,
-	pub description: &'a str,
+	pub description: std::borrow::Cow<'a, str>,
 	pub camera_position: Point,
 	pub synced: SyncState,
 	pub player_data: Slice<'a, u16, u8>,
-	pub hub_description: &'a str,
+	pub hub_description: std::borrow::Cow<'a, str>,
 	pub components: Slice<'a, u64, Component<'a>>,
 	pub wires: Slice<'a, u64, Wire<'a>>,
 }
 
 impl CircuitData<'_> {
-	pub fn check_wire_dupes(&self) {
-		let mut wires: std::collections::BTreeMap<_, _> = Default::default();
-		let mut found_dupes = false;
-		for wire in &self.wires {
-			let wire = wire.as_inner_ref();
-			let WireSegments::Segments(segments) = &wire.segments else { continue; };
-
-			let start = wire.start;
-			let mut end = start;
-			for segment in segments {
-				let segment = segment.as_inner_ref();
-				let len = i16::from(segment.length);
-				match segment.direction {
-					WireDirection::Right => end.x += len,
-					WireDirection::DownRight => {end.x += len; end.y += len; },
-					WireDirection::Down => end.y += len,
-					WireDirection::DownLeft => { end.x -= len; end.y += len; },
-					WireDirection::Left => end.x -= len,
-					WireDirection::UpLeft => { end.x -= len; end.y -= len; },
-					WireDirection::Up => end.y -= len,
-					WireDirection::UpRight => { end.x += len; end.y -= len; },
-				}
-			}
-
-			if let Some(previous_wire) = wires.insert((start.min(end), start.max(end)), format!("{wire:?}")) {
-				println!("{wire:?} overlaps with {previous_wire}");
-				found_dupes = true;
-			}
-		}
-		assert!(!found_dupes);
+	pub fn encode_final(&self) -> Vec<u8> {
+		let mut raw = vec![];
+		self.encode(&mut raw);
+		let mut raw = snap::raw::Encoder::new().compress_vec(&raw).unwrap();
+		raw.insert(0, 7);
+		raw
 	}
 }
 
-impl<'a> Parse<'a> for CircuitData<'a> {
-	fn parse(input: &mut &'a [u8]) -> Self {
-		let custom_id = <_>::parse(input);
-		let hub_id = <_>::parse(input);
-		let gate = <_>::parse(input);
-		let delay = <_>::parse(input);
-		let menu_visible = <_>::parse(input);
-		let clock_speed = <_>::parse(input);
-		let dependencies = Slice::parse_with_length_prefix(input);
-		let description = <_>::parse(input);
-		let camera_position = <_>::parse(input);
-		let synced = u8::parse(input).into();
-		_ = u16::parse(input);
-		let player_data = Slice::parse_with_length_prefix(input);
-		let hub_description = <_>::parse(input);
-		let components = Slice::parse_with_length_prefix(input);
-		let wires = Slice::parse_with_length_prefix(input);
-
-		Self {
+impl<'a> CircuitData<'a> {
+	/// Parses the fields that come before the components, and leaves `components` and `wires` empty.
+	pub(crate) fn parse_header(input: &mut Input<'a>) -> Result<Self, ParseError> {
+		let custom_id = <_>::parse(input).field("CircuitData.custom_id")?;
+		let hub_id = <_>::parse(input).field("CircuitData.hub_id")?;
+		let gate = <_>::parse(input).field("CircuitData.gate")?;
+		let delay = <_>::parse(input).field("CircuitData.delay")?;
+		let menu_visible = <_>::parse(input).field("CircuitData.menu_visible")?;
+		let clock_speed = <_>::parse(input).field("CircuitData.clock_speed")?;
+		let dependencies = Slice::parse_with_length_prefix(input).field("CircuitData.dependencies")?;
+		let description = <_>::parse(input).field("CircuitData.description")?;
+		let camera_position = <_>::parse(input).field("CircuitData.camera_position")?;
+		let synced = <_>::parse(input).field("CircuitData.synced")?;
+		_ = u16::parse(input).field("CircuitData")?;
+		let player_data = Slice::parse_with_length_prefix(input).field("CircuitData.player_data")?;
+		let hub_description = <_>::parse(input).field("CircuitData.hub_description")?;
+
+		Ok(Self {
 			custom_id,
 			hub_id,
 			gate,
@@ -86,29 +68,90 @@ impl<'a> Parse<'a> for CircuitData<'a> {
 			synced,
 			player_data,
 			hub_description,
-			components,
-			wires,
+			components: vec![].into(),
+			wires: vec![].into(),
+		})
+	}
+}
+
+impl<'a> Parse<'a> for CircuitData<'a> {
+	fn parse(input: &mut Input<'a>) -> Result<Self, ParseError> {
+		let header = Self::parse_header(input)?;
+		let components = Slice::parse_with_length_prefix(input).field("CircuitData.components")?;
+		let wires = Slice::parse_with_length_prefix(input).field("CircuitData.wires")?;
+		Ok(Self { components, wires, ..header })
+	}
+}
+
+impl Encode for CircuitData<'_> {
+	fn encode(&self, out: &mut Vec<u8>) {
+		self.custom_id.encode(out);
+		self.hub_id.encode(out);
+		self.gate.encode(out);
+		self.delay.encode(out);
+		self.menu_visible.encode(out);
+		self.clock_speed.encode(out);
+		self.dependencies.encode_with_length_prefix(out);
+		self.description.encode(out);
+		self.camera_position.encode(out);
+		self.synced.encode(out);
+		0_u16.encode(out);
+		self.player_data.encode_with_length_prefix(out);
+		self.hub_description.encode(out);
+		self.components.encode_with_length_prefix(out);
+		self.wires.encode_with_length_prefix(out);
+	}
+}
+
+impl IntoOwned for CircuitData<'_> {
+	type Owned = CircuitData<'static>;
+
+	fn into_owned(self) -> Self::Owned {
+		CircuitData {
+			custom_id: self.custom_id,
+			hub_id: self.hub_id,
+			gate: self.gate,
+			delay: self.delay,
+			menu_visible: self.menu_visible,
+			clock_speed: self.clock_speed,
+			dependencies: self.dependencies.into_owned(),
+			description: self.description.into_owned().into(),
+			camera_position: self.camera_position,
+			synced: self.synced,
+			player_data: self.player_data.into_owned(),
+			hub_description: self.hub_description.into_owned().into(),
+			components: self.components.into_owned(),
+			wires: self.wires.into_owned(),
 		}
 	}
 }
 
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Point {
 	pub x: i16,
 	pub y: i16,
 }
 
 impl Parse<'_> for Point {
-	fn parse(input: &mut &[u8]) -> Self {
-		Self {
-			x: i16::parse(input),
-			y: i16::parse(input),
-		}
+	fn parse(input: &mut Input<'_>) -> Result<Self, ParseError> {
+		Ok(Self {
+			x: i16::parse(input).field("Point.x")?,
+			y: i16::parse(input).field("Point.y")?,
+		})
+	}
+}
+
+impl Encode for Point {
+	fn encode(&self, out: &mut Vec<u8>) {
+		self.x.encode(out);
+		self.y.encode(out);
 	}
 }
 
 enum_impl_from! {
 	#[derive(Clone, Copy, Debug)]
+	#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 	pub enum SyncState: u8 {
 		Unsynced = 0,
 		Synced = 1,
@@ -117,12 +160,13 @@ enum_impl_from! {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Component<'a> {
 	pub kind: ComponentKind,
 	pub position: Point,
 	pub rotation: u8,
 	pub permanent_id: u64,
-	pub custom_string: &'a str,
+	pub custom_string: std::borrow::Cow<'a, str>,
 	pub settings: Slice<'a, u16, u64>,
 	pub buffer_size: i64,
 	pub ui_order: i16,
@@ -133,27 +177,66 @@ pub struct Component<'a> {
 }
 
 impl<'a> Parse<'a> for Component<'a> {
-	fn parse(input: &mut &'a [u8]) -> Self {
-		let kind = <_>::parse(input);
-		Self {
+	fn parse(input: &mut Input<'a>) -> Result<Self, ParseError> {
+		let kind = <_>::parse(input).field("Component.kind")?;
+		Ok(Self {
 			kind,
-			position: <_>::parse(input),
-			rotation: <_>::parse(input),
-			permanent_id: <_>::parse(input),
-			custom_string: <_>::parse(input),
-			settings: Slice::parse_with_length_prefix(input),
-			buffer_size: <_>::parse(input),
-			ui_order: <_>::parse(input),
-			word_size: <_>::parse(input),
-			discarded: <_>::parse(input),
-			custom_data: matches!(kind, ComponentKind::Custom).then(|| <_>::parse(input)),
-			assembler_data: matches!(kind, ComponentKind::Assembler | ComponentKind::ProbeMemoryBit | ComponentKind::ImmProbeMemoryBit | ComponentKind::ProbeMemoryWord | ComponentKind::StaticValue | ComponentKind::Console | ComponentKind::PixelScreen).then(|| <_>::parse(input)),
+			position: <_>::parse(input).field("Component.position")?,
+			rotation: <_>::parse(input).field("Component.rotation")?,
+			permanent_id: <_>::parse(input).field("Component.permanent_id")?,
+			custom_string: <_>::parse(input).field("Component.custom_string")?,
+			settings: Slice::parse_with_length_prefix(input).field("Component.settings")?,
+			buffer_size: <_>::parse(input).field("Component.buffer_size")?,
+			ui_order: <_>::parse(input).field("Component.ui_order")?,
+			word_size: <_>::parse(input).field("Component.word_size")?,
+			discarded: <_>::parse(input).field("Component.discarded")?,
+			custom_data: matches!(kind, ComponentKind::Custom).then(|| <_>::parse(input)).transpose().field("Component.custom_data")?,
+			assembler_data: kind.has_assembler_data().then(|| <_>::parse(input)).transpose().field("Component.assembler_data")?,
+		})
+	}
+}
+
+impl Encode for Component<'_> {
+	fn encode(&self, out: &mut Vec<u8>) {
+		self.kind.encode(out);
+		self.position.encode(out);
+		self.rotation.encode(out);
+		self.permanent_id.encode(out);
+		self.custom_string.encode(out);
+		self.settings.encode_with_length_prefix(out);
+		self.buffer_size.encode(out);
+		self.ui_order.encode(out);
+		self.word_size.encode(out);
+		self.discarded.encode(out);
+		self.custom_data.encode(out);
+		self.assembler_data.encode(out);
+	}
+}
+
+impl IntoOwned for Component<'_> {
+	type Owned = Component<'static>;
+
+	fn into_owned(self) -> Self::Owned {
+		Component {
+			kind: self.kind,
+			position: self.position,
+			rotation: self.rotation,
+			permanent_id: self.permanent_id,
+			custom_string: self.custom_string.into_owned().into(),
+			settings: self.settings.into_owned(),
+			buffer_size: self.buffer_size,
+			ui_order: self.ui_order,
+			word_size: self.word_size,
+			discarded: self.discarded,
+			custom_data: self.custom_data.into_owned(),
+			assembler_data: self.assembler_data.into_owned(),
 		}
 	}
 }
 
 enum_impl_from! {
 	#[derive(Clone, Copy, Debug)]
+	#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 	pub enum ComponentKind: u16 {
 		None = 0,
 		Off = 1,
@@ -277,7 +360,14 @@ enum_impl_from! {
 	}
 }
 
+impl ComponentKind {
+	pub(crate) fn has_assembler_data(self) -> bool {
+		matches!(self, Self::Assembler | Self::ProbeMemoryBit | Self::ImmProbeMemoryBit | Self::ProbeMemoryWord | Self::StaticValue | Self::Console | Self::PixelScreen)
+	}
+}
+
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct CustomCompData<'a> {
 	pub id: i64,
 	pub static_states: Slice<'a, u16, (i64, i64)>,
@@ -285,107 +375,260 @@ pub struct CustomCompData<'a> {
 }
 
 impl<'a> Parse<'a> for CustomCompData<'a> {
-	fn parse(input: &mut &'a [u8]) -> Self {
-		Self {
-			id: <_>::parse(input),
-			static_states: Slice::parse_with_length_prefix(input),
-			linked_word_sizes: Slice::parse_with_length_prefix(input),
+	fn parse(input: &mut Input<'a>) -> Result<Self, ParseError> {
+		Ok(Self {
+			id: <_>::parse(input).field("CustomCompData.id")?,
+			static_states: Slice::parse_with_length_prefix(input).field("CustomCompData.static_states")?,
+			linked_word_sizes: Slice::parse_with_length_prefix(input).field("CustomCompData.linked_word_sizes")?,
+		})
+	}
+}
+
+impl Encode for CustomCompData<'_> {
+	fn encode(&self, out: &mut Vec<u8>) {
+		self.id.encode(out);
+		self.static_states.encode_with_length_prefix(out);
+		self.linked_word_sizes.encode_with_length_prefix(out);
+	}
+}
+
+impl IntoOwned for CustomCompData<'_> {
+	type Owned = CustomCompData<'static>;
+
+	fn into_owned(self) -> Self::Owned {
+		CustomCompData {
+			id: self.id,
+			static_states: self.static_states.into_owned(),
+			linked_word_sizes: self.linked_word_sizes.into_owned(),
 		}
 	}
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct AssemblerInfo<'a> {
-	pub programs: Slice<'a, u16, (&'a str, &'a str)>,
+	pub programs: Slice<'a, u16, (std::borrow::Cow<'a, str>, std::borrow::Cow<'a, str>)>,
 	pub watched_components: Slice<'a, u16, WatchedComponent<'a>>,
 }
 
 impl<'a> Parse<'a> for AssemblerInfo<'a> {
-	fn parse(input: &mut &'a [u8]) -> Self {
-		Self {
-			programs: Slice::parse_with_length_prefix(input),
-			watched_components: Slice::parse_with_length_prefix(input),
+	fn parse(input: &mut Input<'a>) -> Result<Self, ParseError> {
+		Ok(Self {
+			programs: Slice::parse_with_length_prefix(input).field("AssemblerInfo.programs")?,
+			watched_components: Slice::parse_with_length_prefix(input).field("AssemblerInfo.watched_components")?,
+		})
+	}
+}
+
+impl Encode for AssemblerInfo<'_> {
+	fn encode(&self, out: &mut Vec<u8>) {
+		self.programs.encode_with_length_prefix(out);
+		self.watched_components.encode_with_length_prefix(out);
+	}
+}
+
+impl IntoOwned for AssemblerInfo<'_> {
+	type Owned = AssemblerInfo<'static>;
+
+	fn into_owned(self) -> Self::Owned {
+		AssemblerInfo {
+			programs: self.programs.into_owned(),
+			watched_components: self.watched_components.into_owned(),
 		}
 	}
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct WatchedComponent<'a> {
 	pub permanent_id: i64,
 	pub inner_id: i64,
-	pub name: &'a str,
+	pub name: std::borrow::Cow<'a, str>,
 }
 
 impl<'a> Parse<'a> for WatchedComponent<'a> {
-	fn parse(input: &mut &'a [u8]) -> Self {
-		Self {
-			permanent_id: <_>::parse(input),
-			inner_id: <_>::parse(input),
-			name: <_>::parse(input),
+	fn parse(input: &mut Input<'a>) -> Result<Self, ParseError> {
+		Ok(Self {
+			permanent_id: <_>::parse(input).field("WatchedComponent.permanent_id")?,
+			inner_id: <_>::parse(input).field("WatchedComponent.inner_id")?,
+			name: <_>::parse(input).field("WatchedComponent.name")?,
+		})
+	}
+}
+
+impl Encode for WatchedComponent<'_> {
+	fn encode(&self, out: &mut Vec<u8>) {
+		self.permanent_id.encode(out);
+		self.inner_id.encode(out);
+		self.name.encode(out);
+	}
+}
+
+impl IntoOwned for WatchedComponent<'_> {
+	type Owned = WatchedComponent<'static>;
+
+	fn into_owned(self) -> Self::Owned {
+		WatchedComponent {
+			permanent_id: self.permanent_id,
+			inner_id: self.inner_id,
+			name: self.name.into_owned().into(),
 		}
 	}
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Wire<'a> {
 	pub color: u8,
-	pub comment: &'a str,
+	pub comment: std::borrow::Cow<'a, str>,
 	pub start: Point,
 	pub segments: WireSegments<'a>,
 }
 
 impl<'a> Parse<'a> for Wire<'a> {
-	fn parse(input: &mut &'a [u8]) -> Self {
-		Self {
-			color: <_>::parse(input),
-			comment: <_>::parse(input),
-			start: <_>::parse(input),
-			segments: <_>::parse(input),
+	fn parse(input: &mut Input<'a>) -> Result<Self, ParseError> {
+		Ok(Self {
+			color: <_>::parse(input).field("Wire.color")?,
+			comment: <_>::parse(input).field("Wire.comment")?,
+			start: <_>::parse(input).field("Wire.start")?,
+			segments: <_>::parse(input).field("Wire.segments")?,
+		})
+	}
+}
+
+impl Encode for Wire<'_> {
+	fn encode(&self, out: &mut Vec<u8>) {
+		self.color.encode(out);
+		self.comment.encode(out);
+		self.start.encode(out);
+		self.segments.encode(out);
+	}
+}
+
+impl IntoOwned for Wire<'_> {
+	type Owned = Wire<'static>;
+
+	fn into_owned(self) -> Self::Owned {
+		Wire {
+			color: self.color,
+			comment: self.comment.into_owned().into(),
+			start: self.start,
+			segments: self.segments.into_owned(),
 		}
 	}
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum WireSegments<'a> {
 	TeleWireEnd(Point),
 	Segments(Slice<'a, u64, WireSegment>),
 }
 
 impl<'a> Parse<'a> for WireSegments<'a> {
-	fn parse(input: &mut &'a [u8]) -> Self {
-		if input[0] == 0x20 {
-			_ = u8::parse(input);
-			Self::TeleWireEnd(<_>::parse(input))
+	fn parse(input: &mut Input<'a>) -> Result<Self, ParseError> {
+		let &first = input.bytes.first().ok_or_else(|| input.error(ParseErrorKind::UnexpectedEof))?;
+		if first == 0x20 {
+			_ = u8::parse(input)?;
+			Ok(Self::TeleWireEnd(<_>::parse(input)?))
 		}
 		else {
 			#[allow(clippy::verbose_bit_mask)]
-			let mut segments = &input[..input.iter().position(|&b| b & 0x1f == 0).unwrap()];
-			*input = &input[(segments.len() + 1)..];
-			Self::Segments(Slice::parse_until_end(&mut segments))
+			let segments_end_pos =
+				input.bytes.iter().position(|&b| b & 0x1f == 0)
+				.ok_or_else(|| input.error(ParseErrorKind::UnexpectedEof))? + 1;
+			let mut segments = input.take(segments_end_pos)?;
+			Ok(Self::Segments(Slice::parse_until_end(&mut segments)?))
+		}
+	}
+}
+
+impl Encode for WireSegments<'_> {
+	fn encode(&self, out: &mut Vec<u8>) {
+		match self {
+			Self::TeleWireEnd(point) => {
+				out.push(0x20);
+				point.encode(out);
+			},
+
+			Self::Segments(segments) => segments.encode_without_length_prefix(out),
+		}
+	}
+}
+
+impl IntoOwned for WireSegments<'_> {
+	type Owned = WireSegments<'static>;
+
+	fn into_owned(self) -> Self::Owned {
+		match self {
+			Self::TeleWireEnd(end) => WireSegments::TeleWireEnd(end),
+			Self::Segments(segments) => WireSegments::Segments(segments.into_owned()),
 		}
 	}
 }
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(try_from = "UncheckedWireSegment"))]
 pub struct WireSegment {
+	/// At most 31, since the length is encoded in five bits.
 	pub length: u8,
 	pub direction: WireDirection,
 }
 
+/// A [`WireSegment`] deserialized before its length is checked.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct UncheckedWireSegment {
+	length: u8,
+	direction: WireDirection,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<UncheckedWireSegment> for WireSegment {
+	type Error = String;
+
+	fn try_from(UncheckedWireSegment { length, direction }: UncheckedWireSegment) -> Result<Self, Self::Error> {
+		if length > 0x1f {
+			return Err(format!("wire segment length {length} is more than 31"));
+		}
+
+		Ok(Self { length, direction })
+	}
+}
+
 impl<'a> Parse<'a> for WireSegment {
-	fn parse(input: &mut &'a [u8]) -> Self {
-		let ws = u8::parse(input);
+	fn parse(input: &mut Input<'a>) -> Result<Self, ParseError> {
+		let ws = u8::parse(input).field("WireSegment")?;
 		let length = ws & 0x1f;
-		let direction = WireDirection::from(ws >> 5);
-		Self {
+		let direction = WireDirection::try_from(ws >> 5).expect("3-bit value is always a valid WireDirection");
+		Ok(Self {
 			length,
 			direction,
-		}
+		})
+	}
+}
+
+impl Encode for WireSegment {
+	fn encode(&self, out: &mut Vec<u8>) {
+		let length = self.length & 0x1f;
+		assert_eq!(self.length, length);
+		let direction = u8::from(self.direction);
+		let ws = length | (direction << 5);
+		ws.encode(out);
+	}
+}
+
+impl IntoOwned for WireSegment {
+	type Owned = Self;
+
+	fn into_owned(self) -> Self::Owned {
+		self
 	}
 }
 
 enum_impl_from! {
 	#[derive(Clone, Copy, Debug)]
+	#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 	pub enum WireDirection: u8 {
 		Right = 0,
 		DownRight = 1,
@@ -397,3 +640,48 @@ enum_impl_from! {
 		UpRight = 7,
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use crate::{Encode, Parse, testing};
+	use super::{CircuitData, WireDirection, WireSegments};
+
+	#[test]
+	fn round_trip() {
+		let mut bytes = vec![];
+		testing::v7_circuit().encode(&mut bytes);
+		assert_eq!(bytes.windows(testing::SEGMENTS.len()).filter(|window| *window == testing::SEGMENTS).count(), 1);
+
+		let circuit = CircuitData::parse_from(&bytes).unwrap();
+		let WireSegments::Segments(segments) = circuit.wires.get(0).unwrap().into_inner().segments else { panic!("expected segments") };
+		let end = segments.get(3).unwrap().into_inner();
+		assert_eq!((end.length, u8::from(end.direction)), (0, u8::from(WireDirection::DownLeft)));
+
+		let mut encoded = vec![];
+		circuit.encode(&mut encoded);
+		assert_eq!(encoded, bytes);
+
+		let mut again = vec![];
+		CircuitData::parse_from(&encoded).unwrap().encode(&mut again);
+		assert_eq!(again, bytes);
+	}
+	#[cfg(feature = "serde")]
+	#[test]
+	fn json() {
+		let circuit = testing::v7_circuit();
+		let json = serde_json::to_string(&circuit).unwrap();
+		let deserialized: CircuitData<'_> = serde_json::from_str(&json).unwrap();
+		let crate::Save::V7(decoded) = crate::from_bytes(&deserialized.encode_final()).unwrap() else { panic!("expected a v7 save") };
+
+		let mut bytes = vec![];
+		circuit.encode(&mut bytes);
+		let mut encoded = vec![];
+		decoded.encode(&mut encoded);
+		assert_eq!(encoded, bytes);
+
+		let too_long = json.replacen(r#""length":3,"#, r#""length":32,"#, 1);
+		assert_ne!(too_long, json);
+		let err = serde_json::from_str::<CircuitData<'_>>(&too_long).unwrap_err();
+		assert!(err.to_string().contains("wire segment length 32 is more than 31"), "{err}");
+	}
+}
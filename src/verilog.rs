@@ -0,0 +1,448 @@
+//! Export of v10 circuits as synthesizable Verilog.
+//!
+//! A circuit becomes a module with a `clk` input, an `in_<permanent_id>` input for each `LevelInput*` component
+//! and an `out_<permanent_id>` output for each `LevelOutput*` component. Every net and every pin becomes a wire,
+//! and every component becomes the logic that drives its output pins. Sequential components update on the rising edge of `clk`,
+//! which corresponds to one [`crate::sim::Simulation::tick`].
+//!
+//! Custom components whose schematics are available become instances of a `custom_<custom_id>` module,
+//! which is exported after the module that uses it.
+//!
+//! A net that no output drives is left unassigned, and each input pin on it is reported as an [`ExportWarning`].
+//! The game and [`crate::sim`] treat such inputs as 0, but in a circuit made in the game they are usually a sign that
+//! [`crate::layout`] does not know where its pins are.
+
+use crate::{
+	layout,
+	netlist::{
+		self,
+		CustomPins,
+		Netlist,
+	},
+	v10,
+};
+
+#[derive(Debug)]
+pub enum ExportError {
+	/// There is no Verilog equivalent for the component's kind.
+	UnsupportedComponent { permanent_id: u64, kind: v10::ComponentKind },
+}
+
+impl std::fmt::Display for ExportError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::UnsupportedComponent { permanent_id, kind } => write!(f, "component {permanent_id} of kind {kind:?} cannot be exported"),
+		}
+	}
+}
+
+impl std::error::Error for ExportError {}
+
+#[derive(Clone, Debug)]
+pub enum ExportWarning {
+	/// No output drives the net of the input pin, so the exported wire is left undriven.
+	UndrivenInput { module: String, permanent_id: u64, index: usize },
+}
+
+impl std::fmt::Display for ExportWarning {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::UndrivenInput { module, permanent_id, index } => write!(f, "input {index} of component {permanent_id} in module {module} is not driven"),
+		}
+	}
+}
+
+/// The result of [`export`].
+#[derive(Clone, Debug)]
+pub struct Export {
+	pub verilog: String,
+	pub warnings: Vec<ExportWarning>,
+}
+
+/// Exports the circuit as a module with the given name, followed by the modules of the custom components it uses.
+///
+/// `schematics` holds the schematics of custom components by their `custom_id`.
+/// Custom components whose schematic is not in it are left out of the module, with a comment in their place.
+pub fn export(
+	circuit: &v10::CircuitData<'_>,
+	name: &str,
+	schematics: &std::collections::BTreeMap<i64, v10::CircuitData<'_>>,
+) -> Result<Export, ExportError> {
+	let mut out = String::new();
+	let mut warnings = vec![];
+
+	let mut used = vec![];
+	write_module(&mut out, &mut warnings, &identifier(name), circuit, schematics, &mut used)?;
+
+	let mut exported: std::collections::BTreeSet<_> = Default::default();
+	while let Some(custom_id) = used.pop() {
+		if !exported.insert(custom_id) {
+			continue;
+		}
+
+		let schematic = &schematics[&custom_id];
+		out.push('\n');
+		write_module(&mut out, &mut warnings, &custom_module_name(custom_id), schematic, schematics, &mut used)?;
+	}
+
+	Ok(Export { verilog: out, warnings })
+}
+
+fn write_module(
+	out: &mut String,
+	warnings: &mut Vec<ExportWarning>,
+	name: &str,
+	circuit: &v10::CircuitData<'_>,
+	schematics: &std::collections::BTreeMap<i64, v10::CircuitData<'_>>,
+	used: &mut Vec<i64>,
+) -> Result<(), ExportError> {
+	use std::fmt::Write;
+
+	let netlist = Netlist::with_custom_components(circuit, |custom_id| schematics.get(&custom_id).map(CustomPins::of));
+
+	let ports = CustomPins::of(circuit);
+	_ = writeln!(out, "module {name} (");
+	_ = write!(out, "\tinput wire clk");
+	for &(permanent_id, width) in &ports.inputs {
+		_ = write!(out, ",\n\tinput wire {} in_{permanent_id}", range(width));
+	}
+	for &(permanent_id, width) in &ports.outputs {
+		_ = write!(out, ",\n\toutput wire {} out_{permanent_id}", range(width));
+	}
+	_ = writeln!(out, "\n);");
+
+	for (k, net) in netlist.nets.iter().enumerate() {
+		if net.pins.is_empty() {
+			continue;
+		}
+
+		let width = net.pins.iter().map(|&pin| netlist.pin(pin).width).max().unwrap_or(1);
+		_ = writeln!(out, "\twire {} n{k};", range(width));
+	}
+
+	for (i, component) in netlist.components.iter().enumerate() {
+		for (j, pin) in component.inputs.iter().enumerate() {
+			_ = writeln!(out, "\twire {} c{i}_i{j} = n{};", range(pin.width), pin.net.0);
+		}
+		for (j, pin) in component.outputs.iter().enumerate() {
+			_ = writeln!(out, "\twire {} c{i}_o{j};", range(pin.width));
+		}
+	}
+
+	for (k, net) in netlist.nets.iter().enumerate() {
+		if net.pins.is_empty() {
+			continue;
+		}
+
+		let drivers: Vec<_> =
+			net.pins.iter()
+			.filter(|pin| pin.direction == layout::PinDirection::Output)
+			.map(|pin| format!("c{}_o{}", pin.component, pin.index))
+			.collect();
+		if drivers.is_empty() {
+			warnings.extend(net.pins.iter().map(|pin| ExportWarning::UndrivenInput {
+				module: name.to_owned(),
+				permanent_id: netlist.components[pin.component].permanent_id,
+				index: pin.index,
+			}));
+		}
+		else {
+			_ = writeln!(out, "\tassign n{k} = {};", drivers.join(" | "));
+		}
+	}
+
+	for ((i, component), original) in netlist.components.iter().enumerate().zip(&circuit.components) {
+		let original = original.as_inner_ref();
+
+		if matches!(component.kind, v10::ComponentKind::Custom) {
+			match component.custom_id.filter(|_| component.known_pins) {
+				Some(custom_id) => {
+					write_custom_instance(out, i, custom_id, &CustomPins::of(&schematics[&custom_id]));
+					used.push(custom_id);
+				},
+
+				None => _ = writeln!(out, "\t// component {} is an instance of a custom component whose schematic is not available", component.permanent_id),
+			}
+			continue;
+		}
+
+		if !component.known_pins {
+			return Err(ExportError::UnsupportedComponent { permanent_id: component.permanent_id, kind: component.kind });
+		}
+
+		let constant = original.settings.iter().next().map_or(0, crate::Either::into_inner);
+		write_component(out, i, component, constant);
+	}
+
+	_ = writeln!(out, "endmodule");
+
+	Ok(())
+}
+
+#[allow(clippy::many_single_char_names, clippy::too_many_lines)]
+fn write_component(out: &mut String, i: usize, component: &netlist::Component, constant: u64) {
+	use std::fmt::Write;
+
+	let w = component.word_size;
+	let permanent_id = component.permanent_id;
+	let state = format!("c{i}_state");
+	let a = format!("c{i}_i0");
+	let b = format!("c{i}_i1");
+	let c = format!("c{i}_i2");
+	let o0 = format!("c{i}_o0");
+	let o1 = format!("c{i}_o1");
+	let inputs_msb_first = (0..component.inputs.len()).rev().map(|j| format!("c{i}_i{j}")).collect::<Vec<_>>().join(", ");
+
+	match component.kind {
+		v10::ComponentKind::Off => assign(out, &o0, "1'b0"),
+		v10::ComponentKind::On => assign(out, &o0, "1'b1"),
+
+		v10::ComponentKind::NotBit |
+		v10::ComponentKind::NotWord => assign(out, &o0, &format!("~{a}")),
+		v10::ComponentKind::AndBit |
+		v10::ComponentKind::AndWord => assign(out, &o0, &format!("{a} & {b}")),
+		v10::ComponentKind::And3Bit => assign(out, &o0, &format!("{a} & {b} & {c}")),
+		v10::ComponentKind::NandBit |
+		v10::ComponentKind::NandWord => assign(out, &o0, &format!("~({a} & {b})")),
+		v10::ComponentKind::OrBit |
+		v10::ComponentKind::OrWord => assign(out, &o0, &format!("{a} | {b}")),
+		v10::ComponentKind::Or3Bit => assign(out, &o0, &format!("{a} | {b} | {c}")),
+		v10::ComponentKind::NorBit |
+		v10::ComponentKind::NorWord => assign(out, &o0, &format!("~({a} | {b})")),
+		v10::ComponentKind::XorBit |
+		v10::ComponentKind::XorWord => assign(out, &o0, &format!("{a} ^ {b}")),
+		v10::ComponentKind::XnorBit |
+		v10::ComponentKind::XnorWord => assign(out, &o0, &format!("~({a} ^ {b})")),
+
+		v10::ComponentKind::SwitchBit |
+		v10::ComponentKind::SwitchWord => assign(out, &o0, &format!("{a} ? {b} : 0")),
+
+		v10::ComponentKind::DelayLineBit |
+		v10::ComponentKind::DelayLineWord => {
+			_ = writeln!(out, "\treg {} {state} = 0;", range(component.outputs[0].width));
+			assign(out, &o0, &state);
+			_ = writeln!(out, "\talways @(posedge clk) {state} <= {a};");
+		},
+
+		v10::ComponentKind::RegisterBit |
+		v10::ComponentKind::RegisterWord => {
+			_ = writeln!(out, "\treg {} {state} = 0;", range(component.outputs[0].width));
+			assign(out, &o0, &format!("{a} ? {state} : 0"));
+			_ = writeln!(out, "\talways @(posedge clk) if ({b}) {state} <= {c};");
+		},
+
+		v10::ComponentKind::Counter => {
+			_ = writeln!(out, "\treg {} {state} = 0;", range(w));
+			assign(out, &o0, &state);
+			_ = writeln!(out, "\talways @(posedge clk) {state} <= {a} ? {b} : {state} + 1;");
+		},
+
+		v10::ComponentKind::RamFast => {
+			let memory = format!("c{i}_memory");
+			let address = format!("{c}[{}:0]", ram_address_bits(w) - 1);
+			let d = format!("c{i}_i3");
+			_ = writeln!(out, "\treg {} {memory} [0:{}];", range(w), (1_u64 << ram_address_bits(w)) - 1);
+			assign(out, &o0, &format!("{a} ? {memory}[{address}] : 0"));
+			_ = writeln!(out, "\talways @(posedge clk) if ({b}) {memory}[{address}] <= {d};");
+		},
+
+		v10::ComponentKind::FullAdder |
+		v10::ComponentKind::Add => assign(out, &format!("{{{o1}, {o0}}}"), &format!("{a} + {b} + {c}")),
+
+		v10::ComponentKind::MakerBit2 |
+		v10::ComponentKind::MakerBit4 |
+		v10::ComponentKind::MakerBit8 => assign(out, &o0, &format!("{{{inputs_msb_first}}}")),
+
+		v10::ComponentKind::SplitterBit2 |
+		v10::ComponentKind::SplitterBit4 |
+		v10::ComponentKind::SplitterBit8 =>
+			for j in 0..component.outputs.len() {
+				assign(out, &format!("c{i}_o{j}"), &format!("{a}[{j}]"));
+			},
+
+		v10::ComponentKind::Equal => assign(out, &o0, &format!("{a} == {b}")),
+		v10::ComponentKind::LessU => assign(out, &o0, &format!("{a} < {b}")),
+		v10::ComponentKind::LessS => assign(out, &o0, &format!("$signed({a}) < $signed({b})")),
+
+		v10::ComponentKind::Neg => assign(out, &o0, &format!("-{a}")),
+		v10::ComponentKind::Inc => assign(out, &o0, &format!("{a} + 1")),
+		v10::ComponentKind::Mul => assign(out, &format!("{{{o1}, {o0}}}"), &format!("{a} * {b}")),
+
+		v10::ComponentKind::Div => {
+			assign(out, &o0, &format!("{b} == 0 ? 0 : {a} / {b}"));
+			assign(out, &o1, &format!("{b} == 0 ? 0 : {a} % {b}"));
+		},
+
+		v10::ComponentKind::Mod => assign(out, &o0, &format!("{b} == 0 ? 0 : {a} % {b}")),
+
+		v10::ComponentKind::Lsl => assign(out, &o0, &format!("{a} << {b}")),
+		v10::ComponentKind::Lsr => assign(out, &o0, &format!("{a} >> {b}")),
+		v10::ComponentKind::Asr => assign(out, &o0, &format!("$signed({a}) >>> {b}")),
+		v10::ComponentKind::Rol => assign(out, &o0, &format!("({a} << ({b} % {w})) | ({a} >> ({w} - {b} % {w}))")),
+		v10::ComponentKind::Ror => assign(out, &o0, &format!("({a} >> ({b} % {w})) | ({a} << ({w} - {b} % {w}))")),
+
+		v10::ComponentKind::Clz |
+		v10::ComponentKind::Ctz => {
+			let count = format!("c{i}_count");
+			let k = format!("c{i}_k");
+			_ = writeln!(out, "\treg {} {count};", range(w));
+			_ = writeln!(out, "\tinteger {k};");
+			assign(out, &o0, &count);
+			_ = writeln!(out, "\talways @* begin");
+			_ = writeln!(out, "\t\t{count} = {w};");
+			if matches!(component.kind, v10::ComponentKind::Clz) {
+				_ = writeln!(out, "\t\tfor ({k} = 0; {k} < {w}; {k} = {k} + 1) if ({a}[{k}]) {count} = {} - {k};", w - 1);
+			}
+			else {
+				_ = writeln!(out, "\t\tfor ({k} = {}; {k} >= 0; {k} = {k} - 1) if ({a}[{k}]) {count} = {k};", w - 1);
+			}
+			_ = writeln!(out, "\tend");
+		},
+
+		v10::ComponentKind::Mux => assign(out, &o0, &format!("{a} ? {c} : {b}")),
+
+		v10::ComponentKind::Decoder1 |
+		v10::ComponentKind::Decoder2 |
+		v10::ComponentKind::Decoder3 =>
+			for j in 0..component.outputs.len() {
+				assign(out, &format!("c{i}_o{j}"), &format!("{{{inputs_msb_first}}} == {j}"));
+			},
+
+		v10::ComponentKind::Constant => assign(out, &o0, &format!("{w}'d{}", constant & (u64::MAX >> (64 - w)))),
+
+		v10::ComponentKind::LevelInput1 |
+		v10::ComponentKind::LevelInputWord => assign(out, &o0, &format!("in_{permanent_id}")),
+
+		v10::ComponentKind::LevelInput2Pin |
+		v10::ComponentKind::LevelInput3Pin |
+		v10::ComponentKind::LevelInput4Pin =>
+			for j in 0..component.outputs.len() {
+				assign(out, &format!("c{i}_o{j}"), &format!("in_{permanent_id}[{j}]"));
+			},
+
+		v10::ComponentKind::LevelOutput1 |
+		v10::ComponentKind::LevelOutputWord |
+		v10::ComponentKind::LevelOutput2Pin |
+		v10::ComponentKind::LevelOutput3Pin |
+		v10::ComponentKind::LevelOutput4Pin => assign(out, &format!("out_{permanent_id}"), &format!("{{{inputs_msb_first}}}")),
+
+		_ => (),
+	}
+}
+
+fn assign(out: &mut String, lhs: &str, rhs: &str) {
+	use std::fmt::Write;
+
+	_ = writeln!(out, "\tassign {lhs} = {rhs};");
+}
+
+fn write_custom_instance(out: &mut String, i: usize, custom_id: i64, pins: &CustomPins) {
+	use std::fmt::Write;
+
+	_ = write!(out, "\t{} c{i} (\n\t\t.clk(clk)", custom_module_name(custom_id));
+	for (j, &(permanent_id, _)) in pins.inputs.iter().enumerate() {
+		_ = write!(out, ",\n\t\t.in_{permanent_id}(c{i}_i{j})");
+	}
+	for (j, &(permanent_id, _)) in pins.outputs.iter().enumerate() {
+		_ = write!(out, ",\n\t\t.out_{permanent_id}(c{i}_o{j})");
+	}
+	_ = writeln!(out, "\n\t);");
+}
+
+/// RAM components have one word for every address that fits in their word size, up to 2^16 words.
+pub(crate) fn ram_address_bits(word_size: u32) -> u32 {
+	word_size.min(16)
+}
+
+fn custom_module_name(custom_id: i64) -> String {
+	format!("custom_{}", custom_id.cast_unsigned())
+}
+
+fn range(width: u32) -> String {
+	format!("[{}:0]", width.max(1) - 1)
+}
+
+/// Replaces everything that cannot be part of a Verilog identifier with `_`.
+fn identifier(name: &str) -> String {
+	let mut result: String = name.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+	if !result.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+		result.insert(0, '_');
+	}
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		builder::{CircuitBuilder, at},
+		v10,
+	};
+	use super::{ExportWarning, export};
+
+	/// A word-wide register with its load, save and value inputs wired to level inputs, and its output to a level output.
+	#[test]
+	fn register() {
+		let mut builder = CircuitBuilder::new();
+		let register = builder.add(v10::ComponentKind::RegisterWord, at(10, 0));
+		let load = builder.add(v10::ComponentKind::LevelInput1, at(0, 0));
+		let save = builder.add(v10::ComponentKind::LevelInput1, at(0, 2));
+		let value = builder.add(v10::ComponentKind::LevelInputWord, at(0, 4));
+		let out = builder.add(v10::ComponentKind::LevelOutputWord, at(20, 0));
+		builder.wire(load.out(0), register.input(0));
+		builder.wire(save.out(0), register.input(1));
+		builder.wire(value.out(0), register.input(2));
+		builder.wire(register.out(0), out.input(0));
+
+		let export = export(&builder.build(), "register", &Default::default()).unwrap();
+		assert!(export.warnings.is_empty());
+		assert_eq!(
+			export.verilog,
+			"module register (\n\
+			\tinput wire clk,\n\
+			\tinput wire [0:0] in_2,\n\
+			\tinput wire [0:0] in_3,\n\
+			\tinput wire [7:0] in_4,\n\
+			\toutput wire [7:0] out_5\n\
+			);\n\
+			\twire [0:0] n0;\n\
+			\twire [0:0] n1;\n\
+			\twire [7:0] n2;\n\
+			\twire [7:0] n3;\n\
+			\twire [0:0] c0_i0 = n0;\n\
+			\twire [0:0] c0_i1 = n1;\n\
+			\twire [7:0] c0_i2 = n2;\n\
+			\twire [7:0] c0_o0;\n\
+			\twire [0:0] c1_o0;\n\
+			\twire [0:0] c2_o0;\n\
+			\twire [7:0] c3_o0;\n\
+			\twire [7:0] c4_i0 = n3;\n\
+			\tassign n0 = c1_o0;\n\
+			\tassign n1 = c2_o0;\n\
+			\tassign n2 = c3_o0;\n\
+			\tassign n3 = c0_o0;\n\
+			\treg [7:0] c0_state = 0;\n\
+			\tassign c0_o0 = c0_i0 ? c0_state : 0;\n\
+			\talways @(posedge clk) if (c0_i1) c0_state <= c0_i2;\n\
+			\tassign c1_o0 = in_2;\n\
+			\tassign c2_o0 = in_3;\n\
+			\tassign c3_o0 = in_4;\n\
+			\tassign out_5 = {c4_i0};\n\
+			endmodule\n",
+		);
+	}
+
+	#[test]
+	fn undriven_inputs() {
+		let mut builder = CircuitBuilder::new();
+		let not = builder.add(v10::ComponentKind::NotBit, at(0, 0));
+		let out = builder.add(v10::ComponentKind::LevelOutput1, at(10, 0));
+		builder.wire(not.out(0), out.input(0));
+
+		let export = export(&builder.build(), "undriven", &Default::default()).unwrap();
+		assert!(matches!(
+			export.warnings.as_slice(),
+			[ExportWarning::UndrivenInput { module, permanent_id, index: 0 }] if module == "undriven" && *permanent_id == not.permanent_id,
+		));
+		assert!(!export.verilog.contains("= 0;"));
+	}
+}
@@ -0,0 +1,73 @@
+//! Tests of the exit codes of the command-line tool.
+
+mod common;
+
+use common::temp_dir;
+use turing_complete_saves_parser::Save;
+
+/// Writes a save of [`common::not_gate`] to `dir/name`.
+fn write_save(dir: &std::path::Path, name: &str) -> std::path::PathBuf {
+	let path = dir.join(name);
+	std::fs::write(&path, Save::V10(common::not_gate()).encode_final()).unwrap();
+	path
+}
+
+fn exit_code<I, S>(args: I) -> i32 where I: IntoIterator<Item = S>, S: AsRef<std::ffi::OsStr> {
+	common::run(env!("CARGO_BIN_EXE_turing-complete-saves-parser"), args).0
+}
+
+#[test]
+fn success() {
+	let dir = temp_dir("cli-success");
+	let a = write_save(&dir, "a.data");
+	let b = write_save(&dir, "b.data");
+
+	assert_eq!(exit_code(["--help"]), 0);
+	for command in ["check", "stats", "roundtrip", "textconv", "dump"] {
+		assert_eq!(exit_code([command.as_ref(), a.as_os_str()]), 0, "{command}");
+	}
+	assert_eq!(exit_code(["diff".as_ref(), a.as_os_str(), b.as_os_str()]), 0);
+
+	// Without the serde feature, the JSON format is a failure rather than a usage error.
+	let json = exit_code(["dump".as_ref(), "--format".as_ref(), "json".as_ref(), a.as_os_str()]);
+	#[cfg(feature = "serde")]
+	assert_eq!(json, 0);
+	#[cfg(not(feature = "serde"))]
+	assert_eq!(json, 1);
+
+	std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn failure() {
+	let dir = temp_dir("cli-failure");
+	let save = write_save(&dir, "save.data");
+	let missing = dir.join("missing.data");
+	let invalid = dir.join("invalid.data");
+	std::fs::write(&invalid, b"not a save").unwrap();
+
+	for path in [&missing, &invalid] {
+		assert_eq!(exit_code(["check".as_ref(), path.as_os_str()]), 1, "{}", path.display());
+		assert_eq!(exit_code(["diff".as_ref(), save.as_os_str(), path.as_os_str()]), 1, "{}", path.display());
+	}
+
+	// One save that fails fails the whole run, even though the others succeed.
+	assert_eq!(exit_code(["stats".as_ref(), save.as_os_str(), invalid.as_os_str()]), 1);
+
+	std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn usage() {
+	let dir = temp_dir("cli-usage");
+	let save = write_save(&dir, "save.data");
+
+	assert_eq!(exit_code::<[&str; 0], _>([]), 2);
+	assert_eq!(exit_code(["check"]), 2);
+	assert_eq!(exit_code(["frobnicate".as_ref(), save.as_os_str()]), 2);
+	assert_eq!(exit_code(["check".as_ref(), "--format".as_ref(), "svg".as_ref(), save.as_os_str()]), 2);
+	assert_eq!(exit_code(["convert".as_ref(), "--to-version".as_ref(), "11".as_ref(), save.as_os_str()]), 2);
+	assert_eq!(exit_code(["diff".as_ref(), save.as_os_str()]), 2);
+
+	std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,31 @@
+//! Helpers that the tests of the binaries share.
+
+use turing_complete_saves_parser::{
+	builder::{CircuitBuilder, at},
+	v10,
+};
+
+/// An empty directory under the system's temporary directory, unique to the test and the process.
+pub fn temp_dir(name: &str) -> std::path::PathBuf {
+	let dir = std::env::temp_dir().join(format!("turing-complete-saves-parser-{}-{name}", std::process::id()));
+	_ = std::fs::remove_dir_all(&dir);
+	std::fs::create_dir_all(&dir).unwrap();
+	dir
+}
+
+/// A NOT gate between an input and an output.
+pub fn not_gate() -> v10::CircuitData<'static> {
+	let mut builder = CircuitBuilder::new();
+	let input = builder.add(v10::ComponentKind::LevelInput1, at(0, 0));
+	let not = builder.add(v10::ComponentKind::NotBit, at(6, 0));
+	let output = builder.add(v10::ComponentKind::LevelOutput1, at(12, 0));
+	builder.wire(input.out(0), not.input(0));
+	builder.wire(not.out(0), output.input(0));
+	builder.build()
+}
+
+/// Runs the binary with the given arguments, and returns its exit code and what it wrote to stderr.
+pub fn run<I, S>(binary: &str, args: I) -> (i32, String) where I: IntoIterator<Item = S>, S: AsRef<std::ffi::OsStr> {
+	let output = std::process::Command::new(binary).args(args).output().unwrap();
+	(output.status.code().unwrap(), String::from_utf8(output.stderr).unwrap())
+}
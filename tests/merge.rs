@@ -0,0 +1,84 @@
+//! Tests of the exit codes of the merge driver, and of the save that it writes over ours.
+
+mod common;
+
+use common::temp_dir;
+use turing_complete_saves_parser::{
+	Save,
+	v10,
+};
+
+/// Writes the three saves to `dir`, and returns their paths.
+fn write_saves(dir: &std::path::Path, saves: [v10::CircuitData<'static>; 3]) -> [std::path::PathBuf; 3] {
+	let paths = ["base.data", "ours.data", "theirs.data"].map(|name| dir.join(name));
+	for (path, save) in paths.iter().zip(saves) {
+		std::fs::write(path, Save::V10(save).encode_final()).unwrap();
+	}
+	paths
+}
+
+fn merge(paths: &[std::path::PathBuf]) -> (i32, String) {
+	common::run(env!("CARGO_BIN_EXE_turing-complete-saves-merge"), paths)
+}
+
+fn load(path: &std::path::Path) -> v10::CircuitData<'static> {
+	let Save::V10(circuit) = turing_complete_saves_parser::load(path).unwrap() else { panic!("expected a v10 save") };
+	circuit
+}
+
+#[test]
+fn clean() {
+	let dir = temp_dir("merge-clean");
+	let base = common::not_gate();
+	let mut ours = base.clone();
+	ours.description = "ours".into();
+	let mut theirs = base.clone();
+	theirs.clock_speed = 100;
+	let paths = write_saves(&dir, [base, ours, theirs]);
+
+	let (code, stderr) = merge(&paths);
+	assert_eq!(code, 0, "{stderr}");
+	let merged = load(&paths[1]);
+	assert_eq!(merged.description, "ours");
+	assert_eq!(merged.clock_speed, 100);
+
+	std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn conflicting() {
+	let dir = temp_dir("merge-conflicting");
+	let base = common::not_gate();
+	let mut ours = base.clone();
+	ours.description = "ours".into();
+	let mut theirs = base.clone();
+	theirs.description = "theirs".into();
+	theirs.clock_speed = 100;
+	let mut paths = write_saves(&dir, [base, ours, theirs]).to_vec();
+	paths.push("circuit.data".into());
+
+	let (code, stderr) = merge(&paths);
+	assert_eq!(code, 1);
+	assert_eq!(stderr, "circuit.data: conflict: both sides changed description, kept ours\n");
+
+	// The merged save is still written, with ours kept for the conflict.
+	let merged = load(&paths[1]);
+	assert_eq!(merged.description, "ours");
+	assert_eq!(merged.clock_speed, 100);
+
+	std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn failure_and_usage() {
+	let dir = temp_dir("merge-failure");
+	let base = common::not_gate();
+	let paths = write_saves(&dir, [base.clone(), base.clone(), base]);
+
+	let missing = [paths[0].clone(), paths[1].clone(), dir.join("missing.data")];
+	assert_eq!(merge(&missing).0, 1);
+
+	assert_eq!(merge(&paths[..2]).0, 2);
+
+	std::fs::remove_dir_all(&dir).unwrap();
+}